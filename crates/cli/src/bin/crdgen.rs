@@ -0,0 +1,73 @@
+use std::{env, fs, io, path::PathBuf};
+
+use kube::CustomResourceExt;
+
+use dapp_domain_operator::crd as domain;
+use dapp_mesh_operator::crd as mesh;
+use dapp_product_operator::{
+	contract::crd::DataContract,
+	crd as product,
+	flow::crd::DataFlow,
+	port::crd::{InputPort, OutputPort},
+	schema::crd::DataSchema,
+	snapshot::crd::DataSnapshot,
+};
+
+const HEADER: &str = "# Generated by crdgen from the Rust types, do not edit.\n";
+
+/// Renders the CRD manifest of every DappMesh kind, keyed by its file name.
+fn manifests() -> Vec<(&'static str, String)> {
+	vec![
+		("mesh.yaml", render(mesh::crd())),
+		("domain.yaml", render(domain::crd())),
+		("product.yaml", render(product::crd())),
+		("dataschema.yaml", render(DataSchema::crd())),
+		("datacontract.yaml", render(DataContract::crd())),
+		("dataflow.yaml", render(DataFlow::crd())),
+		("datasnapshot.yaml", render(DataSnapshot::crd())),
+		("inputport.yaml", render(InputPort::crd())),
+		("outputport.yaml", render(OutputPort::crd())),
+	]
+}
+
+fn render<T: serde::Serialize>(crd: T) -> String {
+	format!("{}{}", HEADER, serde_yaml::to_string(&crd).expect("CRD serializes to YAML"))
+}
+
+fn default_output_dir() -> PathBuf {
+	PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../manifests/platform/crds")
+}
+
+/// Writes the manifests to the directory given as the only argument, or to
+/// `manifests/platform/crds` by default.
+fn main() -> io::Result<()> {
+	let output_dir = env::args().nth(1).map(PathBuf::from).unwrap_or_else(default_output_dir);
+	fs::create_dir_all(&output_dir)?;
+
+	for (file, manifest) in manifests() {
+		let path = output_dir.join(file);
+		fs::write(&path, manifest)?;
+		println!("Wrote {}", path.display());
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn checked_in_crds_match_the_rust_types() {
+		for (file, manifest) in manifests() {
+			let path = default_output_dir().join(file);
+			let checked_in = fs::read_to_string(&path).unwrap_or_default();
+
+			assert!(
+				checked_in == manifest,
+				"{} is out of date, regenerate it with `cargo run -p dappctl --bin crdgen`",
+				path.display()
+			);
+		}
+	}
+}
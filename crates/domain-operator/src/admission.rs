@@ -0,0 +1,357 @@
+use json_patch::Patch;
+use kube::{api::DynamicObject, core::admission::AdmissionRequest, Api, Client, ResourceExt};
+use serde::de::DeserializeOwned;
+
+use dapp_platform::{
+	core::{
+		hierarchy::ParentReference,
+		ownership::{owning_team, Ownership},
+	},
+	surrealdb::spec::SurrealDBSpec,
+};
+
+use crate::{crd::DappDomain, defaults::ProductDefaults, policy::DomainPolicies};
+
+pub const VALIDATION_PATH: &str = "/validate/dappproducts";
+pub const MUTATION_PATH: &str = "/mutate/dappproducts";
+
+/// Admits a DappProduct if it resolves to an owning team, lives in the namespace and follows the
+/// policies of the DappDomain it refers to. Products whose domain does not exist yet are left to
+/// the product operator, which waits for the domain to be Ready. Products being deleted are
+/// always admitted, so that their finalizer can be removed whatever their domain requires by now.
+pub async fn validate_product(
+	client: Client,
+	request: AdmissionRequest<DynamicObject>,
+) -> Result<(), String> {
+	let Some(product) = &request.object else {
+		return Ok(());
+	};
+	if product.metadata.deletion_timestamp.is_some() {
+		return Ok(());
+	}
+	// An update is judged against the product it replaces while it stays in the same domain.
+	let old = request
+		.old_object
+		.as_ref()
+		.filter(|old| old.data["spec"]["domainRef"] == product.data["spec"]["domainRef"]);
+	if product.data["spec"]["domainRef"].is_null() {
+		return rejection(product, old, "", None).map_or(Ok(()), Err);
+	}
+
+	let Some((name, domain)) = owning_domain(client, &request, product).await? else {
+		return Ok(());
+	};
+	let namespace = product.namespace().or(request.namespace.clone()).unwrap_or_default();
+	rejection(product, old, &namespace, Some((&name, &domain))).map_or(Ok(()), Err)
+}
+
+/// Why `product` in `namespace` is not admitted to `domain`. An update only gets rejected for
+/// what it introduces over `old`, so that existing products stay updatable once their domain
+/// got stricter.
+pub fn rejection(
+	product: &DynamicObject,
+	old: Option<&DynamicObject>,
+	namespace: &str,
+	domain: Option<(&str, &DappDomain)>,
+) -> Option<String> {
+	if let Some(reason) = unowned(product, domain) {
+		if old.map_or(true, |old| unowned(old, domain).is_none()) {
+			return Some(reason);
+		}
+	}
+	let (name, domain) = domain?;
+	// The namespace of a product cannot change, an update is never misplaced by itself.
+	if let Some(reason) = misplaced(namespace, name, domain).filter(|_| old.is_none()) {
+		return Some(reason);
+	}
+	denial(product, old, name, domain.spec.policies.as_ref()?)
+}
+
+/// Fills the fields a new DappProduct leaves unset from the defaults of the DappDomain it
+/// refers to, before its policies are validated.
+pub async fn mutate_product(
+	client: Client,
+	request: AdmissionRequest<DynamicObject>,
+) -> Result<Patch, String> {
+	let Some(product) = &request.object else {
+		return Ok(Patch::default());
+	};
+
+	let Some((name, domain)) = owning_domain(client, &request, product).await? else {
+		return Ok(Patch::default());
+	};
+	match domain.spec.product_defaults {
+		Some(defaults) => defaulted(product, &name, &defaults),
+		None => Ok(Patch::default()),
+	}
+}
+
+/// The patch filling the fields `product` leaves unset from the `defaults` of `domain`.
+pub fn defaulted(
+	product: &DynamicObject,
+	domain: &str,
+	defaults: &ProductDefaults,
+) -> Result<Patch, String> {
+	let original = serde_json::to_value(product).map_err(|error| error.to_string())?;
+	let mut mutated = original.clone();
+	defaults.inject(&mut mutated, domain);
+	Ok(json_patch::diff(&original, &mutated))
+}
+
+/// The DappDomain `product` refers to, with its `namespace/name`.
+async fn owning_domain(
+	client: Client,
+	request: &AdmissionRequest<DynamicObject>,
+	product: &DynamicObject,
+) -> Result<Option<(String, DappDomain)>, String> {
+	let Some(domain_ref) = spec_field::<ParentReference>(product, "domainRef") else {
+		return Ok(None);
+	};
+
+	let namespace = domain_ref.namespace.or(request.namespace.clone()).unwrap_or_default();
+	let domains: Api<DappDomain> = Api::namespaced(client, &namespace);
+	let domain = domains.get_opt(&domain_ref.name).await.map_err(|error| {
+		format!("cannot read DappDomain {}/{}: {}", namespace, domain_ref.name, error)
+	})?;
+
+	Ok(domain.map(|domain| (format!("{}/{}", namespace, domain_ref.name), domain)))
+}
+
+/// Why `product` resolves to no owning team, naming none itself and belonging to no `domain`
+/// that does.
+pub fn unowned(product: &DynamicObject, domain: Option<(&str, &DappDomain)>) -> Option<String> {
+	let owner = spec_field::<Ownership>(product, "owner");
+	let parent = domain.and_then(|(_, domain)| domain.spec.owner.as_ref());
+	if owning_team(owner.as_ref(), parent).is_some() {
+		return None;
+	}
+
+	let hint = match domain {
+		Some((name, _)) => format!("set spec.owner.team or the owner team of DappDomain {}", name),
+		None => "set spec.owner.team or refer to a DappDomain with an owner team".to_string(),
+	};
+	Some(format!("DappProduct {} has no owning team: {}", product_name(product), hint))
+}
+
+/// Why a DappProduct in `namespace` cannot belong to `domain`, which may dedicate a namespace
+/// to its products.
+pub fn misplaced(namespace: &str, name: &str, domain: &DappDomain) -> Option<String> {
+	let dedicated = domain.spec.namespace.as_ref()?.name(&domain.name_any());
+	(dedicated != namespace).then(|| {
+		format!("DappProducts of DappDomain {} have to be created in namespace {}", name, dedicated)
+	})
+}
+
+/// Why `product` is denied by the policies of `domain`, listing every violation at once. The
+/// violations `old` has already are left out.
+pub fn denial(
+	product: &DynamicObject,
+	old: Option<&DynamicObject>,
+	domain: &str,
+	policies: &DomainPolicies,
+) -> Option<String> {
+	let name = product_name(product);
+	let existing = old.map(|old| violations(old, policies)).unwrap_or_default();
+
+	let mut violations = violations(product, policies);
+	violations.retain(|violation| !existing.contains(violation));
+	if violations.is_empty() {
+		return None;
+	}
+
+	Some(format!(
+		"DappProduct {} violates the policies of DappDomain {}: {}",
+		name,
+		domain,
+		violations.join("; ")
+	))
+}
+
+fn violations(product: &DynamicObject, policies: &DomainPolicies) -> Vec<String> {
+	let database = spec_field::<SurrealDBSpec>(product, "database");
+	policies.violations(&product_name(product), product.labels(), database.as_ref())
+}
+
+/// The name of `product`, or its prefix when the API server generates it.
+fn product_name(product: &DynamicObject) -> String {
+	let name = product.metadata.name.clone().or(product.metadata.generate_name.clone());
+	name.unwrap_or_default()
+}
+
+fn spec_field<T: DeserializeOwned>(object: &DynamicObject, field: &str) -> Option<T> {
+	let value = object.data.get("spec")?.get(field)?.clone();
+	serde_json::from_value(value).ok()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		crd::DappDomainSpec, defaults::DEFAULTED_FROM_ANNOTATION, tenancy::DomainNamespace,
+	};
+	use hyper::{Request, Response};
+	use kube::{api::ApiResource, client::Body, core::admission::AdmissionReview};
+	use serde_json::json;
+
+	fn product(labels: serde_json::Value, database: serde_json::Value) -> DynamicObject {
+		let resource = ApiResource {
+			group: "dappmesh.io".to_string(),
+			version: "v1alpha1".to_string(),
+			api_version: "dappmesh.io/v1alpha1".to_string(),
+			kind: "DappProduct".to_string(),
+			plural: "dappproducts".to_string(),
+		};
+		let mut product = DynamicObject::new("sales-orders", &resource)
+			.data(json!({ "spec": { "domainRef": { "name": "sales" }, "database": database } }));
+		product.metadata.labels = serde_json::from_value(labels).unwrap();
+		product
+	}
+
+	fn policies() -> DomainPolicies {
+		DomainPolicies {
+			required_labels: vec!["dappmesh.io/owner".to_string()],
+			max_storage_size: Some("10Gi".to_string()),
+			..DomainPolicies::default()
+		}
+	}
+
+	#[test]
+	fn compliant_products_are_admitted() {
+		let product = product(json!({ "dappmesh.io/owner": "team-orders" }), json!({}));
+
+		assert_eq!(denial(&product, None, "shop/sales", &policies()), None);
+	}
+
+	#[test]
+	fn denials_name_the_domain_and_every_violation() {
+		let product = product(json!({}), json!({ "storage": { "size": "20Gi" } }));
+
+		assert_eq!(
+			denial(&product, None, "shop/sales", &policies()).unwrap(),
+			"DappProduct sales-orders violates the policies of DappDomain shop/sales: \
+			 label dappmesh.io/owner is required; storage size 20Gi exceeds the maximum of 10Gi"
+		);
+	}
+
+	fn strict_domain() -> DappDomain {
+		let mut domain = DappDomain::new("sales", DappDomainSpec::default());
+		domain.spec.namespace = Some(DomainNamespace::default());
+		domain.spec.policies = Some(policies());
+		domain.spec.owner = Some(Ownership {
+			team: Some("sales".to_string()),
+			..Ownership::default()
+		});
+		domain
+	}
+
+	#[test]
+	fn updates_are_only_denied_for_violations_they_introduce() {
+		let domain = strict_domain();
+		let old = product(json!({}), json!({}));
+		let mut relabelled = old.clone();
+		relabelled.labels_mut().insert("tier".to_string(), "gold".to_string());
+		let mut grown = old.clone();
+		grown.data["spec"]["database"] = json!({ "storage": { "size": "20Gi" } });
+
+		let domain = Some(("shop/sales", &domain));
+		assert!(rejection(&relabelled, None, "shop", domain).is_some());
+		assert_eq!(rejection(&relabelled, Some(&old), "shop", domain), None);
+		assert_eq!(
+			rejection(&grown, Some(&old), "shop", domain).unwrap(),
+			"DappProduct sales-orders violates the policies of DappDomain shop/sales: \
+			 storage size 20Gi exceeds the maximum of 10Gi"
+		);
+	}
+
+	#[tokio::test]
+	async fn deleted_products_are_admitted_without_looking_up_their_domain() {
+		let review: AdmissionReview<DynamicObject> = serde_json::from_value(json!({
+			"apiVersion": "admission.k8s.io/v1",
+			"kind": "AdmissionReview",
+			"request": {
+				"uid": "42",
+				"kind": { "group": "dappmesh.io", "version": "v1alpha1", "kind": "DappProduct" },
+				"resource": { "group": "dappmesh.io", "version": "v1alpha1", "resource": "dappproducts" },
+				"name": "sales-orders",
+				"namespace": "shop",
+				"operation": "UPDATE",
+				"userInfo": {},
+				"object": {
+					"apiVersion": "dappmesh.io/v1alpha1",
+					"kind": "DappProduct",
+					"metadata": {
+						"name": "sales-orders",
+						"namespace": "shop",
+						"deletionTimestamp": "2024-05-01T00:00:00Z",
+					},
+					"spec": { "domainRef": { "name": "sales" } },
+				},
+				"dryRun": false,
+			},
+		}))
+		.unwrap();
+		// Any request to the API server fails, as nothing answers it.
+		let (service, _) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+
+		let request = review.try_into().unwrap();
+
+		assert_eq!(validate_product(Client::new(service, "default"), request).await, Ok(()));
+	}
+
+	#[test]
+	fn products_belong_in_the_namespace_of_their_domain() {
+		let mut domain = DappDomain::new("sales", DappDomainSpec::default());
+		assert_eq!(misplaced("shop", "shop/sales", &domain), None);
+
+		domain.spec.namespace = Some(DomainNamespace::default());
+
+		assert_eq!(misplaced("sales", "shop/sales", &domain), None);
+		assert_eq!(
+			misplaced("shop", "shop/sales", &domain).unwrap(),
+			"DappProducts of DappDomain shop/sales have to be created in namespace sales"
+		);
+	}
+
+	#[test]
+	fn products_resolve_to_an_owning_team() {
+		let orphan = product(json!({}), json!({}));
+		let mut owned = orphan.clone();
+		owned.data["spec"]["owner"] = json!({ "team": "orders" });
+		let mut domain = DappDomain::new("sales", DappDomainSpec::default());
+
+		assert_eq!(unowned(&owned, None), None);
+		assert_eq!(
+			unowned(&orphan, Some(("shop/sales", &domain))).unwrap(),
+			"DappProduct sales-orders has no owning team: \
+			 set spec.owner.team or the owner team of DappDomain shop/sales"
+		);
+
+		domain.spec.owner = Some(Ownership {
+			team: Some("sales".to_string()),
+			..Ownership::default()
+		});
+
+		assert_eq!(unowned(&orphan, Some(("shop/sales", &domain))), None);
+	}
+
+	#[test]
+	fn defaults_are_patched_into_the_product() {
+		let product = product(json!({}), json!({ "storage": { "size": "20Gi" } }));
+		let defaults = ProductDefaults {
+			labels: [("dappmesh.io/owner".to_string(), "team-sales".to_string())].into(),
+			..ProductDefaults::default()
+		};
+
+		let mut patched = serde_json::to_value(&product).unwrap();
+		let patch = defaulted(&product, "shop/sales", &defaults).unwrap();
+		json_patch::patch(&mut patched, &patch).unwrap();
+
+		assert_eq!(patched["metadata"]["labels"]["dappmesh.io/owner"], "team-sales");
+		assert_eq!(patched["metadata"]["annotations"][DEFAULTED_FROM_ANNOTATION], "shop/sales");
+		assert_eq!(patched["spec"]["database"]["storage"]["size"], "20Gi");
+		assert!(defaulted(&product, "shop/sales", &ProductDefaults::default())
+			.unwrap()
+			.0
+			.is_empty());
+	}
+}
@@ -1,8 +1,26 @@
-use kube::CustomResource;
+pub mod v1beta1;
+
+use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition;
+use kube::{core::crd::merge_crds, CustomResource, CustomResourceExt};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use dapp_platform::{
+	core::{
+		constants::{DOMAIN_OPERATOR, OPERATOR_NAMESPACE},
+		conversion::{self, with_webhook, ConversionError},
+		hierarchy::ParentReference,
+		operator::OperatorResource,
+		ownership::Ownership,
+		security::SecurityContextOverrides,
+		status::Condition,
+		validation::{optional_schema, DNS_SUBDOMAIN},
+	},
+	surrealdb::spec::SurrealDBSpec,
+};
 
-use dapp_platform::core::operator::OperatorResource;
+use crate::{defaults::ProductDefaults, policy::DomainPolicies, tenancy::DomainNamespace};
 
 #[derive(
 	CustomResource, Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema,
@@ -17,16 +35,66 @@ use dapp_platform::core::operator::OperatorResource;
 	plural = "dappdomains",
 	shortname = "domain",
 	shortname = "domains",
+	status = "DappDomainStatus",
 	derive = "PartialEq",
 	derive = "Default"
 )]
+#[serde(rename_all = "camelCase")]
 pub struct DappDomainSpec {
-	#[schemars(regex(pattern = r"[A-Za-z0-9](?:[A-Za-z0-9\-]{0,61}[A-Za-z0-9])?"))]
+	/// Repeats `metadata.name` and is dropped in v1beta1.
+	#[schemars(regex = "DNS_SUBDOMAIN", length(min = 1, max = 253))]
 	name: String,
+	/// The DappMesh the resource belongs to, which has to be Ready before it is provisioned.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub mesh_ref: Option<ParentReference>,
+	/// Overrides for the hardened security context of the generated workloads.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub security_context: Option<SecurityContextOverrides>,
+	/// Replication and scheduling of the SurrealDB instance backing this resource.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schemars(schema_with = "optional_schema::<SurrealDBSpec>")]
+	pub database: Option<SurrealDBSpec>,
+	/// Governance the DappProducts of the domain are checked against.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub policies: Option<DomainPolicies>,
+	/// Settings filled into the DappProducts of the domain that leave them unset.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub product_defaults: Option<ProductDefaults>,
+	/// The namespace dedicated to the domain, with its quota, roles and network policy.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub namespace: Option<DomainNamespace>,
+	/// The team accountable for the domain and, unless they name their own, its products.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub owner: Option<Ownership>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+pub struct DappDomainStatus {
+	/// Number of DappProducts referring to this resource.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub products: Option<i32>,
+	#[serde(default)]
+	pub conditions: Vec<Condition>,
 }
 
 impl OperatorResource for DappDomain {}
 
+/// Objects are stored as v1alpha1 until every one of them has been migrated to v1beta1.
+pub const STORAGE_VERSION: &str = "v1alpha1";
+pub const CONVERSION_PATH: &str = "/convert/dappdomains";
+
+/// The CRD serving every version of DappDomain, converted by the webhook of the domain operator.
+pub fn crd() -> CustomResourceDefinition {
+	let crd = merge_crds(vec![DappDomain::crd(), v1beta1::DappDomain::crd()], STORAGE_VERSION)
+		.expect("versions of DappDomain have the same names and scope");
+	with_webhook(crd, DOMAIN_OPERATOR, OPERATOR_NAMESPACE, CONVERSION_PATH)
+}
+
+/// Converts a DappDomain object between v1alpha1 and v1beta1.
+pub fn convert(object: Value, desired_api_version: &str) -> Result<Value, ConversionError> {
+	conversion::convert::<DappDomain, v1beta1::DappDomain>(object, desired_api_version)
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -56,4 +124,31 @@ mod tests {
 			self
 		}
 	}
+
+	#[test]
+	fn v1alpha1_objects_round_trip_through_v1beta1() {
+		let mut domain = DappDomain::test_instance();
+		domain.spec.name = domain.name_any();
+		let object = serde_json::to_value(&domain).unwrap();
+
+		let beta = convert(object.clone(), "dappmesh.io/v1beta1").unwrap();
+		assert_eq!(beta["apiVersion"], "dappmesh.io/v1beta1");
+		assert!(beta["spec"].get("name").is_none());
+
+		assert_eq!(convert(beta, "dappmesh.io/v1alpha1").unwrap(), object);
+	}
+
+	#[test]
+	fn both_versions_are_served_and_v1alpha1_is_stored() {
+		let crd = crd();
+
+		let versions: Vec<_> = crd
+			.spec
+			.versions
+			.iter()
+			.map(|version| (version.name.as_str(), version.storage))
+			.collect();
+		assert_eq!(versions, vec![("v1alpha1", true), ("v1beta1", false)]);
+		assert!(crd.spec.conversion.is_some());
+	}
 }
@@ -0,0 +1,95 @@
+use kube::CustomResource;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use dapp_platform::{
+	core::{
+		hierarchy::ParentReference, ownership::Ownership, security::SecurityContextOverrides,
+		validation::optional_schema,
+	},
+	surrealdb::spec::SurrealDBSpec,
+};
+
+use super::DappDomainStatus;
+use crate::{defaults::ProductDefaults, policy::DomainPolicies, tenancy::DomainNamespace};
+
+#[derive(
+	CustomResource, Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema,
+)]
+#[kube(
+	doc = "DappMesh domain controller custom resource.",
+	group = "dappmesh.io",
+	version = "v1beta1",
+	kind = "DappDomain",
+	namespaced,
+	singular = "dappdomain",
+	plural = "dappdomains",
+	shortname = "domain",
+	shortname = "domains",
+	status = "DappDomainStatus",
+	derive = "PartialEq",
+	derive = "Default"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct DappDomainSpec {
+	/// The DappMesh the resource belongs to, which has to be Ready before it is provisioned.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub mesh_ref: Option<ParentReference>,
+	/// Overrides for the hardened security context of the generated workloads.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub security_context: Option<SecurityContextOverrides>,
+	/// Replication and scheduling of the SurrealDB instance backing this resource.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schemars(schema_with = "optional_schema::<SurrealDBSpec>")]
+	pub database: Option<SurrealDBSpec>,
+	/// Governance the DappProducts of the domain are checked against.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub policies: Option<DomainPolicies>,
+	/// Settings filled into the DappProducts of the domain that leave them unset.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub product_defaults: Option<ProductDefaults>,
+	/// The namespace dedicated to the domain, with its quota, roles and network policy.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub namespace: Option<DomainNamespace>,
+	/// The team accountable for the domain and, unless they name their own, its products.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub owner: Option<Ownership>,
+}
+
+impl From<super::DappDomain> for DappDomain {
+	fn from(domain: super::DappDomain) -> Self {
+		Self {
+			metadata: domain.metadata,
+			spec: DappDomainSpec {
+				mesh_ref: domain.spec.mesh_ref,
+				security_context: domain.spec.security_context,
+				database: domain.spec.database,
+				policies: domain.spec.policies,
+				product_defaults: domain.spec.product_defaults,
+				namespace: domain.spec.namespace,
+				owner: domain.spec.owner,
+			},
+			status: domain.status,
+		}
+	}
+}
+
+/// Restores the `name` that `v1alpha1` repeats from the metadata.
+impl From<DappDomain> for super::DappDomain {
+	fn from(domain: DappDomain) -> Self {
+		Self {
+			spec: super::DappDomainSpec {
+				name: domain.metadata.name.clone().unwrap_or_default(),
+				mesh_ref: domain.spec.mesh_ref,
+				security_context: domain.spec.security_context,
+				database: domain.spec.database,
+				policies: domain.spec.policies,
+				product_defaults: domain.spec.product_defaults,
+				namespace: domain.spec.namespace,
+				owner: domain.spec.owner,
+			},
+			metadata: domain.metadata,
+			status: domain.status,
+		}
+	}
+}
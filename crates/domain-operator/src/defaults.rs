@@ -0,0 +1,166 @@
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use dapp_platform::{core::validation::optional_schema, surrealdb::spec::SurrealDBSpec};
+
+/// Names the DappDomain whose defaults were injected into a product.
+pub const DEFAULTED_FROM_ANNOTATION: &str = "dappmesh.io/defaulted-from";
+/// Lists the JSON pointers of the fields that were injected, comma separated.
+pub const DEFAULTED_FIELDS_ANNOTATION: &str = "dappmesh.io/defaulted-fields";
+
+/// Settings the DappProducts of the domain get unless they set them themselves.
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ProductDefaults {
+	/// Added to the labels of products that do not have them, e.g. `dappmesh.io/owner`.
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	pub labels: BTreeMap<String, String>,
+	/// Fills the database settings a product leaves unset, field by field.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schemars(schema_with = "optional_schema::<SurrealDBSpec>")]
+	pub database: Option<SurrealDBSpec>,
+}
+
+impl ProductDefaults {
+	/// Fills the fields `product` leaves unset and stamps where they came from, returning
+	/// the pointers of the injected fields.
+	pub fn inject(&self, product: &mut Value, domain: &str) -> Vec<String> {
+		let template = json!({
+			"metadata": { "labels": self.labels },
+			"spec": { "database": self.database },
+		});
+		let mut injected = Vec::new();
+		fill(product, &template, "", &mut injected);
+
+		if !injected.is_empty() {
+			let annotations = json!({
+				"metadata": { "annotations": {
+					DEFAULTED_FROM_ANNOTATION: domain,
+					DEFAULTED_FIELDS_ANNOTATION: injected.join(","),
+				}},
+			});
+			merge(product, annotations);
+		}
+
+		injected
+	}
+}
+
+/// Copies the leaves of `defaults` missing from `target`, recording their pointers.
+fn fill(target: &mut Value, defaults: &Value, pointer: &str, injected: &mut Vec<String>) {
+	let Value::Object(defaults) = defaults else {
+		return;
+	};
+
+	for (key, default) in defaults {
+		if default.is_null() || default.as_object().is_some_and(Map::is_empty) {
+			continue;
+		}
+		let Some(target) = target.as_object_mut() else {
+			return;
+		};
+
+		let pointer = format!("{}/{}", pointer, key.replace('~', "~0").replace('/', "~1"));
+		match target.get_mut(key) {
+			Some(value) if !value.is_null() => {
+				if default.is_object() {
+					fill(value, default, &pointer, injected);
+				}
+			}
+			_ if default.is_object() => {
+				target.insert(key.clone(), Value::Object(Map::new()));
+				fill(&mut target[key], default, &pointer, injected);
+				if target[key].as_object().is_some_and(Map::is_empty) {
+					target.remove(key);
+				}
+			}
+			_ => {
+				target.insert(key.clone(), default.clone());
+				injected.push(pointer);
+			}
+		}
+	}
+}
+
+/// Merges `patch` into `target`, replacing what both of them set.
+fn merge(target: &mut Value, patch: Value) {
+	match (target, patch) {
+		(Value::Object(target), Value::Object(patch)) => {
+			for (key, value) in patch {
+				merge(target.entry(key).or_insert(Value::Null), value);
+			}
+		}
+		(target, patch) => *target = patch,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use dapp_platform::surrealdb::spec::StorageSpec;
+
+	fn defaults() -> ProductDefaults {
+		ProductDefaults {
+			labels: BTreeMap::from([
+				("dappmesh.io/owner".to_string(), "team-sales".to_string()),
+				("tier".to_string(), "gold".to_string()),
+			]),
+			database: Some(SurrealDBSpec {
+				replicas: Some(3),
+				storage: Some(StorageSpec {
+					size: Some("20Gi".to_string()),
+					class_name: Some("fast-ssd".to_string()),
+				}),
+				..SurrealDBSpec::default()
+			}),
+		}
+	}
+
+	#[test]
+	fn unset_fields_are_filled_and_their_provenance_stamped() {
+		let mut product = json!({
+			"metadata": { "name": "orders", "labels": { "tier": "silver" } },
+			"spec": { "database": { "storage": { "size": "5Gi" } } },
+		});
+
+		let injected = defaults().inject(&mut product, "shop/sales");
+
+		assert_eq!(
+			injected,
+			vec![
+				"/metadata/labels/dappmesh.io~1owner",
+				"/spec/database/replicas",
+				"/spec/database/storage/className",
+			]
+		);
+		assert_eq!(product["metadata"]["labels"]["tier"], "silver");
+		assert_eq!(product["metadata"]["labels"]["dappmesh.io/owner"], "team-sales");
+		assert_eq!(product["spec"]["database"]["replicas"], 3);
+		assert_eq!(product["spec"]["database"]["storage"]["size"], "5Gi");
+		assert_eq!(product["spec"]["database"]["storage"]["className"], "fast-ssd");
+		assert_eq!(product["metadata"]["annotations"][DEFAULTED_FROM_ANNOTATION], "shop/sales");
+		assert_eq!(
+			product["metadata"]["annotations"][DEFAULTED_FIELDS_ANNOTATION],
+			"/metadata/labels/dappmesh.io~1owner,/spec/database/replicas,/spec/database/storage/className"
+		);
+	}
+
+	#[test]
+	fn products_setting_everything_are_left_untouched() {
+		let mut product = json!({
+			"metadata": { "name": "orders", "labels": { "tier": "silver", "dappmesh.io/owner": "x" } },
+			"spec": { "database": { "replicas": 1, "storage": { "size": "5Gi", "className": "standard" } } },
+		});
+		let original = product.clone();
+
+		assert!(defaults().inject(&mut product, "shop/sales").is_empty());
+		assert_eq!(product, original);
+
+		let mut empty = json!({});
+		assert!(ProductDefaults::default().inject(&mut empty, "shop/sales").is_empty());
+		assert_eq!(empty, json!({}));
+	}
+}
@@ -0,0 +1,396 @@
+use kube::{
+	api::{ApiResource, DynamicObject, GroupVersionKind, ListParams},
+	Api, Client, ResourceExt,
+};
+use serde_json::Value;
+
+use dapp_platform::core::{
+	governance::{
+		compliance, DataClassification, DataGovernance, GovernanceRule, PolicyEngine, COMPLIANT,
+	},
+	hierarchy::{refers_to, Level, GROUP, VERSION},
+	operator::OperatorError,
+	quantity::seconds,
+	status::{publish_condition, Condition},
+};
+
+use crate::policy::{DomainPolicies, PiiPolicy};
+
+/// A DappProduct of the domain with the DataContracts it provides.
+#[derive(Debug, Default, Clone)]
+pub struct GovernedProduct {
+	pub name: String,
+	pub namespace: String,
+	pub governance: DataGovernance,
+	/// The specs of the DataContracts, by name.
+	pub contracts: Vec<(String, Value)>,
+}
+
+impl GovernedProduct {
+	/// Picks the contracts of `product` out of `contracts`.
+	pub fn new(product: &DynamicObject, contracts: &[DynamicObject]) -> Self {
+		let name = product.name_any();
+		let namespace = product.namespace().unwrap_or_default();
+		let governance =
+			serde_json::from_value(product.data["spec"]["governance"].clone()).unwrap_or_default();
+		let contracts = contracts
+			.iter()
+			.filter(|contract| contract.namespace().as_deref() == Some(&namespace))
+			.filter(|contract| contract.data["spec"]["product"] == name.as_str())
+			.map(|contract| (contract.name_any(), contract.data["spec"].clone()))
+			.collect();
+
+		Self {
+			name,
+			namespace,
+			governance,
+			contracts,
+		}
+	}
+}
+
+/// The built-in rules for the `policies` of a domain. Organization specific rules are added
+/// with [`PolicyEngine::rule`].
+pub fn engine(policies: &DomainPolicies) -> PolicyEngine<GovernedProduct> {
+	let mut engine = PolicyEngine::default();
+	if !policies.classifications.is_empty() {
+		engine = engine.rule(Classification(policies.classifications.clone()));
+	}
+	if let Some(pii) = &policies.pii {
+		engine = engine.rule(PiiHandling(pii.clone()));
+	}
+	if !policies.required_contract_fields.is_empty() {
+		engine = engine.rule(ContractFields(policies.required_contract_fields.clone()));
+	}
+	if let Some(max) = &policies.max_retention {
+		engine = engine.rule(Retention(max.clone()));
+	}
+	engine
+}
+
+pub struct Classification(pub Vec<DataClassification>);
+
+impl GovernanceRule<GovernedProduct> for Classification {
+	fn name(&self) -> &str {
+		"Classification"
+	}
+
+	fn check(&self, product: &GovernedProduct) -> Vec<String> {
+		let allowed = self.0.iter().map(|level| format!("{:?}", level)).collect::<Vec<_>>();
+		match product.governance.classification {
+			Some(level) if self.0.contains(&level) => Vec::new(),
+			Some(level) => vec![format!(
+				"classification {:?} is not allowed, use one of {}",
+				level,
+				allowed.join(", ")
+			)],
+			None => {
+				vec![format!("a classification is required, use one of {}", allowed.join(", "))]
+			}
+		}
+	}
+}
+
+pub struct PiiHandling(pub PiiPolicy);
+
+impl GovernanceRule<GovernedProduct> for PiiHandling {
+	fn name(&self) -> &str {
+		"PiiHandling"
+	}
+
+	fn check(&self, product: &GovernedProduct) -> Vec<String> {
+		let governance = &product.governance;
+		if !governance.pii {
+			return Vec::new();
+		}
+		if self.0.forbidden {
+			return vec!["personal data is not allowed in the domain".to_string()];
+		}
+
+		let mut violations = Vec::new();
+		if let Some(minimum) = self.0.minimum_classification {
+			if governance.classification.map_or(true, |level| level < minimum) {
+				violations
+					.push(format!("personal data has to be classified at least {:?}", minimum));
+			}
+		}
+		if let Some(max) = &self.0.max_retention {
+			violations.extend(retention_violation(governance, max, "personal data"));
+		}
+		violations
+	}
+}
+
+pub struct ContractFields(pub Vec<String>);
+
+impl GovernanceRule<GovernedProduct> for ContractFields {
+	fn name(&self) -> &str {
+		"ContractFields"
+	}
+
+	fn check(&self, product: &GovernedProduct) -> Vec<String> {
+		let mut violations = Vec::new();
+		for (contract, spec) in &product.contracts {
+			for field in &self.0 {
+				let value = field.split('.').fold(spec, |value, key| &value[key]);
+				if value.is_null() {
+					violations.push(format!("DataContract {} does not set {}", contract, field));
+				}
+			}
+		}
+		violations
+	}
+}
+
+pub struct Retention(pub String);
+
+impl GovernanceRule<GovernedProduct> for Retention {
+	fn name(&self) -> &str {
+		"Retention"
+	}
+
+	fn check(&self, product: &GovernedProduct) -> Vec<String> {
+		retention_violation(&product.governance, &self.0, "data").into_iter().collect()
+	}
+}
+
+fn retention_violation(governance: &DataGovernance, max: &str, data: &str) -> Option<String> {
+	match &governance.retention {
+		Some(retention) => match (seconds(retention), seconds(max)) {
+			(Some(kept), Some(limit)) if kept > limit => {
+				Some(format!("retention {} of {} exceeds the maximum of {}", retention, data, max))
+			}
+			_ => None,
+		},
+		None => Some(format!("a retention of at most {} is required for {}", max, data)),
+	}
+}
+
+/// Evaluates the products of a domain against its policies, publishing the outcome as the
+/// Compliant condition of every product and of the domain.
+pub struct Governance {
+	client: Client,
+	name: String,
+	namespace: String,
+	engine: PolicyEngine<GovernedProduct>,
+	dry_run: bool,
+}
+
+impl Governance {
+	pub fn new(
+		client: Client,
+		name: &str,
+		namespace: &str,
+		engine: PolicyEngine<GovernedProduct>,
+	) -> Self {
+		Self {
+			client,
+			name: name.to_string(),
+			namespace: namespace.to_string(),
+			engine,
+			dry_run: false,
+		}
+	}
+
+	/// Evaluates the products without publishing the outcome.
+	pub fn dry_run(mut self, dry_run: bool) -> Self {
+		self.dry_run = dry_run;
+		self
+	}
+
+	pub async fn enforce(&self) -> Result<(), OperatorError> {
+		let products: Api<DynamicObject> =
+			Api::all_with(self.client.clone(), &Level::Product.api_resource());
+		let contracts: Api<DynamicObject> =
+			Api::all_with(self.client.clone(), &data_contract_resource());
+
+		let products: Vec<DynamicObject> = products
+			.list(&ListParams::default())
+			.await?
+			.into_iter()
+			.filter(|product| refers_to(product, "domainRef", &self.name, &self.namespace))
+			.collect();
+		let contracts = contracts.list(&ListParams::default()).await?.items;
+
+		let mut violating = Vec::new();
+		for product in &products {
+			let product = GovernedProduct::new(product, &contracts);
+			let violations = self.engine.evaluate(&product);
+			if !violations.is_empty() {
+				violating.push(format!("{}/{}", product.namespace, product.name));
+			}
+			if !self.dry_run {
+				let api: Api<DynamicObject> = Api::namespaced_with(
+					self.client.clone(),
+					&product.namespace,
+					&Level::Product.api_resource(),
+				);
+				publish_condition(&api, &product.name, compliance(&violations)).await?;
+			}
+		}
+
+		if !self.dry_run {
+			let api: Api<DynamicObject> = Api::namespaced_with(
+				self.client.clone(),
+				&self.namespace,
+				&Level::Domain.api_resource(),
+			);
+			publish_condition(&api, &self.name, domain_compliance(products.len(), &violating))
+				.await?;
+		}
+		Ok(())
+	}
+}
+
+/// The Compliant condition of a domain with `products`, of which `violating` break a policy.
+fn domain_compliance(products: usize, violating: &[String]) -> Condition {
+	if violating.is_empty() {
+		let message = format!("All {} DappProducts meet the policies.", products);
+		return Condition::new(COMPLIANT, true, "PoliciesMet", &message);
+	}
+
+	let message = format!(
+		"{} of {} DappProducts violate the policies: {}.",
+		violating.len(),
+		products,
+		violating.join(", ")
+	);
+	Condition::new(COMPLIANT, false, "PolicyViolation", &message)
+}
+
+fn data_contract_resource() -> ApiResource {
+	let gvk = GroupVersionKind::gvk(GROUP, VERSION, "DataContract");
+	ApiResource::from_gvk_with_plural(&gvk, "datacontracts")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json::json;
+
+	fn product(governance: Value) -> GovernedProduct {
+		GovernedProduct {
+			name: "orders".to_string(),
+			namespace: "sales".to_string(),
+			governance: serde_json::from_value(governance).unwrap(),
+			contracts: vec![(
+				"orders-v1".to_string(),
+				json!({ "product": "orders", "terms": { "usage": "analytics" } }),
+			)],
+		}
+	}
+
+	fn policies() -> DomainPolicies {
+		DomainPolicies {
+			classifications: vec![DataClassification::Internal, DataClassification::Confidential],
+			pii: Some(PiiPolicy {
+				minimum_classification: Some(DataClassification::Confidential),
+				max_retention: Some("30d".to_string()),
+				..PiiPolicy::default()
+			}),
+			required_contract_fields: vec!["terms.usage".to_string(), "terms.billing".to_string()],
+			max_retention: Some("365d".to_string()),
+			..DomainPolicies::default()
+		}
+	}
+
+	fn messages(product: &GovernedProduct) -> Vec<String> {
+		engine(&policies()).evaluate(product).into_iter().map(|v| v.message).collect()
+	}
+
+	#[test]
+	fn compliant_products_only_miss_contract_fields_they_do_not_set() {
+		let product = product(json!({ "classification": "Confidential", "retention": "90d" }));
+
+		assert_eq!(messages(&product), vec!["DataContract orders-v1 does not set terms.billing"]);
+	}
+
+	#[test]
+	fn every_policy_is_evaluated() {
+		let product =
+			product(json!({ "classification": "Internal", "pii": true, "retention": "400d" }));
+
+		let violations = engine(&policies()).evaluate(&product);
+
+		assert_eq!(
+			violations.iter().map(|violation| violation.rule.as_str()).collect::<Vec<_>>(),
+			vec!["PiiHandling", "PiiHandling", "ContractFields", "Retention"]
+		);
+		assert_eq!(
+			messages(&product),
+			vec![
+				"personal data has to be classified at least Confidential",
+				"retention 400d of personal data exceeds the maximum of 30d",
+				"DataContract orders-v1 does not set terms.billing",
+				"retention 400d of data exceeds the maximum of 365d",
+			]
+		);
+	}
+
+	#[test]
+	fn undeclared_data_is_reported() {
+		let product = product(json!({}));
+
+		assert_eq!(
+			messages(&product),
+			vec![
+				"a classification is required, use one of Internal, Confidential",
+				"DataContract orders-v1 does not set terms.billing",
+				"a retention of at most 365d is required for data",
+			]
+		);
+	}
+
+	#[test]
+	fn forbidden_personal_data_is_reported_alone() {
+		let policies = DomainPolicies {
+			pii: Some(PiiPolicy {
+				forbidden: true,
+				..PiiPolicy::default()
+			}),
+			..DomainPolicies::default()
+		};
+
+		let violations = engine(&policies).evaluate(&product(json!({ "pii": true })));
+
+		assert_eq!(violations.len(), 1);
+		assert_eq!(violations[0].message, "personal data is not allowed in the domain");
+		assert!(engine(&DomainPolicies::default()).evaluate(&product(json!({}))).is_empty());
+	}
+
+	#[test]
+	fn products_pick_their_own_contracts() {
+		let resource = data_contract_resource();
+		let contract = |name: &str, namespace: &str, product: &str| {
+			let mut contract =
+				DynamicObject::new(name, &resource).data(json!({ "spec": { "product": product } }));
+			contract.metadata.namespace = Some(namespace.to_string());
+			contract
+		};
+		let mut object = DynamicObject::new("orders", &Level::Product.api_resource())
+			.data(json!({ "spec": { "governance": { "pii": true } } }));
+		object.metadata.namespace = Some("sales".to_string());
+
+		let product = GovernedProduct::new(
+			&object,
+			&[
+				contract("orders-v1", "sales", "orders"),
+				contract("orders-v1", "crm", "orders"),
+				contract("invoices-v1", "sales", "invoices"),
+			],
+		);
+
+		assert!(product.governance.pii);
+		assert_eq!(product.contracts.len(), 1);
+		assert_eq!(product.contracts[0].0, "orders-v1");
+	}
+
+	#[test]
+	fn domains_name_their_violating_products() {
+		let condition = domain_compliance(3, &["sales/orders".to_string()]);
+
+		assert!(!condition.is_true());
+		assert_eq!(condition.message, "1 of 3 DappProducts violate the policies: sales/orders.");
+		assert!(domain_compliance(3, &[]).is_true());
+	}
+}
@@ -1,5 +1,10 @@
+pub mod admission;
 pub mod crd;
+pub mod defaults;
+pub mod governance;
 pub mod operator;
+pub mod policy;
+pub mod tenancy;
 
 #[cfg(test)]
 mod tests;
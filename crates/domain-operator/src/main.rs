@@ -1,12 +1,20 @@
-use kube::{runtime::controller::Action, ResourceExt};
+use kube::{runtime::controller::Action, Client, ResourceExt};
 use std::sync::Arc;
+use tracing::error;
 
-use dapp_domain_operator::{crd::DappDomain, operator::DomainOperatorController};
-use dapp_platform::core::operator::{Operator, OperatorContext, OperatorController, OperatorError};
+use dapp_domain_operator::{
+	admission::{mutate_product, validate_product, MUTATION_PATH, VALIDATION_PATH},
+	crd::{convert, DappDomain, CONVERSION_PATH},
+	operator::DomainOperatorController,
+};
+use dapp_platform::core::{
+	operator::{Operator, OperatorContext, OperatorController, OperatorError},
+	webhook::{WebhookServer, DEFAULT_WEBHOOK_PORT},
+};
 
 #[tokio::main]
 async fn main() {
-	Operator::run(reconcile).await;
+	tokio::join!(Operator::run(reconcile), serve_webhooks());
 }
 
 async fn reconcile(
@@ -14,10 +22,36 @@ async fn reconcile(
 	context: Arc<OperatorContext>,
 ) -> Result<Action, OperatorError> {
 	if let Some(namespace) = resource.namespace() {
-		let controller =
-			DomainOperatorController::new(resource.name_any(), namespace, context.client.clone());
+		let controller = DomainOperatorController::new(
+			resource.name_any(),
+			namespace,
+			&resource.spec,
+			context.client.clone(),
+		);
 		controller.reconcile(resource, context).await
 	} else {
 		Err(OperatorError::UserInputError("Expected resource to be namespaced.".to_string()))
 	}
 }
+
+/// Serves the conversion webhook of the CRD and the defaulting and governance of the products,
+/// leaving the operator running if they cannot start.
+async fn serve_webhooks() {
+	let client = match Client::try_default().await {
+		Ok(client) => client,
+		Err(error) => {
+			error!("Webhook server not started: {}", error);
+			return;
+		}
+	};
+	let webhooks = WebhookServer::new(DEFAULT_WEBHOOK_PORT)
+		.conversion(CONVERSION_PATH, convert)
+		.mutation(MUTATION_PATH, {
+			let client = client.clone();
+			move |request| mutate_product(client.clone(), request)
+		})
+		.validation(VALIDATION_PATH, move |request| validate_product(client.clone(), request));
+	if let Err(error) = webhooks.run().await {
+		error!("Webhook server stopped: {}", error);
+	}
+}
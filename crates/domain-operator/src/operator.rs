@@ -1,37 +1,116 @@
+use k8s_openapi::api::networking::v1::NetworkPolicyPeer;
 use kube::Client;
 
 use dapp_platform::{
-	core::operator::{OperatorController, OperatorError},
-	surrealdb::app::SurrealDBApp,
+	core::{
+		constants::{DOMAIN_OPERATOR, OPERATOR_NAMESPACE},
+		hierarchy::{Hierarchy, Level},
+		labels::DOMAIN_LABEL,
+		network_policy::{namespace_peer, pod_peer},
+		operator::{OperatorController, OperatorError},
+		plan::Plan,
+	},
+	surrealdb::{app::SurrealDBApp, config::SurrealDBConfig},
 };
 
-use crate::crd::DappDomain;
+use crate::{
+	crd::{DappDomain, DappDomainSpec},
+	governance::{engine, Governance},
+	tenancy::Tenancy,
+};
 
 pub struct DomainOperatorController {
 	pub surrealdb_app: SurrealDBApp,
+	pub hierarchy: Hierarchy,
+	pub governance: Governance,
+	pub tenancy: Option<Tenancy>,
 }
 
 impl DomainOperatorController {
 	pub const FINALIZER: &'static str = "dappdomains.dappmesh.io/finalizer";
 
-	pub fn new(name: String, namespace: String, client: Client) -> Self {
+	pub fn new(name: String, namespace: String, spec: &DappDomainSpec, client: Client) -> Self {
+		let peers = Self::storage_peers(&name, spec);
+		let hierarchy =
+			Hierarchy::new(client.clone(), Level::Domain, &name, &namespace, spec.mesh_ref.clone());
+		let engine = spec.policies.as_ref().map(engine).unwrap_or_default();
+		let governance = Governance::new(client.clone(), &name, &namespace, engine);
+		let config = SurrealDBConfig::new(SurrealDBApp::db_name(&name), namespace.clone())
+			.with_network_policy(peers)
+			.with_security_overrides(spec.security_context.as_ref())
+			.with_spec(spec.database.as_ref());
+
+		let surrealdb_app = SurrealDBApp::with_config(config, client.clone());
+		let tenancy = spec
+			.namespace
+			.as_ref()
+			.map(|spec| Tenancy::new(client, &name, &namespace, spec))
+			.map(|tenancy| tenancy.plan(surrealdb_app.plan().clone()));
+
 		Self {
-			surrealdb_app: SurrealDBApp::new(name, namespace, client),
+			surrealdb_app,
+			hierarchy,
+			governance,
+			tenancy,
+		}
+	}
+
+	/// The domain's own workloads, in its namespace when it has one, and its operator.
+	fn storage_peers(name: &str, spec: &DappDomainSpec) -> Vec<NetworkPolicyPeer> {
+		let mut peers = vec![
+			pod_peer(vec![(DOMAIN_LABEL, name)], None),
+			pod_peer(vec![("app", DOMAIN_OPERATOR)], Some(OPERATOR_NAMESPACE)),
+		];
+		if let Some(namespace) = &spec.namespace {
+			peers.push(namespace_peer(&namespace.name(name)));
 		}
+		peers
 	}
 }
 
 impl OperatorController<DappDomain> for DomainOperatorController {
 	async fn create_resources(&self) -> Result<(), OperatorError> {
+		self.hierarchy.ensure_parent_ready().await?;
+		if let Some(tenancy) = &self.tenancy {
+			tenancy.provision().await?;
+		}
 		self.surrealdb_app.create().await?;
-		Ok(())
+		self.hierarchy.publish_ready().await?;
+		self.governance.enforce().await
 	}
 
+	/// Keeps the resource, and its finalizer, while children still refer to it.
 	async fn delete_resources(&self) -> Result<(), OperatorError> {
+		self.hierarchy.ensure_no_children().await?;
 		self.surrealdb_app.delete().await?;
+		if let Some(tenancy) = &self.tenancy {
+			tenancy.delete().await?;
+		}
 		Ok(())
 	}
 
+	async fn update_resources(&self) -> Result<(), OperatorError> {
+		if let Some(tenancy) = &self.tenancy {
+			tenancy.provision().await?;
+		}
+		self.surrealdb_app.update().await?;
+		self.hierarchy.publish_ready().await?;
+		self.governance.enforce().await
+	}
+
+	fn dry_run(mut self, dry_run: bool) -> Self {
+		self.surrealdb_app = self.surrealdb_app.dry_run(dry_run);
+		self.hierarchy = self.hierarchy.dry_run(dry_run);
+		self.governance = self.governance.dry_run(dry_run);
+		let plan = self.surrealdb_app.plan().clone();
+		self.tenancy = self.tenancy.map(|tenancy| tenancy.plan(plan));
+		self
+	}
+
+	fn plan(&self) -> Plan {
+		self.surrealdb_app.plan().clone()
+	}
+
 	fn finalizer(&self) -> &str {
 		Self::FINALIZER
 	}
@@ -40,6 +119,7 @@ impl OperatorController<DappDomain> for DomainOperatorController {
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use crate::tenancy::DomainNamespace;
 	use dapp_platform::core::operator::OperatorAction;
 	use std::sync::Arc;
 
@@ -70,4 +150,16 @@ mod tests {
 
 		matches!(action, OperatorAction::NoOp);
 	}
+
+	#[test]
+	fn storage_peers_include_the_namespace_of_the_domain() {
+		let mut spec = DappDomainSpec::default();
+		assert_eq!(DomainOperatorController::storage_peers("sales", &spec).len(), 2);
+
+		spec.namespace = Some(DomainNamespace::default());
+		let peers = DomainOperatorController::storage_peers("sales", &spec);
+
+		assert_eq!(peers[0], pod_peer(vec![(DOMAIN_LABEL, "sales")], None));
+		assert_eq!(peers[2], namespace_peer("sales"));
+	}
 }
@@ -0,0 +1,201 @@
+use std::collections::BTreeMap;
+
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use dapp_platform::{
+	core::{
+		governance::DataClassification,
+		quantity::bytes,
+		validation::{DNS_SUBDOMAIN, DURATION, QUANTITY},
+	},
+	surrealdb::{config::PVCConfig, spec::SurrealDBSpec},
+};
+
+/// Rules every DappProduct of the domain has to follow. Names, labels and storage are
+/// enforced when a product is admitted, the governance of its data is evaluated continuously.
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DomainPolicies {
+	/// StorageClasses products may request. Any class, including the default one, when empty.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	#[schemars(inner(regex = "DNS_SUBDOMAIN"))]
+	pub allowed_storage_classes: Vec<String>,
+	/// Regular expression the names of products have to match, e.g. `^sales-`.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub name_pattern: Option<String>,
+	/// Labels every product has to carry, e.g. `dappmesh.io/owner`.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub required_labels: Vec<String>,
+	/// Largest volume a product may request for each of its database replicas.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schemars(regex = "QUANTITY")]
+	pub max_storage_size: Option<String>,
+	/// Classifications products have to declare one of. Unchecked when empty.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub classifications: Vec<DataClassification>,
+	/// How products holding personally identifiable information have to treat it.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub pii: Option<PiiPolicy>,
+	/// Fields every DataContract of a product has to set, as paths into its spec, e.g.
+	/// `terms.usage`.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub required_contract_fields: Vec<String>,
+	/// Longest retention products may declare, e.g. `365d`.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schemars(regex = "DURATION")]
+	pub max_retention: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PiiPolicy {
+	/// Rejects every product holding personal data.
+	#[serde(default)]
+	pub forbidden: bool,
+	/// Lowest classification of products holding personal data.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub minimum_classification: Option<DataClassification>,
+	/// Longest retention of personal data, e.g. `30d`.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schemars(regex = "DURATION")]
+	pub max_retention: Option<String>,
+}
+
+impl DomainPolicies {
+	/// Why a product named `name` breaks the policies, empty if it follows them.
+	pub fn violations(
+		&self,
+		name: &str,
+		labels: &BTreeMap<String, String>,
+		database: Option<&SurrealDBSpec>,
+	) -> Vec<String> {
+		let mut violations = Vec::new();
+
+		if let Some(pattern) = &self.name_pattern {
+			match Regex::new(pattern) {
+				Ok(regex) if !regex.is_match(name) => {
+					violations.push(format!("name {} does not match {}", name, pattern))
+				}
+				Ok(_) => {}
+				Err(_) => {
+					violations.push(format!("namePattern {} is not a regular expression", pattern))
+				}
+			}
+		}
+
+		for label in &self.required_labels {
+			if !labels.contains_key(label) {
+				violations.push(format!("label {} is required", label));
+			}
+		}
+
+		let storage = database.and_then(|database| database.storage.as_ref());
+		if !self.allowed_storage_classes.is_empty() {
+			let allowed = self.allowed_storage_classes.join(", ");
+			match storage.and_then(|storage| storage.class_name.as_ref()) {
+				Some(class) if !self.allowed_storage_classes.contains(class) => violations.push(
+					format!("storage class {} is not allowed, use one of {}", class, allowed),
+				),
+				Some(_) => {}
+				None => violations.push(format!(
+					"spec.database.storage.className is required, use one of {}",
+					allowed
+				)),
+			}
+		}
+
+		if let Some(max) = &self.max_storage_size {
+			let size = storage
+				.and_then(|storage| storage.size.as_deref())
+				.unwrap_or(PVCConfig::DEFAULT_SIZE);
+			if let (Some(requested), Some(limit)) = (bytes(size), bytes(max)) {
+				if requested > limit {
+					violations
+						.push(format!("storage size {} exceeds the maximum of {}", size, max));
+				}
+			}
+		}
+
+		violations
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use dapp_platform::surrealdb::spec::StorageSpec;
+
+	fn policies() -> DomainPolicies {
+		DomainPolicies {
+			allowed_storage_classes: vec!["standard".to_string(), "fast-ssd".to_string()],
+			name_pattern: Some("^sales-".to_string()),
+			required_labels: vec!["dappmesh.io/owner".to_string()],
+			max_storage_size: Some("50Gi".to_string()),
+			..DomainPolicies::default()
+		}
+	}
+
+	fn database(class_name: Option<&str>, size: Option<&str>) -> SurrealDBSpec {
+		SurrealDBSpec {
+			storage: Some(StorageSpec {
+				size: size.map(str::to_string),
+				class_name: class_name.map(str::to_string),
+			}),
+			..SurrealDBSpec::default()
+		}
+	}
+
+	fn owned() -> BTreeMap<String, String> {
+		BTreeMap::from([("dappmesh.io/owner".to_string(), "team-orders".to_string())])
+	}
+
+	#[test]
+	fn compliant_products_have_no_violations() {
+		let database = database(Some("fast-ssd"), Some("20Gi"));
+
+		assert!(policies().violations("sales-orders", &owned(), Some(&database)).is_empty());
+		assert!(DomainPolicies::default().violations("orders", &BTreeMap::new(), None).is_empty());
+	}
+
+	#[test]
+	fn every_violation_is_reported() {
+		let database = database(Some("premium"), Some("100Gi"));
+
+		let violations = policies().violations("orders", &BTreeMap::new(), Some(&database));
+
+		assert_eq!(
+			violations,
+			vec![
+				"name orders does not match ^sales-",
+				"label dappmesh.io/owner is required",
+				"storage class premium is not allowed, use one of standard, fast-ssd",
+				"storage size 100Gi exceeds the maximum of 50Gi",
+			]
+		);
+	}
+
+	#[test]
+	fn the_default_storage_class_is_only_allowed_without_a_list() {
+		let violations = policies().violations("sales-orders", &owned(), None);
+
+		assert_eq!(
+			violations,
+			vec!["spec.database.storage.className is required, use one of standard, fast-ssd"]
+		);
+	}
+
+	#[test]
+	fn the_default_size_counts_against_the_maximum() {
+		let policies = DomainPolicies {
+			max_storage_size: Some("500Mi".to_string()),
+			..DomainPolicies::default()
+		};
+
+		assert_eq!(
+			policies.violations("orders", &BTreeMap::new(), None),
+			vec!["storage size 1Gi exceeds the maximum of 500Mi"]
+		);
+	}
+}
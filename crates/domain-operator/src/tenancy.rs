@@ -0,0 +1,465 @@
+use std::collections::BTreeMap;
+
+use k8s_openapi::{
+	api::{
+		core::v1::{
+			LimitRange, LimitRangeItem, LimitRangeSpec, Namespace, ResourceQuota, ResourceQuotaSpec,
+		},
+		networking::v1::{NetworkPolicy, NetworkPolicyIngressRule, NetworkPolicySpec},
+		rbac::v1::{PolicyRule, Role, RoleBinding, RoleRef, Subject},
+	},
+	apimachinery::pkg::{
+		api::resource::Quantity,
+		apis::meta::v1::{LabelSelector, ObjectMeta},
+	},
+};
+use kube::{
+	api::{Patch, PatchParams},
+	Api, Client,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+use dapp_platform::core::{
+	constants::{DOMAIN_OPERATOR, OPERATOR_NAMESPACE},
+	hierarchy::GROUP,
+	labels::{Labels, MANAGED_BY_LABEL, OWNER_DOMAIN_LABEL},
+	metadata::{truncated_name, MAX_LABEL_NAME_LENGTH},
+	network_policy::{namespace_peer, pod_peer},
+	operator::OperatorError,
+	plan::Plan,
+	validation::{DNS_LABEL, QUANTITY},
+};
+
+pub const QUOTA: &str = "domain-quota";
+pub const LIMITS: &str = "domain-limits";
+pub const OWNER: &str = "domain-owner";
+pub const CONSUMER: &str = "domain-consumer";
+pub const NETWORK_POLICY: &str = "domain-default";
+
+/// A namespace dedicated to the domain, which its DappProducts are created in.
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DomainNamespace {
+	/// Defaults to the name of the domain. A namespace that already exists is adopted.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schemars(regex = "DNS_LABEL", length(max = 63))]
+	pub name: Option<String>,
+	/// Hard limits of the ResourceQuota of the namespace, e.g. `requests.cpu: "8"`.
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	#[schemars(inner(regex = "QUANTITY"))]
+	pub quota: BTreeMap<String, String>,
+	/// The LimitRange applied to every container of the namespace.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub limits: Option<ContainerLimits>,
+	/// Groups managing the resources of the domain.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub owner_groups: Vec<String>,
+	/// Groups reading the resources of the domain.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub consumer_groups: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ContainerLimits {
+	/// Requests of containers that do not set them, e.g. `cpu: 100m`.
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	pub default_request: BTreeMap<String, String>,
+	/// Limits of containers that do not set them.
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	pub default: BTreeMap<String, String>,
+	/// Largest limits a container may set.
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	pub max: BTreeMap<String, String>,
+}
+
+impl DomainNamespace {
+	/// The namespace of the domain called `domain`.
+	pub fn name(&self, domain: &str) -> String {
+		self.name.clone().unwrap_or_else(|| domain.to_string())
+	}
+}
+
+/// The value of the owner domain label of the namespace dedicated to the DappDomain `name` in
+/// `namespace`. It stays unique across namespaces, long names are shortened before a hash.
+pub fn owner_label(namespace: &str, name: &str) -> String {
+	let owner = format!("{}.{}", namespace, name);
+	if owner.len() <= MAX_LABEL_NAME_LENGTH {
+		return owner;
+	}
+
+	let hash = hex::encode(Sha256::digest(format!("{}/{}", namespace, name)));
+	truncated_name(&owner, &hash[..8], MAX_LABEL_NAME_LENGTH)
+}
+
+/// Creates or adopts the namespace of a domain, with its quota, limits, roles and default
+/// network policy. The namespace is labelled with its owning domain, and a namespace owned by
+/// another domain is reported as a conflict. Namespaces created by the operator are deleted
+/// with the domain, adopted ones only lose what the operator added to them.
+pub struct Tenancy {
+	client: Client,
+	plan: Plan,
+	domain: String,
+	owner: String,
+	namespace: String,
+	spec: DomainNamespace,
+}
+
+impl Tenancy {
+	/// Tenancy of the DappDomain `domain` in `domain_namespace`.
+	pub fn new(
+		client: Client,
+		domain: &str,
+		domain_namespace: &str,
+		spec: &DomainNamespace,
+	) -> Self {
+		Self {
+			client,
+			plan: Plan::default(),
+			domain: format!("{}/{}", domain_namespace, domain),
+			owner: owner_label(domain_namespace, domain),
+			namespace: spec.name(domain),
+			spec: spec.clone(),
+		}
+	}
+
+	/// Records the changes in `plan`, which decides whether they are persisted.
+	pub fn plan(mut self, plan: Plan) -> Self {
+		self.plan = plan;
+		self
+	}
+
+	pub async fn provision(&self) -> Result<(), OperatorError> {
+		let namespaces: Api<Namespace> = Api::all(self.client.clone());
+		let created = match namespaces.get_opt(&self.namespace).await? {
+			Some(live) => {
+				if let Some(owner) = self.other_owner(&live.metadata) {
+					return Err(OperatorError::UserInputError(format!(
+						"Namespace {} belongs to another DappDomain ({} label {}), not to {}.",
+						self.namespace, OWNER_DOMAIN_LABEL, owner, self.domain
+					)));
+				}
+				self.is_owned(&live.metadata) && is_managed(&live.metadata)
+			}
+			None => true,
+		};
+		self.plan.apply(&namespaces, &self.namespace_manifest(created)).await?;
+
+		let quotas: Api<ResourceQuota> = Api::namespaced(self.client.clone(), &self.namespace);
+		match self.resource_quota() {
+			Some(quota) => self.plan.apply(&quotas, &quota).await?,
+			None => self.plan.delete(&quotas, QUOTA).await?,
+		}
+
+		let limits: Api<LimitRange> = Api::namespaced(self.client.clone(), &self.namespace);
+		match self.limit_range() {
+			Some(limit_range) => self.plan.apply(&limits, &limit_range).await?,
+			None => self.plan.delete(&limits, LIMITS).await?,
+		}
+
+		let roles: Api<Role> = Api::namespaced(self.client.clone(), &self.namespace);
+		let bindings: Api<RoleBinding> = Api::namespaced(self.client.clone(), &self.namespace);
+		for (role, groups) in
+			[(OWNER, &self.spec.owner_groups), (CONSUMER, &self.spec.consumer_groups)]
+		{
+			self.plan.apply(&roles, &self.role(role)).await?;
+			match self.role_binding(role, groups) {
+				Some(binding) => self.plan.apply(&bindings, &binding).await?,
+				None => self.plan.delete(&bindings, role).await?,
+			}
+		}
+
+		let policies: Api<NetworkPolicy> = Api::namespaced(self.client.clone(), &self.namespace);
+		self.plan.apply(&policies, &self.network_policy()).await?;
+
+		Ok(())
+	}
+
+	/// Deletes the namespace if the operator created it for this domain, otherwise what it
+	/// added to it. A namespace that belongs to another domain is left alone.
+	pub async fn delete(&self) -> Result<(), OperatorError> {
+		let namespaces: Api<Namespace> = Api::all(self.client.clone());
+		let Some(live) = namespaces.get_opt(&self.namespace).await? else {
+			return Ok(());
+		};
+		if !self.is_owned(&live.metadata) {
+			return Ok(());
+		}
+		if is_managed(&live.metadata) {
+			return Ok(self.plan.delete(&namespaces, &self.namespace).await?);
+		}
+
+		let policies: Api<NetworkPolicy> = Api::namespaced(self.client.clone(), &self.namespace);
+		let bindings: Api<RoleBinding> = Api::namespaced(self.client.clone(), &self.namespace);
+		let roles: Api<Role> = Api::namespaced(self.client.clone(), &self.namespace);
+		let limits: Api<LimitRange> = Api::namespaced(self.client.clone(), &self.namespace);
+		let quotas: Api<ResourceQuota> = Api::namespaced(self.client.clone(), &self.namespace);
+
+		self.plan.delete(&policies, NETWORK_POLICY).await?;
+		for role in [OWNER, CONSUMER] {
+			self.plan.delete(&bindings, role).await?;
+			self.plan.delete(&roles, role).await?;
+		}
+		self.plan.delete(&limits, LIMITS).await?;
+		self.plan.delete(&quotas, QUOTA).await?;
+
+		let patch = json!({ "metadata": { "labels": { OWNER_DOMAIN_LABEL: null } } });
+		let params = self.plan.patch_params(PatchParams::default());
+		namespaces.patch(&self.namespace, &params, &Patch::Merge(&patch)).await?;
+		Ok(())
+	}
+
+	/// Whether the namespace is labelled with this domain.
+	pub fn is_owned(&self, metadata: &ObjectMeta) -> bool {
+		domain_label(metadata) == Some(&self.owner)
+	}
+
+	/// The owner domain label of a namespace that is labelled with another domain.
+	pub fn other_owner<'a>(&self, metadata: &'a ObjectMeta) -> Option<&'a String> {
+		domain_label(metadata).filter(|owner| **owner != self.owner)
+	}
+
+	/// Labelled as managed by the operator only if it creates the namespace.
+	pub fn namespace_manifest(&self, created: bool) -> Namespace {
+		let mut labels = Labels::labels(vec![(OWNER_DOMAIN_LABEL, &self.owner)]);
+		if created {
+			labels.insert(MANAGED_BY_LABEL.to_string(), DOMAIN_OPERATOR.to_string());
+		}
+
+		Namespace {
+			metadata: ObjectMeta {
+				name: Some(self.namespace.clone()),
+				labels: Some(labels),
+				..ObjectMeta::default()
+			},
+			..Namespace::default()
+		}
+	}
+
+	pub fn resource_quota(&self) -> Option<ResourceQuota> {
+		if self.spec.quota.is_empty() {
+			return None;
+		}
+
+		Some(ResourceQuota {
+			metadata: self.metadata(QUOTA),
+			spec: Some(ResourceQuotaSpec {
+				hard: Some(quantities(&self.spec.quota)),
+				..ResourceQuotaSpec::default()
+			}),
+			..ResourceQuota::default()
+		})
+	}
+
+	pub fn limit_range(&self) -> Option<LimitRange> {
+		let limits = self.spec.limits.as_ref()?;
+		let optional = |values: &BTreeMap<String, String>| {
+			Some(quantities(values)).filter(|values| !values.is_empty())
+		};
+
+		Some(LimitRange {
+			metadata: self.metadata(LIMITS),
+			spec: Some(LimitRangeSpec {
+				limits: vec![LimitRangeItem {
+					type_: "Container".to_string(),
+					default_request: optional(&limits.default_request),
+					default: optional(&limits.default),
+					max: optional(&limits.max),
+					..LimitRangeItem::default()
+				}],
+			}),
+		})
+	}
+
+	/// Owners manage every DappMesh resource of the namespace, consumers read them.
+	pub fn role(&self, role: &str) -> Role {
+		let verbs: &[&str] = match role {
+			OWNER => &["get", "list", "watch", "create", "update", "patch", "delete"],
+			_ => &["get", "list", "watch"],
+		};
+
+		Role {
+			metadata: self.metadata(role),
+			rules: Some(vec![PolicyRule {
+				api_groups: Some(vec![GROUP.to_string()]),
+				resources: Some(vec!["*".to_string()]),
+				verbs: verbs.iter().map(|verb| verb.to_string()).collect(),
+				..PolicyRule::default()
+			}]),
+		}
+	}
+
+	pub fn role_binding(&self, role: &str, groups: &[String]) -> Option<RoleBinding> {
+		if groups.is_empty() {
+			return None;
+		}
+
+		Some(RoleBinding {
+			metadata: self.metadata(role),
+			role_ref: RoleRef {
+				api_group: "rbac.authorization.k8s.io".to_string(),
+				kind: "Role".to_string(),
+				name: role.to_string(),
+			},
+			subjects: Some(
+				groups
+					.iter()
+					.map(|group| Subject {
+						api_group: Some("rbac.authorization.k8s.io".to_string()),
+						kind: "Group".to_string(),
+						name: group.clone(),
+						..Subject::default()
+					})
+					.collect(),
+			),
+		})
+	}
+
+	/// Admits traffic from within the namespace and from the DappMesh operators only.
+	pub fn network_policy(&self) -> NetworkPolicy {
+		NetworkPolicy {
+			metadata: self.metadata(NETWORK_POLICY),
+			spec: Some(NetworkPolicySpec {
+				pod_selector: LabelSelector::default(),
+				policy_types: Some(vec!["Ingress".to_string()]),
+				ingress: Some(vec![NetworkPolicyIngressRule {
+					from: Some(vec![pod_peer(vec![], None), namespace_peer(OPERATOR_NAMESPACE)]),
+					ports: None,
+				}]),
+				..NetworkPolicySpec::default()
+			}),
+			status: None,
+		}
+	}
+
+	fn metadata(&self, name: &str) -> ObjectMeta {
+		ObjectMeta {
+			name: Some(name.to_string()),
+			namespace: Some(self.namespace.clone()),
+			labels: Some(Labels::labels(vec![
+				(OWNER_DOMAIN_LABEL, &self.owner),
+				(MANAGED_BY_LABEL, DOMAIN_OPERATOR),
+			])),
+			..ObjectMeta::default()
+		}
+	}
+}
+
+fn domain_label(metadata: &ObjectMeta) -> Option<&String> {
+	metadata.labels.as_ref().and_then(|labels| labels.get(OWNER_DOMAIN_LABEL))
+}
+
+fn is_managed(metadata: &ObjectMeta) -> bool {
+	metadata.labels.as_ref().and_then(|labels| labels.get(MANAGED_BY_LABEL)).map(String::as_str)
+		== Some(DOMAIN_OPERATOR)
+}
+
+fn quantities(values: &BTreeMap<String, String>) -> BTreeMap<String, Quantity> {
+	values.iter().map(|(key, value)| (key.clone(), Quantity(value.clone()))).collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use hyper::{Request, Response};
+	use kube::client::Body;
+
+	fn spec() -> DomainNamespace {
+		DomainNamespace {
+			quota: [("requests.cpu".to_string(), "8".to_string())].into(),
+			limits: Some(ContainerLimits {
+				default_request: [("cpu".to_string(), "100m".to_string())].into(),
+				..ContainerLimits::default()
+			}),
+			owner_groups: vec!["sales-engineers".to_string()],
+			..DomainNamespace::default()
+		}
+	}
+
+	fn tenancy(spec: &DomainNamespace) -> Tenancy {
+		let (service, _) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+		Tenancy::new(Client::new(service, "default"), "sales", "shop", spec)
+	}
+
+	#[tokio::test]
+	async fn the_namespace_defaults_to_the_domain_name() {
+		let tenancy = tenancy(&spec());
+
+		let namespace = tenancy.namespace_manifest(true);
+
+		assert_eq!(namespace.metadata.name.as_deref(), Some("sales"));
+		assert!(is_managed(&namespace.metadata));
+		assert!(!is_managed(&tenancy.namespace_manifest(false).metadata));
+		assert_eq!(namespace.metadata.labels.unwrap()[OWNER_DOMAIN_LABEL], "shop.sales");
+	}
+
+	#[tokio::test]
+	async fn namespaces_of_other_domains_are_not_owned() {
+		let tenancy = tenancy(&spec());
+		let (service, _) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+		let other = Tenancy::new(Client::new(service, "default"), "sales", "retail", &spec());
+
+		let namespace = other.namespace_manifest(true).metadata;
+
+		assert!(other.is_owned(&namespace));
+		assert!(!tenancy.is_owned(&namespace));
+		assert_eq!(tenancy.other_owner(&namespace).map(String::as_str), Some("retail.sales"));
+		assert_eq!(tenancy.other_owner(&ObjectMeta::default()), None);
+	}
+
+	#[test]
+	fn long_owner_labels_are_shortened_and_stay_unique() {
+		let domain = "sales".repeat(20);
+
+		let first = owner_label("shop", &format!("{}-a", domain));
+		let second = owner_label("shop", &format!("{}-b", domain));
+
+		assert_eq!(owner_label("shop", "sales"), "shop.sales");
+		assert_eq!(first.len(), MAX_LABEL_NAME_LENGTH);
+		assert_ne!(first, second);
+	}
+
+	#[tokio::test]
+	async fn quota_and_limits_follow_the_spec() {
+		let tenancy = tenancy(&spec());
+
+		let quota = tenancy.resource_quota().unwrap();
+		let limits = tenancy.limit_range().unwrap().spec.unwrap().limits;
+
+		assert_eq!(quota.spec.unwrap().hard.unwrap()["requests.cpu"], Quantity("8".to_string()));
+		assert_eq!(
+			limits[0].default_request.as_ref().unwrap()["cpu"],
+			Quantity("100m".to_string())
+		);
+		assert_eq!(limits[0].default, None);
+		assert!(self::tenancy(&DomainNamespace::default()).resource_quota().is_none());
+	}
+
+	#[tokio::test]
+	async fn groups_are_bound_to_their_role() {
+		let tenancy = tenancy(&spec());
+
+		let binding = tenancy.role_binding(OWNER, &tenancy.spec.owner_groups).unwrap();
+		let consumer = tenancy.role(CONSUMER).rules.unwrap();
+
+		assert_eq!(binding.role_ref.name, OWNER);
+		assert_eq!(binding.subjects.unwrap()[0].name, "sales-engineers");
+		assert!(tenancy.role_binding(CONSUMER, &tenancy.spec.consumer_groups).is_none());
+		assert_eq!(consumer[0].verbs, vec!["get", "list", "watch"]);
+	}
+
+	#[tokio::test]
+	async fn ingress_is_limited_to_the_namespace_and_the_operators() {
+		let policy = tenancy(&spec()).network_policy().spec.unwrap();
+
+		let peers = policy.ingress.unwrap()[0].from.clone().unwrap();
+
+		assert_eq!(policy.pod_selector, LabelSelector::default());
+		assert_eq!(peers.len(), 2);
+		assert!(peers[0].namespace_selector.is_none());
+		assert!(peers[1].namespace_selector.is_some());
+	}
+}
@@ -25,16 +25,19 @@ mod tests {
 		let domain_api: Api<DappDomain> = Api::namespaced(client.clone(), DOMAIN_NAMESPACE);
 		let domain_resource = DappDomain::new(DOMAIN_NAME, DappDomainSpec::default());
 
-		let controller = DomainOperatorController::new(
-			DOMAIN_NAME.to_string(),
-			DOMAIN_NAMESPACE.to_string(),
-			client.clone(),
-		);
+		let controller = || {
+			DomainOperatorController::new(
+				DOMAIN_NAME.to_string(),
+				DOMAIN_NAMESPACE.to_string(),
+				&domain_resource.spec,
+				client.clone(),
+			)
+		};
 
 		let context: Arc<OperatorContext> = Arc::new(OperatorContext::new(client.clone()));
 		let domain = domain_api.create(&PostParams::default(), &domain_resource).await?;
 
-		let action = controller.reconcile(Arc::new(domain), context.clone()).await?;
+		let action = controller().reconcile(Arc::new(domain), context.clone()).await?;
 		ensure!(action == Action::requeue(Duration::from_secs(5)));
 
 		let finalizers = domain_api.get(DOMAIN_NAME).await?.metadata.finalizers;
@@ -42,7 +45,7 @@ mod tests {
 
 		let domain = domain_api.delete(DOMAIN_NAME, &DeleteParams::default()).await?;
 
-		let action = controller.reconcile(Arc::new(domain.left_or_default()), context).await?;
+		let action = controller().reconcile(Arc::new(domain.left_or_default()), context).await?;
 		ensure!(action == Action::await_change());
 
 		Ok(())
@@ -0,0 +1,416 @@
+use std::{
+	collections::BTreeMap, convert::Infallible, io, net::SocketAddr, sync::Arc, time::Duration,
+};
+
+use http_body_util::Full;
+use hyper::{
+	body::{Bytes, Incoming},
+	header,
+	server::conn::http1,
+	service::service_fn,
+	Method, Request, Response, StatusCode,
+};
+use hyper_util::rt::TokioIo;
+use serde_json::{json, Value};
+use tokio::net::TcpListener;
+use tracing::{error, info, warn};
+
+use dapp_platform::{
+	catalog::{
+		lineage::{product_impact, schema_impact, walk, Direction, MAX_LINEAGE_DEPTH},
+		query::{CatalogReader, Filter, Select},
+		record_id, CONTRACT_TABLE, DOMAIN_TABLE, PORT_TABLE, PRODUCT_TABLE,
+	},
+	surrealdb::client::SurrealDBError,
+};
+
+pub const DEFAULT_API_PORT: u16 = 8080;
+pub const DEFAULT_PAGE_SIZE: usize = 50;
+pub const MAX_PAGE_SIZE: usize = 500;
+/// How long to wait before accepting connections again after accepting one failed.
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Read-only HTTP/JSON API over the mesh catalog, so that teams can query the mesh without
+/// database credentials.
+pub struct CatalogApi<R> {
+	address: SocketAddr,
+	reader: R,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ApiError {
+	#[error("{0}")]
+	BadRequest(String),
+	#[error("{0}")]
+	NotFound(String),
+	#[error("{0}")]
+	Catalog(#[from] SurrealDBError),
+}
+
+impl ApiError {
+	fn status(&self) -> StatusCode {
+		match self {
+			Self::BadRequest(_) => StatusCode::BAD_REQUEST,
+			Self::NotFound(_) => StatusCode::NOT_FOUND,
+			Self::Catalog(_) => StatusCode::BAD_GATEWAY,
+		}
+	}
+}
+
+type Params = BTreeMap<String, String>;
+
+impl<R: CatalogReader + 'static> CatalogApi<R> {
+	pub fn new(port: u16, reader: R) -> Self {
+		Self {
+			address: SocketAddr::from(([0, 0, 0, 0], port)),
+			reader,
+		}
+	}
+
+	/// Answers `GET` requests on
+	/// - `/api/v1/domains` and `/api/v1/products`, filtered by `owner`, `tag` and, for products,
+	///   `domain`, and paged by `start` and `limit`,
+	/// - `/api/v1/products/{namespace}/{name}`, with the ports and contracts of the product,
+	/// - `/api/v1/lineage/{namespace}/{name}`, walking `downstream` or `upstream` as `direction`
+	///   says up to `depth` products away,
+	/// - `/api/v1/impact/products/{namespace}/{name}` and
+	///   `/api/v1/impact/schemas/{namespace}/{name}?version=`, listing what a breaking change
+	///   to the product or schema version affects downstream.
+	pub async fn handle(
+		&self,
+		method: &Method,
+		path: &str,
+		query: Option<&str>,
+	) -> Response<Full<Bytes>> {
+		if method != Method::GET {
+			let error = json!({ "error": "the catalog API is read-only" });
+			return respond(StatusCode::METHOD_NOT_ALLOWED, &error);
+		}
+
+		let params: Params =
+			form_urlencoded::parse(query.unwrap_or_default().as_bytes()).into_owned().collect();
+		let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+		let result = match segments.as_slice() {
+			["api", "v1", "domains"] => self.list(DOMAIN_TABLE, &params).await,
+			["api", "v1", "products"] => self.list(PRODUCT_TABLE, &params).await,
+			["api", "v1", "products", namespace, name] => self.product(namespace, name).await,
+			["api", "v1", "lineage", namespace, name] => {
+				self.lineage(namespace, name, &params).await
+			}
+			["api", "v1", "impact", kind, namespace, name] => {
+				self.impact(kind, &record_id(&[namespace, name]), &params).await
+			}
+			_ => Err(ApiError::NotFound(format!("{} is not a catalog resource", path))),
+		};
+
+		match result {
+			Ok(body) => respond(StatusCode::OK, &body),
+			Err(error) => respond(error.status(), &json!({ "error": error.to_string() })),
+		}
+	}
+
+	async fn list(&self, table: &str, params: &Params) -> Result<Value, ApiError> {
+		let (start, limit) = page(params)?;
+		// One more record than asked for tells whether there is a next page.
+		let mut select = Select::from(table).page(start, limit + 1);
+		if let Some(owner) = params.get("owner") {
+			select = select.equals("owner.team", owner.as_str());
+		}
+		if let Some(tag) = params.get("tag") {
+			select = select.filter(tag_filter(tag));
+		}
+		if let (PRODUCT_TABLE, Some(domain)) = (table, params.get("domain")) {
+			select = select.equals("domain", domain.as_str());
+		}
+
+		let mut items = self.reader.select(&select).await?;
+		let next = (items.len() > limit).then_some(start + limit);
+		items.truncate(limit);
+		Ok(json!({ "items": items, "start": start, "limit": limit, "next": next }))
+	}
+
+	async fn product(&self, namespace: &str, name: &str) -> Result<Value, ApiError> {
+		let id = record_id(&[namespace, name]);
+		let select =
+			Select::from(PRODUCT_TABLE).equals("namespace", namespace).equals("name", name);
+		let Some(mut product) = self.reader.select(&select).await?.into_iter().next() else {
+			return Err(ApiError::NotFound(format!("DappProduct {} is not in the catalog", id)));
+		};
+
+		let ports = Select::from(PORT_TABLE).equals("product", id.as_str());
+		let contracts = Select::from(CONTRACT_TABLE).equals("product", id.as_str());
+		product["ports"] = Value::from(self.reader.select(&ports.page(0, MAX_PAGE_SIZE)).await?);
+		product["contracts"] =
+			Value::from(self.reader.select(&contracts.page(0, MAX_PAGE_SIZE)).await?);
+		Ok(product)
+	}
+
+	/// The feeds reached from the product, each with its distance to it.
+	async fn lineage(
+		&self,
+		namespace: &str,
+		name: &str,
+		params: &Params,
+	) -> Result<Value, ApiError> {
+		let id = record_id(&[namespace, name]);
+		let direction = match params.get("direction").map(String::as_str) {
+			None | Some("downstream") => Direction::Downstream,
+			Some("upstream") => Direction::Upstream,
+			Some(direction) => {
+				let message = format!("direction {} is neither downstream nor upstream", direction);
+				return Err(ApiError::BadRequest(message));
+			}
+		};
+		let depth = number(params, "depth", 1)?;
+		if !(1..=MAX_LINEAGE_DEPTH).contains(&depth) {
+			let message = format!("depth has to be between 1 and {}", MAX_LINEAGE_DEPTH);
+			return Err(ApiError::BadRequest(message));
+		}
+
+		let edges = walk(&self.reader, std::slice::from_ref(&id), direction, depth).await?;
+		let direction = params.get("direction").map_or("downstream", String::as_str);
+		Ok(json!({ "product": id, "direction": direction, "edges": edges }))
+	}
+
+	async fn impact(&self, kind: &str, id: &str, params: &Params) -> Result<Value, ApiError> {
+		let impact = match (kind, params.get("version")) {
+			("products", _) => product_impact(&self.reader, id).await?,
+			("schemas", Some(version)) => schema_impact(&self.reader, id, version).await?,
+			("schemas", None) => {
+				return Err(ApiError::BadRequest("version of the schema is required".to_string()))
+			}
+			_ => return Err(ApiError::NotFound(format!("{} have no impact to analyse", kind))),
+		};
+		Ok(json!(impact))
+	}
+
+	/// Serves the API over plain HTTP until the listener cannot be bound.
+	pub async fn run(self) -> io::Result<()> {
+		let listener = TcpListener::bind(self.address).await?;
+		info!("Serving the catalog API on {}", self.address);
+
+		let api = Arc::new(self);
+		loop {
+			let (stream, peer) = match listener.accept().await {
+				Ok(accepted) => accepted,
+				Err(error) => {
+					error!("Accepting a catalog API connection failed: {}", error);
+					tokio::time::sleep(ACCEPT_BACKOFF).await;
+					continue;
+				}
+			};
+			let api = api.clone();
+
+			tokio::spawn(async move {
+				let service = service_fn(|request: Request<Incoming>| {
+					let api = api.clone();
+					async move {
+						let uri = request.uri();
+						Ok::<_, Infallible>(
+							api.handle(request.method(), uri.path(), uri.query()).await,
+						)
+					}
+				});
+				if let Err(error) =
+					http1::Builder::new().serve_connection(TokioIo::new(stream), service).await
+				{
+					warn!("Catalog API connection with {} failed: {}", peer, error);
+				}
+			});
+		}
+	}
+}
+
+/// `key=value` matches a label with its value, `key` any value of the label.
+fn tag_filter(tag: &str) -> Filter {
+	match tag.split_once('=') {
+		Some((key, value)) => Filter::Label(key.to_string(), Some(value.to_string())),
+		None => Filter::Label(tag.to_string(), None),
+	}
+}
+
+fn page(params: &Params) -> Result<(usize, usize), ApiError> {
+	let start = number(params, "start", 0)?;
+	let limit = number(params, "limit", DEFAULT_PAGE_SIZE)?;
+	if !(1..=MAX_PAGE_SIZE).contains(&limit) {
+		let message = format!("limit has to be between 1 and {}", MAX_PAGE_SIZE);
+		return Err(ApiError::BadRequest(message));
+	}
+	Ok((start, limit))
+}
+
+fn number(params: &Params, name: &str, default: usize) -> Result<usize, ApiError> {
+	match params.get(name) {
+		Some(value) => value
+			.parse()
+			.map_err(|_| ApiError::BadRequest(format!("{} {} is not a number", name, value))),
+		None => Ok(default),
+	}
+}
+
+fn respond(status: StatusCode, body: &Value) -> Response<Full<Bytes>> {
+	let mut response = Response::new(Full::new(Bytes::from(body.to_string())));
+	*response.status_mut() = status;
+	response.headers_mut().insert(header::CONTENT_TYPE, "application/json".parse().unwrap());
+	response
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use dapp_platform::catalog::{lineage::Feed, testing, Catalog};
+	use http_body_util::BodyExt;
+
+	async fn catalog() -> Catalog {
+		let catalog = testing::catalog().await;
+		let products = [
+			("shop", "orders", "sales", json!({ "tier": "gold" })),
+			("shop", "invoices", "sales", json!({})),
+			("crm", "leads", "marketing", json!({ "tier": "silver" })),
+			("bi", "revenue", "finance", json!({})),
+		];
+		for (namespace, name, team, labels) in products {
+			let product = json!({
+				"namespace": namespace,
+				"name": name,
+				"domain": format!("{}/{}", namespace, team),
+				"owner": { "team": team },
+				"labels": labels,
+			});
+			catalog.upsert(PRODUCT_TABLE, &record_id(&[namespace, name]), &product).await.unwrap();
+		}
+
+		let port = json!({ "direction": "output", "namespace": "shop", "name": "api", "product": "shop/orders" });
+		catalog.upsert(PORT_TABLE, "shop/api", &port).await.unwrap();
+		let contract =
+			json!({ "namespace": "shop", "name": "orders-v1", "product": "shop/orders" });
+		catalog.upsert(CONTRACT_TABLE, "shop/orders-v1", &contract).await.unwrap();
+
+		let feeds = [
+			("crm/orders", "shop/orders", "crm/leads", Some("shop/orders")),
+			("bi/leads", "crm/leads", "bi/revenue", None),
+		];
+		for (port, upstream, product, schema) in feeds {
+			let feed = Feed {
+				port: port.to_string(),
+				product: product.to_string(),
+				source: format!("{}/api", upstream.split('/').next().unwrap()),
+				upstream: upstream.to_string(),
+				contract: None,
+				schema: schema.map(str::to_string),
+				schema_version: schema.map(|_| "v1".to_string()),
+			};
+			catalog.relate_feed(port, Some(&feed)).await.unwrap();
+		}
+		catalog
+	}
+
+	async fn get(path: &str, query: Option<&str>) -> (StatusCode, Value) {
+		let api = CatalogApi::new(DEFAULT_API_PORT, catalog().await);
+		let response = api.handle(&Method::GET, path, query).await;
+		let status = response.status();
+		let body = response.into_body().collect().await.unwrap().to_bytes();
+		(status, serde_json::from_slice(&body).unwrap())
+	}
+
+	fn names(items: &Value) -> Vec<&str> {
+		items.as_array().unwrap().iter().map(|item| item["name"].as_str().unwrap()).collect()
+	}
+
+	#[tokio::test]
+	async fn lists_are_paged() {
+		let (status, first) = get("/api/v1/products", Some("limit=3")).await;
+		assert_eq!(status, StatusCode::OK);
+		assert_eq!(names(&first["items"]), vec!["revenue", "leads", "invoices"]);
+		assert_eq!(first["next"], 3);
+
+		let (_, last) = get("/api/v1/products", Some("limit=3&start=3")).await;
+		assert_eq!(names(&last["items"]), vec!["orders"]);
+		assert_eq!(last["next"], Value::Null);
+
+		let (status, domains) = get("/api/v1/domains", None).await;
+		assert_eq!(status, StatusCode::OK);
+		assert_eq!(domains["items"], json!([]));
+	}
+
+	#[tokio::test]
+	async fn products_are_searched_by_owner_tag_and_domain() {
+		let (_, sales) = get("/api/v1/products", Some("owner=sales")).await;
+		assert_eq!(names(&sales["items"]), vec!["invoices", "orders"]);
+
+		let (_, tiered) = get("/api/v1/products", Some("tag=tier")).await;
+		assert_eq!(names(&tiered["items"]), vec!["leads", "orders"]);
+
+		let (_, gold) = get("/api/v1/products", Some("tag=tier%3Dgold")).await;
+		assert_eq!(names(&gold["items"]), vec!["orders"]);
+
+		let (_, domain) = get("/api/v1/products", Some("domain=crm%2Fmarketing")).await;
+		assert_eq!(names(&domain["items"]), vec!["leads"]);
+	}
+
+	#[tokio::test]
+	async fn products_come_with_their_ports_and_contracts() {
+		let (status, product) = get("/api/v1/products/shop/orders", None).await;
+
+		assert_eq!(status, StatusCode::OK);
+		assert_eq!(product["owner"]["team"], "sales");
+		assert_eq!(names(&product["ports"]), vec!["api"]);
+		assert_eq!(names(&product["contracts"]), vec!["orders-v1"]);
+
+		let (status, missing) = get("/api/v1/products/shop/stock", None).await;
+		assert_eq!(status, StatusCode::NOT_FOUND);
+		assert_eq!(missing["error"], "DappProduct shop/stock is not in the catalog");
+	}
+
+	#[tokio::test]
+	async fn lineage_is_walked_in_both_directions() {
+		let (_, direct) = get("/api/v1/lineage/shop/orders", None).await;
+		let consumers: Vec<&Value> =
+			direct["edges"].as_array().unwrap().iter().map(|edge| &edge["product"]).collect();
+		assert_eq!(consumers, vec!["crm/leads"]);
+
+		let (_, transitive) = get("/api/v1/lineage/shop/orders", Some("depth=5")).await;
+		let edges = transitive["edges"].as_array().unwrap();
+		assert_eq!(edges.len(), 2);
+		assert_eq!(edges[1]["product"], "bi/revenue");
+		assert_eq!(edges[1]["depth"], 2);
+
+		let (_, upstream) =
+			get("/api/v1/lineage/bi/revenue", Some("direction=upstream&depth=10")).await;
+		let sources: Vec<&Value> =
+			upstream["edges"].as_array().unwrap().iter().map(|edge| &edge["source"]).collect();
+		assert_eq!(sources, vec!["crm/api", "shop/api"]);
+	}
+
+	#[tokio::test]
+	async fn impact_lists_every_downstream_consumer() {
+		let (status, product) = get("/api/v1/impact/products/shop/orders", None).await;
+		assert_eq!(status, StatusCode::OK);
+		assert_eq!(product["consumers"], json!(["bi/revenue", "crm/leads"]));
+
+		let (_, schema) = get("/api/v1/impact/schemas/shop/orders", Some("version=v1")).await;
+		assert_eq!(schema["schemas"], json!([{ "schema": "shop/orders", "version": "v1" }]));
+		assert_eq!(schema["consumers"], json!(["bi/revenue", "crm/leads"]));
+
+		let (_, unused) = get("/api/v1/impact/schemas/shop/orders", Some("version=v2")).await;
+		assert_eq!(unused["consumers"], json!([]));
+
+		let (status, _) = get("/api/v1/impact/schemas/shop/orders", None).await;
+		assert_eq!(status, StatusCode::BAD_REQUEST);
+	}
+
+	#[tokio::test]
+	async fn invalid_requests_are_rejected() {
+		let api = CatalogApi::new(DEFAULT_API_PORT, catalog().await);
+		let response = api.handle(&Method::POST, "/api/v1/products", None).await;
+		assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+
+		assert_eq!(get("/api/v1/products", Some("limit=0")).await.0, StatusCode::BAD_REQUEST);
+		assert_eq!(get("/api/v1/products", Some("start=x")).await.0, StatusCode::BAD_REQUEST);
+		assert_eq!(
+			get("/api/v1/lineage/shop/orders", Some("direction=sideways")).await.0,
+			StatusCode::BAD_REQUEST
+		);
+		assert_eq!(get("/api/v1/schemas", None).await.0, StatusCode::NOT_FOUND);
+	}
+}
@@ -0,0 +1,727 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use futures::{stream, FutureExt, StreamExt};
+use kube::{
+	api::{ApiResource, DynamicObject, GroupVersionKind},
+	runtime::{
+		reflector::{self, store::Writer, Store},
+		watcher, WatchStreamExt,
+	},
+	Api, Client, ResourceExt,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use tokio::sync::watch;
+use tracing::{error, info};
+
+use dapp_platform::{
+	catalog::{
+		record_id, Catalog, CONTRACT_TABLE, DOMAIN_TABLE, PORT_TABLE, PRODUCT_TABLE, SCHEMA_TABLE,
+		TABLES,
+	},
+	core::{
+		hierarchy::{ParentReference, GROUP, VERSION},
+		ownership::Ownership,
+		plan::{dry_run_enabled, dry_run_requested},
+	},
+	surrealdb::client::SurrealDBError,
+};
+
+/// How often the catalog is synced when nothing changes, retrying failed writes.
+const RESYNC_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A record of the catalog as table and id.
+pub type Key = (&'static str, String);
+/// The records of the catalog.
+pub type Records = BTreeMap<Key, Value>;
+
+/// A DappDomain as registered in the mesh catalog.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct DomainRecord {
+	pub namespace: String,
+	pub name: String,
+	/// The DappMesh as `namespace/name`.
+	pub mesh: Option<String>,
+	pub owner: Ownership,
+	pub labels: BTreeMap<String, String>,
+}
+
+/// A DappProduct as registered in the mesh catalog, with the ownership it inherits from its
+/// domain.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ProductRecord {
+	pub namespace: String,
+	pub name: String,
+	/// The DappDomain as `namespace/name`.
+	pub domain: Option<String>,
+	/// The DappMesh as `namespace/name`.
+	pub mesh: Option<String>,
+	pub owner: Ownership,
+	pub labels: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaRecord {
+	pub namespace: String,
+	pub name: String,
+	pub product: String,
+	pub format: Value,
+	pub compatibility: Value,
+	pub versions: Vec<String>,
+	/// The version applied to the storage of the product.
+	pub active_version: Option<String>,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractRecord {
+	pub namespace: String,
+	pub name: String,
+	pub product: String,
+	/// The DataSchema as `namespace/name`.
+	pub schema: String,
+	pub schema_version: Option<String>,
+	/// `Valid` or `Invalid` once the product operator checked the contract.
+	pub state: Option<String>,
+	pub contract_id: Option<String>,
+	pub contract_version: Option<String>,
+	/// The consuming products as `namespace/name`.
+	pub consumers: Vec<String>,
+}
+
+/// A port as registered in the mesh catalog, once the product operator resolved its URL.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct PortRecord {
+	/// `input` or `output`.
+	pub direction: &'static str,
+	pub namespace: String,
+	pub name: String,
+	pub product: String,
+	pub protocol: Value,
+	pub url: Option<String>,
+	/// The DataContract as `namespace/name`.
+	pub contract: Option<String>,
+	/// Allowed consumers of an output port as `namespace/name`.
+	pub consumers: Vec<String>,
+	/// The upstream OutputPort as `namespace/name` or the external URI of an input port.
+	pub source: Option<String>,
+}
+
+/// The resources the catalog is kept from.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) enum Kind {
+	Domain,
+	Product,
+	Schema,
+	Contract,
+	InputPort,
+	OutputPort,
+}
+
+const KINDS: [Kind; 6] =
+	[Kind::Domain, Kind::Product, Kind::Schema, Kind::Contract, Kind::InputPort, Kind::OutputPort];
+
+impl Kind {
+	pub(crate) fn api_resource(self) -> ApiResource {
+		let (kind, plural) = match self {
+			Self::Domain => ("DappDomain", "dappdomains"),
+			Self::Product => ("DappProduct", "dappproducts"),
+			Self::Schema => ("DataSchema", "dataschemas"),
+			Self::Contract => ("DataContract", "datacontracts"),
+			Self::InputPort => ("InputPort", "inputports"),
+			Self::OutputPort => ("OutputPort", "outputports"),
+		};
+		ApiResource::from_gvk_with_plural(&GroupVersionKind::gvk(GROUP, VERSION, kind), plural)
+	}
+}
+
+/// The resources of the cluster at one point in time.
+#[derive(Default, Debug, Clone)]
+pub struct Snapshot {
+	pub domains: Vec<DynamicObject>,
+	pub products: Vec<DynamicObject>,
+	pub schemas: Vec<DynamicObject>,
+	pub contracts: Vec<DynamicObject>,
+	pub input_ports: Vec<DynamicObject>,
+	pub output_ports: Vec<DynamicObject>,
+}
+
+impl Snapshot {
+	/// Reads the stores of the kinds, in the order of `KINDS`.
+	fn new(stores: &[Store<DynamicObject>]) -> Self {
+		let objects = |kind: Kind| {
+			stores[kind as usize].state().iter().map(|object| object.as_ref().clone()).collect()
+		};
+
+		Self {
+			domains: objects(Kind::Domain),
+			products: objects(Kind::Product),
+			schemas: objects(Kind::Schema),
+			contracts: objects(Kind::Contract),
+			input_ports: objects(Kind::InputPort),
+			output_ports: objects(Kind::OutputPort),
+		}
+	}
+
+	/// The records of the objects annotated for dry-run, which the sync leaves as they are.
+	pub fn dry_run_keys(&self) -> Vec<Key> {
+		let tables = [
+			(DOMAIN_TABLE, &self.domains),
+			(PRODUCT_TABLE, &self.products),
+			(SCHEMA_TABLE, &self.schemas),
+			(CONTRACT_TABLE, &self.contracts),
+			(PORT_TABLE, &self.input_ports),
+			(PORT_TABLE, &self.output_ports),
+		];
+		tables
+			.into_iter()
+			.flat_map(|(table, objects)| {
+				objects
+					.iter()
+					.filter(|object| dry_run_requested(*object))
+					.map(move |object| (table, id(object)))
+			})
+			.collect()
+	}
+}
+
+/// The catalog records describing `snapshot`.
+pub fn records(snapshot: &Snapshot) -> Records {
+	let mut records = Records::new();
+
+	let mut domains = BTreeMap::new();
+	for domain in &snapshot.domains {
+		let record = DomainRecord {
+			namespace: namespace(domain),
+			name: domain.name_any(),
+			mesh: reference(domain, "meshRef"),
+			owner: spec(domain, "owner").unwrap_or_default(),
+			labels: domain.labels().clone(),
+		};
+		insert(&mut records, DOMAIN_TABLE, id(domain), &record);
+		domains.insert(id(domain), record);
+	}
+
+	for product in &snapshot.products {
+		let domain_id = reference(product, "domainRef");
+		let domain = domain_id.as_ref().and_then(|domain| domains.get(domain));
+		let owner: Ownership = spec(product, "owner").unwrap_or_default();
+		let record = ProductRecord {
+			namespace: namespace(product),
+			name: product.name_any(),
+			mesh: domain.and_then(|domain| domain.mesh.clone()),
+			owner: owner.inherit(domain.map(|domain| &domain.owner)),
+			labels: product.labels().clone(),
+			domain: domain_id,
+		};
+		insert(&mut records, PRODUCT_TABLE, id(product), &record);
+	}
+
+	for schema in &snapshot.schemas {
+		let spec = &schema.data["spec"];
+		let versions = spec["versions"].as_array().cloned().unwrap_or_default();
+		let record = SchemaRecord {
+			namespace: namespace(schema),
+			name: schema.name_any(),
+			product: local(schema, &spec["product"]),
+			format: spec["format"].clone(),
+			compatibility: spec["compatibility"].clone(),
+			versions: versions.iter().filter_map(|version| text(&version["version"])).collect(),
+			active_version: text(&schema.data["status"]["activeVersion"]),
+		};
+		insert(&mut records, SCHEMA_TABLE, id(schema), &record);
+	}
+
+	for contract in &snapshot.contracts {
+		let spec = &contract.data["spec"];
+		let status = &contract.data["status"];
+		let record = ContractRecord {
+			namespace: namespace(contract),
+			name: contract.name_any(),
+			product: local(contract, &spec["product"]),
+			schema: local(contract, &spec["schemaRef"]["name"]),
+			schema_version: text(&spec["schemaRef"]["version"]),
+			state: text(&status["state"]),
+			contract_id: text(&status["contractId"]),
+			contract_version: text(&status["contractVersion"]),
+			consumers: references(contract, &spec["consumers"]),
+		};
+		insert(&mut records, CONTRACT_TABLE, id(contract), &record);
+	}
+
+	for port in &snapshot.input_ports {
+		let spec = &port.data["spec"];
+		let upstream = &spec["source"]["product"];
+		let upstream_namespace = text(&upstream["namespace"]).unwrap_or(namespace(port));
+		let source = match text(&upstream["port"]) {
+			Some(name) => Some(record_id(&[&upstream_namespace, &name])),
+			None => text(&spec["source"]["external"]["uri"]),
+		};
+
+		let Some(url) = text(&port.data["status"]["url"]) else {
+			continue;
+		};
+		let record = PortRecord {
+			direction: "input",
+			namespace: namespace(port),
+			name: port.name_any(),
+			product: local(port, &spec["product"]),
+			protocol: spec["protocol"].clone(),
+			url: Some(url),
+			contract: text(&spec["contract"]["name"]).map(|name| {
+				let namespace = text(&spec["contract"]["namespace"]).unwrap_or(upstream_namespace);
+				record_id(&[&namespace, &name])
+			}),
+			consumers: Vec::new(),
+			source,
+		};
+		insert(&mut records, PORT_TABLE, id(port), &record);
+	}
+
+	for port in &snapshot.output_ports {
+		let spec = &port.data["spec"];
+		let Some(url) = text(&port.data["status"]["url"]) else {
+			continue;
+		};
+		let record = PortRecord {
+			direction: "output",
+			namespace: namespace(port),
+			name: port.name_any(),
+			product: local(port, &spec["product"]),
+			protocol: spec["protocol"].clone(),
+			url: Some(url),
+			contract: spec["contract"].as_str().map(|_| local(port, &spec["contract"])),
+			consumers: references(port, &spec["consumers"]),
+			source: None,
+		};
+		insert(&mut records, PORT_TABLE, id(port), &record);
+	}
+
+	records
+}
+
+fn insert<T: Serialize>(records: &mut Records, table: &'static str, id: String, record: &T) {
+	let record = serde_json::to_value(record).expect("catalog records serialize to JSON");
+	records.insert((table, id), record);
+}
+
+fn id(object: &DynamicObject) -> String {
+	record_id(&[&namespace(object), &object.name_any()])
+}
+
+fn namespace(object: &DynamicObject) -> String {
+	object.namespace().unwrap_or_default()
+}
+
+fn text(value: &Value) -> Option<String> {
+	value.as_str().map(str::to_string)
+}
+
+fn spec<T: DeserializeOwned>(object: &DynamicObject, field: &str) -> Option<T> {
+	serde_json::from_value(object.data["spec"][field].clone()).ok()
+}
+
+/// The resource `name` in the namespace of `object`.
+fn local(object: &DynamicObject, name: &Value) -> String {
+	record_id(&[&namespace(object), name.as_str().unwrap_or_default()])
+}
+
+/// The parent the `field` of `object` refers to, which defaults to its namespace.
+fn reference(object: &DynamicObject, field: &str) -> Option<String> {
+	let parent: ParentReference = spec(object, field)?;
+	let namespace = parent.namespace.unwrap_or(namespace(object));
+	Some(record_id(&[&namespace, &parent.name]))
+}
+
+/// A list of `name` and optional `namespace` pairs, defaulting to the namespace of `object`.
+fn references(object: &DynamicObject, list: &Value) -> Vec<String> {
+	let list = list.as_array().cloned().unwrap_or_default();
+	list.iter()
+		.filter_map(|item| {
+			let name = text(&item["name"])?;
+			let namespace = text(&item["namespace"]).unwrap_or(namespace(object));
+			Some(record_id(&[&namespace, &name]))
+		})
+		.collect()
+}
+
+/// Keeps the mesh catalog in line with the resources of the cluster, writing only what
+/// changed since the last sync.
+pub struct CatalogSync {
+	catalog: Catalog,
+	written: Records,
+	pruned: bool,
+	federated: Option<watch::Receiver<Records>>,
+	dry_run: bool,
+}
+
+impl CatalogSync {
+	pub fn new(catalog: Catalog) -> Self {
+		Self {
+			catalog,
+			written: Records::new(),
+			pruned: false,
+			federated: None,
+			dry_run: dry_run_enabled(),
+		}
+	}
+
+	/// Also keeps the records replicated from peer meshes, as they are received.
+	pub fn federated(mut self, federated: watch::Receiver<Records>) -> Self {
+		self.federated = Some(federated);
+		self
+	}
+
+	/// Only logs the changes instead of writing them.
+	pub fn dry_run(mut self, dry_run: bool) -> Self {
+		self.dry_run = dry_run;
+		self
+	}
+
+	/// Upserts the records that changed and removes those that are gone. The first sync
+	/// also removes the records left behind while the operator was not running. The records
+	/// `held` are left as they are.
+	pub async fn sync(&mut self, mut desired: Records, held: &[Key]) -> Result<(), SurrealDBError> {
+		let kept: Vec<Key> = desired.keys().chain(held).cloned().collect();
+		for key in held {
+			match self.written.get(key) {
+				Some(record) => desired.insert(key.clone(), record.clone()),
+				None => desired.remove(key),
+			};
+		}
+
+		let (upserts, removals) = changes(&self.written, &desired);
+		if self.dry_run {
+			info!("Would upsert {} and remove {} catalog records", upserts.len(), removals.len());
+			return Ok(());
+		}
+		for ((table, id), record) in upserts {
+			self.catalog.upsert(table, id, record).await?;
+		}
+		for (table, id) in removals {
+			self.catalog.remove(table, id).await?;
+		}
+
+		if !self.pruned {
+			for table in TABLES {
+				let keep: Vec<String> = kept
+					.iter()
+					.filter(|(record_table, _)| *record_table == table)
+					.map(|(_, id)| id.clone())
+					.collect();
+				self.catalog.prune(table, &keep).await?;
+			}
+			self.pruned = true;
+		}
+
+		self.written = desired;
+		Ok(())
+	}
+
+	/// Watches the resources cluster-wide and syncs the catalog once all of them are listed,
+	/// after every change and at least every minute.
+	pub async fn run(mut self, client: Client) {
+		let mut stores = Vec::new();
+		let mut streams = Vec::new();
+		for kind in KINDS {
+			let resource = kind.api_resource();
+			let writer = Writer::new(resource.clone());
+			stores.push(writer.as_reader());
+
+			let api: Api<DynamicObject> = Api::all_with(client.clone(), &resource);
+			let events = watcher(api, watcher::Config::default()).default_backoff();
+			streams
+				.push(reflector::reflector(writer, events).map(move |event| (kind, event)).boxed());
+		}
+
+		let mut events = stream::select_all(streams);
+		let mut listed = Vec::new();
+		loop {
+			let mut batch = tokio::select! {
+				event = tokio::time::timeout(RESYNC_INTERVAL, events.next()) => match event {
+					Ok(Some(event)) => vec![event],
+					Ok(None) => return,
+					Err(_) => Vec::new(),
+				},
+				Ok(()) = changed(&mut self.federated) => Vec::new(),
+			};
+			// Bursts, like the initial listing, are synced at once.
+			while let Some(Some(event)) = events.next().now_or_never() {
+				batch.push(event);
+			}
+
+			for (kind, event) in batch {
+				match event {
+					Ok(watcher::Event::InitDone) if !listed.contains(&kind) => listed.push(kind),
+					Err(error) => error!("Watching {:?} failed: {}", kind, error),
+					_ => {}
+				}
+			}
+			if listed.len() < KINDS.len() {
+				continue;
+			}
+
+			let snapshot = Snapshot::new(&stores);
+			let mut desired = records(&snapshot);
+			if let Some(federated) = &self.federated {
+				merge(&mut desired, &federated.borrow());
+			}
+			if let Err(error) = self.sync(desired, &snapshot.dry_run_keys()).await {
+				error!("Catalog sync failed: {}", error);
+			}
+		}
+	}
+}
+
+/// Resolves once `federated` received new records, never without a receiver.
+async fn changed(
+	federated: &mut Option<watch::Receiver<Records>>,
+) -> Result<(), watch::error::RecvError> {
+	match federated {
+		Some(federated) => federated.changed().await,
+		None => std::future::pending().await,
+	}
+}
+
+/// Adds the records replicated from peer meshes to `local`, which wins on conflicts.
+fn merge(local: &mut Records, federated: &Records) {
+	for (key, record) in federated {
+		local.entry(key.clone()).or_insert_with(|| record.clone());
+	}
+}
+
+/// The records to upsert and the keys to remove to go from `written` to `desired`.
+fn changes<'a>(
+	written: &'a Records,
+	desired: &'a Records,
+) -> (Vec<(&'a Key, &'a Value)>, Vec<&'a Key>) {
+	let upserts =
+		desired.iter().filter(|(key, record)| written.get(*key) != Some(*record)).collect();
+	let removals = written.keys().filter(|key| !desired.contains_key(*key)).collect();
+	(upserts, removals)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use dapp_platform::{
+		catalog::{
+			query::{CatalogReader, Select},
+			testing,
+		},
+		core::plan::DRY_RUN_ANNOTATION,
+	};
+	use serde_json::json;
+
+	fn object(kind: Kind, namespace: &str, name: &str, data: Value) -> DynamicObject {
+		let mut object = DynamicObject::new(name, &kind.api_resource()).data(data);
+		object.metadata.namespace = Some(namespace.to_string());
+		object
+	}
+
+	fn snapshot() -> Snapshot {
+		Snapshot {
+			domains: vec![object(
+				Kind::Domain,
+				"shop",
+				"sales",
+				json!({ "spec": {
+					"meshRef": { "name": "mesh", "namespace": "dappmesh" },
+					"owner": { "team": "sales", "costCentre": "CC-100", "description": "Sales" },
+				}}),
+			)],
+			products: vec![
+				object(
+					Kind::Product,
+					"shop",
+					"orders",
+					json!({ "spec": {
+						"domainRef": { "name": "sales" },
+						"owner": { "description": "Orders" },
+					}}),
+				),
+				object(Kind::Product, "crm", "leads", json!({ "spec": {} })),
+			],
+			schemas: vec![object(
+				Kind::Schema,
+				"shop",
+				"orders",
+				json!({
+					"spec": {
+						"product": "orders",
+						"format": "SurrealQL",
+						"compatibility": "Backward",
+						"versions": [{ "version": "v1" }, { "version": "v2" }],
+						"activeVersion": "v2",
+					},
+					"status": { "activeVersion": "v1" },
+				}),
+			)],
+			contracts: vec![object(
+				Kind::Contract,
+				"shop",
+				"orders-v1",
+				json!({
+					"spec": {
+						"product": "orders",
+						"schemaRef": { "name": "orders", "version": "v1" },
+						"consumers": [{ "name": "leads", "namespace": "crm" }],
+					},
+					"status": { "state": "Valid", "contractVersion": "1.0.0" },
+				}),
+			)],
+			input_ports: vec![
+				object(
+					Kind::InputPort,
+					"crm",
+					"orders",
+					json!({
+						"spec": {
+							"product": "leads",
+							"protocol": "GraphQL",
+							"source": { "product": { "port": "api", "namespace": "shop" } },
+							"contract": { "name": "orders-v1" },
+						},
+						"status": { "url": "http://orders-api.shop.svc:8000" },
+					}),
+				),
+				object(
+					Kind::InputPort,
+					"shop",
+					"erp",
+					json!({
+						"spec": {
+							"product": "orders",
+							"protocol": "Sql",
+							"source": { "external": { "uri": "jdbc:postgresql://erp/orders" } },
+						},
+					}),
+				),
+			],
+			output_ports: vec![object(
+				Kind::OutputPort,
+				"shop",
+				"api",
+				json!({
+					"spec": {
+						"product": "orders",
+						"protocol": "GraphQL",
+						"port": 8000,
+						"contract": "orders-v1",
+						"consumers": [{ "name": "leads", "namespace": "crm" }],
+					},
+					"status": { "url": "http://orders-api.shop.svc:8000" },
+				}),
+			)],
+		}
+	}
+
+	fn record<'a>(records: &'a Records, table: &'static str, id: &str) -> &'a Value {
+		&records[&(table, id.to_string())]
+	}
+
+	#[test]
+	fn products_inherit_the_ownership_of_their_domain() {
+		let mut snapshot = snapshot();
+		snapshot.products[0].labels_mut().insert("tier".to_string(), "gold".to_string());
+		let records = records(&snapshot);
+
+		assert_eq!(record(&records, DOMAIN_TABLE, "shop/sales")["mesh"], "dappmesh/mesh");
+		assert_eq!(
+			record(&records, PRODUCT_TABLE, "shop/orders"),
+			&json!({
+				"namespace": "shop",
+				"name": "orders",
+				"domain": "shop/sales",
+				"mesh": "dappmesh/mesh",
+				"owner": { "team": "sales", "costCentre": "CC-100", "description": "Orders" },
+				"labels": { "tier": "gold" },
+			})
+		);
+		assert_eq!(record(&records, PRODUCT_TABLE, "crm/leads")["domain"], Value::Null);
+	}
+
+	#[test]
+	fn schemas_and_contracts_refer_to_records_by_id() {
+		let records = records(&snapshot());
+
+		let schema = record(&records, SCHEMA_TABLE, "shop/orders");
+		assert_eq!(schema["product"], "shop/orders");
+		assert_eq!(schema["versions"], json!(["v1", "v2"]));
+		assert_eq!(schema["activeVersion"], "v1");
+
+		let contract = record(&records, CONTRACT_TABLE, "shop/orders-v1");
+		assert_eq!(contract["schema"], "shop/orders");
+		assert_eq!(contract["schemaVersion"], "v1");
+		assert_eq!(contract["state"], "Valid");
+		assert_eq!(contract["consumers"], json!(["crm/leads"]));
+	}
+
+	#[test]
+	fn only_resolved_ports_are_registered() {
+		let records = records(&snapshot());
+
+		let input = record(&records, PORT_TABLE, "crm/orders");
+		assert_eq!(input["direction"], "input");
+		assert_eq!(input["source"], "shop/api");
+		assert_eq!(input["contract"], "shop/orders-v1");
+		assert_eq!(record(&records, PORT_TABLE, "shop/api")["contract"], "shop/orders-v1");
+		assert!(!records.contains_key(&(PORT_TABLE, "shop/erp".to_string())));
+	}
+
+	#[test]
+	fn only_changes_are_written() {
+		let written = records(&snapshot());
+		let mut snapshot = snapshot();
+		snapshot.products.pop();
+		snapshot.schemas[0].data["status"]["activeVersion"] = json!("v2");
+		let desired = records(&snapshot);
+
+		let (upserts, removals) = changes(&written, &desired);
+
+		assert_eq!(
+			upserts.into_iter().map(|(key, _)| key).collect::<Vec<_>>(),
+			vec![&(SCHEMA_TABLE, "shop/orders".to_string())]
+		);
+		assert_eq!(removals, vec![&(PRODUCT_TABLE, "crm/leads".to_string())]);
+		assert_eq!(changes(&desired, &desired), (Vec::new(), Vec::new()));
+	}
+
+	#[test]
+	fn local_records_win_over_federated_ones() {
+		let mut desired = records(&snapshot());
+		let federated = Records::from([
+			((PRODUCT_TABLE, "shop/orders".to_string()), json!({ "origin": "marketing" })),
+			((PRODUCT_TABLE, "web/visits".to_string()), json!({ "origin": "marketing" })),
+		]);
+
+		merge(&mut desired, &federated);
+
+		assert_eq!(record(&desired, PRODUCT_TABLE, "shop/orders")["origin"], Value::Null);
+		assert_eq!(record(&desired, PRODUCT_TABLE, "web/visits")["origin"], "marketing");
+	}
+
+	#[tokio::test]
+	async fn records_of_objects_in_dry_run_are_left_as_they_are() {
+		let catalog = testing::catalog().await;
+		catalog.upsert(PRODUCT_TABLE, "crm/leads", &json!({ "name": "stale" })).await.unwrap();
+		let mut snapshot = snapshot();
+		for object in [&mut snapshot.products[1], &mut snapshot.schemas[0]] {
+			object.annotations_mut().insert(DRY_RUN_ANNOTATION.to_string(), "true".to_string());
+		}
+
+		let mut sync = CatalogSync::new(catalog.clone()).dry_run(true);
+		sync.sync(records(&snapshot), &[]).await.unwrap();
+		assert_eq!(catalog.select(&Select::from(DOMAIN_TABLE)).await.unwrap(), Vec::<Value>::new());
+
+		let mut sync = CatalogSync::new(catalog.clone()).dry_run(false);
+		sync.sync(records(&snapshot), &snapshot.dry_run_keys()).await.unwrap();
+
+		let products = catalog.select(&Select::from(PRODUCT_TABLE)).await.unwrap();
+		let names: Vec<&Value> = products.iter().map(|product| &product["name"]).collect();
+		assert_eq!(names, vec!["stale", "orders"]);
+		assert!(catalog.select(&Select::from(SCHEMA_TABLE)).await.unwrap().is_empty());
+		assert_eq!(catalog.select(&Select::from(DOMAIN_TABLE)).await.unwrap().len(), 1);
+	}
+}
@@ -1,8 +1,27 @@
-use kube::CustomResource;
+pub mod v1beta1;
+
+use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition;
+use kube::{core::crd::merge_crds, CustomResource, CustomResourceExt};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use dapp_platform::{
+	core::{
+		constants::{MESH_OPERATOR, OPERATOR_NAMESPACE},
+		conversion::{self, with_webhook, ConversionError},
+		operator::OperatorResource,
+		security::SecurityContextOverrides,
+		status::Condition,
+		validation::{optional_schema, DNS_SUBDOMAIN},
+	},
+	surrealdb::spec::SurrealDBSpec,
+};
 
-use dapp_platform::core::operator::OperatorResource;
+use crate::{
+	federation::{FederationSpec, PeerStatus},
+	infrastructure::InfrastructureSpec,
+};
 
 #[derive(
 	CustomResource, Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema,
@@ -17,16 +36,61 @@ use dapp_platform::core::operator::OperatorResource;
 	plural = "dappmeshs",
 	shortname = "mesh",
 	shortname = "meshs",
+	status = "DappMeshStatus",
 	derive = "PartialEq",
 	derive = "Default"
 )]
+#[serde(rename_all = "camelCase")]
 pub struct DappMeshSpec {
-	#[schemars(regex(pattern = r"[A-Za-z0-9](?:[A-Za-z0-9\-]{0,61}[A-Za-z0-9])?"))]
+	/// Repeats `metadata.name` and is dropped in v1beta1.
+	#[schemars(regex = "DNS_SUBDOMAIN", length(min = 1, max = 253))]
 	name: String,
+	/// Overrides for the hardened security context of the generated workloads.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub security_context: Option<SecurityContextOverrides>,
+	/// Replication and scheduling of the SurrealDB instance backing this resource.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schemars(schema_with = "optional_schema::<SurrealDBSpec>")]
+	pub database: Option<SurrealDBSpec>,
+	/// Meshes in other clusters sharing their published products with this one.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schemars(schema_with = "optional_schema::<FederationSpec>")]
+	pub federation: Option<FederationSpec>,
+	/// Core infrastructure provisioned for the mesh, such as certificates and monitoring.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub infrastructure: Option<InfrastructureSpec>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+pub struct DappMeshStatus {
+	/// Number of DappDomains referring to this resource.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub domains: Option<i32>,
+	/// The sync state of each peer mesh.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub peers: Vec<PeerStatus>,
+	#[serde(default)]
+	pub conditions: Vec<Condition>,
 }
 
 impl OperatorResource for DappMesh {}
 
+/// Objects are stored as v1alpha1 until every one of them has been migrated to v1beta1.
+pub const STORAGE_VERSION: &str = "v1alpha1";
+pub const CONVERSION_PATH: &str = "/convert/dappmeshs";
+
+/// The CRD serving every version of DappMesh, converted by the webhook of the mesh operator.
+pub fn crd() -> CustomResourceDefinition {
+	let crd = merge_crds(vec![DappMesh::crd(), v1beta1::DappMesh::crd()], STORAGE_VERSION)
+		.expect("versions of DappMesh have the same names and scope");
+	with_webhook(crd, MESH_OPERATOR, OPERATOR_NAMESPACE, CONVERSION_PATH)
+}
+
+/// Converts a DappMesh object between v1alpha1 and v1beta1.
+pub fn convert(object: Value, desired_api_version: &str) -> Result<Value, ConversionError> {
+	conversion::convert::<DappMesh, v1beta1::DappMesh>(object, desired_api_version)
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -56,4 +120,31 @@ mod tests {
 			self
 		}
 	}
+
+	#[test]
+	fn v1alpha1_objects_round_trip_through_v1beta1() {
+		let mut mesh = DappMesh::test_instance();
+		mesh.spec.name = mesh.name_any();
+		let object = serde_json::to_value(&mesh).unwrap();
+
+		let beta = convert(object.clone(), "dappmesh.io/v1beta1").unwrap();
+		assert_eq!(beta["apiVersion"], "dappmesh.io/v1beta1");
+		assert!(beta["spec"].get("name").is_none());
+
+		assert_eq!(convert(beta, "dappmesh.io/v1alpha1").unwrap(), object);
+	}
+
+	#[test]
+	fn both_versions_are_served_and_v1alpha1_is_stored() {
+		let crd = crd();
+
+		let versions: Vec<_> = crd
+			.spec
+			.versions
+			.iter()
+			.map(|version| (version.name.as_str(), version.storage))
+			.collect();
+		assert_eq!(versions, vec![("v1alpha1", true), ("v1beta1", false)]);
+		assert!(crd.spec.conversion.is_some());
+	}
 }
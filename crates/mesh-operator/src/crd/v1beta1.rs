@@ -0,0 +1,78 @@
+use kube::CustomResource;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use dapp_platform::{
+	core::{security::SecurityContextOverrides, validation::optional_schema},
+	surrealdb::spec::SurrealDBSpec,
+};
+
+use super::DappMeshStatus;
+use crate::{federation::FederationSpec, infrastructure::InfrastructureSpec};
+
+#[derive(
+	CustomResource, Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema,
+)]
+#[kube(
+	doc = "DappMesh mesh controller custom resource.",
+	group = "dappmesh.io",
+	version = "v1beta1",
+	kind = "DappMesh",
+	namespaced,
+	singular = "dappmesh",
+	plural = "dappmeshs",
+	shortname = "mesh",
+	shortname = "meshs",
+	status = "DappMeshStatus",
+	derive = "PartialEq",
+	derive = "Default"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct DappMeshSpec {
+	/// Overrides for the hardened security context of the generated workloads.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub security_context: Option<SecurityContextOverrides>,
+	/// Replication and scheduling of the SurrealDB instance backing this resource.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schemars(schema_with = "optional_schema::<SurrealDBSpec>")]
+	pub database: Option<SurrealDBSpec>,
+	/// Meshes in other clusters sharing their published products with this one.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schemars(schema_with = "optional_schema::<FederationSpec>")]
+	pub federation: Option<FederationSpec>,
+	/// Core infrastructure provisioned for the mesh, such as certificates and monitoring.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub infrastructure: Option<InfrastructureSpec>,
+}
+
+impl From<super::DappMesh> for DappMesh {
+	fn from(mesh: super::DappMesh) -> Self {
+		Self {
+			metadata: mesh.metadata,
+			spec: DappMeshSpec {
+				security_context: mesh.spec.security_context,
+				database: mesh.spec.database,
+				federation: mesh.spec.federation,
+				infrastructure: mesh.spec.infrastructure,
+			},
+			status: mesh.status,
+		}
+	}
+}
+
+/// Restores the `name` that `v1alpha1` repeats from the metadata.
+impl From<DappMesh> for super::DappMesh {
+	fn from(mesh: DappMesh) -> Self {
+		Self {
+			spec: super::DappMeshSpec {
+				name: mesh.metadata.name.clone().unwrap_or_default(),
+				security_context: mesh.spec.security_context,
+				database: mesh.spec.database,
+				federation: mesh.spec.federation,
+				infrastructure: mesh.spec.infrastructure,
+			},
+			metadata: mesh.metadata,
+			status: mesh.status,
+		}
+	}
+}
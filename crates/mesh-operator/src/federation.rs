@@ -0,0 +1,609 @@
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	time::Duration,
+};
+
+use hyper::Request;
+use k8s_openapi::api::core::v1::Secret;
+use kube::{
+	api::{DynamicObject, ListParams},
+	config::{KubeConfigOptions, Kubeconfig, KubeconfigError},
+	Api, Client, Config, ResourceExt,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::sync::watch;
+use tracing::error;
+
+use dapp_platform::{
+	catalog::{record_id, CONTRACT_TABLE, PRODUCT_TABLE},
+	core::{
+		constants::{MESH_OPERATOR, OPERATOR_NAMESPACE},
+		labels::PUBLISHED_LABEL,
+		plan::dry_run_requested,
+		status::{patch_status, set_condition, Condition, READY},
+		validation::{Rule, Validated, DNS_LABEL},
+	},
+};
+
+use crate::{
+	api::MAX_PAGE_SIZE,
+	catalog::{Key, Kind, Records},
+	crd::DappMesh,
+};
+
+/// How often the published metadata of the peers is pulled.
+const FEDERATION_INTERVAL: Duration = Duration::from_secs(60);
+/// The port of the mesh operator Service serving the catalog API.
+const API_PORT_NAME: &str = "api";
+pub const DEFAULT_KUBECONFIG_KEY: &str = "kubeconfig";
+
+/// Peer meshes in other clusters whose published products and contracts are replicated into
+/// the catalog of this mesh.
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FederationSpec {
+	/// In order of precedence.
+	#[serde(default)]
+	pub peers: Vec<PeerMesh>,
+	#[serde(default)]
+	pub conflict_policy: ConflictPolicy,
+}
+
+impl Validated for FederationSpec {
+	const RULES: &'static [Rule] = &[Rule::new(
+		"self.peers.all(peer, self.peers.exists_one(other, other.name == peer.name))",
+		"peer names have to be unique",
+	)];
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PeerMesh {
+	/// Recorded as the `origin` of the records replicated from the peer.
+	#[schemars(regex = "DNS_LABEL", length(max = 63))]
+	pub name: String,
+	/// A Secret in the namespace of the DappMesh holding the kubeconfig of the peer cluster.
+	pub kubeconfig_secret_ref: SecretKeyReference,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+pub struct SecretKeyReference {
+	pub name: String,
+	/// Defaults to `kubeconfig`.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub key: Option<String>,
+}
+
+/// How a product or contract published by more than one mesh is replicated.
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, Copy, JsonSchema)]
+pub enum ConflictPolicy {
+	/// This mesh wins over its peers, and peers listed first over those listed later.
+	#[default]
+	PreferLocal,
+	/// Nothing published by more than one mesh is replicated.
+	Reject,
+}
+
+/// The sync state of a peer mesh.
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+pub struct PeerStatus {
+	pub name: String,
+	/// Number of products replicated from the peer.
+	pub products: i32,
+	/// Number of contracts replicated from the peer.
+	pub contracts: i32,
+	/// Why records published by the peer are not replicated.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub conflicts: Vec<String>,
+	#[serde(default)]
+	pub conditions: Vec<Condition>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum FederationError {
+	#[error("Kubernetes reported error: {0}")]
+	Kube(#[from] kube::Error),
+	#[error("Invalid kubeconfig: {0}")]
+	Kubeconfig(#[from] KubeconfigError),
+	#[error("Secret {0} has no key {1}")]
+	MissingKubeconfig(String, String),
+	#[error("Invalid catalog request: {0}")]
+	Request(#[from] hyper::http::Error),
+	#[error("Invalid catalog response: {0}")]
+	Response(#[from] serde_json::Error),
+}
+
+/// The catalog API of a peer mesh, reached through the API server of the peer cluster.
+pub struct PeerCatalog {
+	client: Client,
+}
+
+impl PeerCatalog {
+	pub fn new(client: Client) -> Self {
+		Self {
+			client,
+		}
+	}
+
+	/// Connects with the kubeconfig `peer` refers to, in the namespace of its DappMesh.
+	pub async fn connect(
+		client: &Client,
+		namespace: &str,
+		peer: &PeerMesh,
+	) -> Result<Self, FederationError> {
+		let reference = &peer.kubeconfig_secret_ref;
+		let key = reference.key.as_deref().unwrap_or(DEFAULT_KUBECONFIG_KEY);
+		let secrets: Api<Secret> = Api::namespaced(client.clone(), namespace);
+		let secret = secrets.get(&reference.name).await?;
+		let Some(kubeconfig) = secret.data.unwrap_or_default().remove(key) else {
+			let secret = record_id(&[namespace, &reference.name]);
+			return Err(FederationError::MissingKubeconfig(secret, key.to_string()));
+		};
+
+		let kubeconfig = Kubeconfig::from_yaml(&String::from_utf8_lossy(&kubeconfig.0))?;
+		let config =
+			Config::from_custom_kubeconfig(kubeconfig, &KubeConfigOptions::default()).await?;
+		Ok(Self::new(Client::try_from(config)?))
+	}
+
+	/// The products labelled `dappmesh.io/published=true` and their contracts, leaving out
+	/// what the peer replicated from other meshes.
+	pub async fn published(&self) -> Result<Records, FederationError> {
+		let mut records = Records::new();
+		let mut start = Some(0);
+		while let Some(offset) = start {
+			let query = form_urlencoded::Serializer::new(String::new())
+				.append_pair("tag", &format!("{}=true", PUBLISHED_LABEL))
+				.append_pair("start", &offset.to_string())
+				.append_pair("limit", &MAX_PAGE_SIZE.to_string())
+				.finish();
+			let page = self.get(&format!("/api/v1/products?{}", query)).await?;
+
+			for item in page["items"].as_array().into_iter().flatten() {
+				let (Some(namespace), Some(name)) =
+					(item["namespace"].as_str(), item["name"].as_str())
+				else {
+					continue;
+				};
+				if !item["origin"].is_null() {
+					continue;
+				}
+
+				let mut product =
+					self.get(&format!("/api/v1/products/{}/{}", namespace, name)).await?;
+				for contract in product["contracts"].as_array().into_iter().flatten() {
+					if let (Some(namespace), Some(name), true) = (
+						contract["namespace"].as_str(),
+						contract["name"].as_str(),
+						contract["origin"].is_null(),
+					) {
+						records.insert(
+							(CONTRACT_TABLE, record_id(&[namespace, name])),
+							contract.clone(),
+						);
+					}
+				}
+				if let Some(product) = product.as_object_mut() {
+					product.remove("ports");
+					product.remove("contracts");
+				}
+				records.insert((PRODUCT_TABLE, record_id(&[namespace, name])), product);
+			}
+			start = page["next"].as_u64().map(|next| next as usize);
+		}
+		Ok(records)
+	}
+
+	async fn get(&self, path: &str) -> Result<Value, FederationError> {
+		let uri = format!(
+			"/api/v1/namespaces/{}/services/{}:{}/proxy{}",
+			OPERATOR_NAMESPACE, MESH_OPERATOR, API_PORT_NAME, path
+		);
+		let body = self.client.request_text(Request::get(uri).body(Vec::new())?).await?;
+		Ok(serde_json::from_str(&body)?)
+	}
+}
+
+/// The records to replicate from `peers`, given in order of precedence with what they publish,
+/// and the conflicts of each peer. `local` holds the keys of the records of this mesh.
+///
+/// Contracts follow their products: a contract is only replicated from the peer its product is
+/// replicated from.
+pub fn federate(
+	local: &BTreeSet<Key>,
+	peers: &[(String, Records)],
+	policy: ConflictPolicy,
+) -> (Records, BTreeMap<String, Vec<String>>) {
+	let mut publishers: BTreeMap<&Key, Vec<usize>> = BTreeMap::new();
+	for (index, (_, records)) in peers.iter().enumerate() {
+		for key in records.keys() {
+			publishers.entry(key).or_default().push(index);
+		}
+	}
+
+	let mut federated = Records::new();
+	let mut conflicts: BTreeMap<String, Vec<String>> =
+		peers.iter().map(|(name, _)| (name.clone(), Vec::new())).collect();
+	for table in [PRODUCT_TABLE, CONTRACT_TABLE] {
+		for (&key, publishers) in publishers.iter().filter(|(key, _)| key.0 == table) {
+			let resource = describe(key);
+			if local.contains(key) {
+				for &index in publishers {
+					let conflict = format!("{} is published by this mesh", resource);
+					conflicts.entry(peers[index].0.clone()).or_default().push(conflict);
+				}
+				continue;
+			}
+
+			let winner = match (table, policy, publishers.as_slice()) {
+				(CONTRACT_TABLE, ..) => publishers
+					.iter()
+					.copied()
+					.find(|&index| product_origin(&federated, peers, index, key)),
+				(_, _, [only]) => Some(*only),
+				(_, ConflictPolicy::PreferLocal, [first, ..]) => Some(*first),
+				_ => None,
+			};
+			for &index in publishers.iter().filter(|&&index| Some(index) != winner) {
+				let others: Vec<&str> = publishers
+					.iter()
+					.filter(|&&other| other != index)
+					.map(|&other| peers[other].0.as_str())
+					.collect();
+				let conflict = if others.is_empty() {
+					let product = peers[index].1[key]["product"].as_str().unwrap_or_default();
+					format!(
+						"{} belongs to {}, which is not replicated from this peer",
+						resource,
+						describe(&(PRODUCT_TABLE, product.to_string()))
+					)
+				} else {
+					format!("{} is published by {} as well", resource, others.join(", "))
+				};
+				conflicts.entry(peers[index].0.clone()).or_default().push(conflict);
+			}
+
+			if let Some(index) = winner {
+				let (name, records) = &peers[index];
+				let mut record = records[key].clone();
+				record["origin"] = json!(name);
+				federated.insert(key.clone(), record);
+			}
+		}
+	}
+
+	(federated, conflicts)
+}
+
+/// Whether the product of the contract `key` published by the peer at `index` is replicated
+/// from that peer.
+fn product_origin(
+	federated: &Records,
+	peers: &[(String, Records)],
+	index: usize,
+	key: &Key,
+) -> bool {
+	let (name, records) = &peers[index];
+	let Some(product) = records[key]["product"].as_str() else {
+		return false;
+	};
+	federated
+		.get(&(PRODUCT_TABLE, product.to_string()))
+		.is_some_and(|product| product["origin"] == **name)
+}
+
+fn describe((table, id): &Key) -> String {
+	let kind = match *table {
+		PRODUCT_TABLE => "DappProduct",
+		CONTRACT_TABLE => "DataContract",
+		table => table,
+	};
+	format!("{} {}", kind, id)
+}
+
+/// The status of each peer after a sync that replicated `federated`, keeping the transition
+/// times of `live`. `errors` holds the peers that could not be reached.
+pub fn peer_statuses(
+	live: &[PeerStatus],
+	peers: &[(String, Records)],
+	federated: &Records,
+	conflicts: &BTreeMap<String, Vec<String>>,
+	errors: &BTreeMap<String, String>,
+) -> Vec<PeerStatus> {
+	peers
+		.iter()
+		.map(|(name, _)| {
+			let replicated = |table: &str| {
+				let count = federated
+					.iter()
+					.filter(|((record_table, _), record)| {
+						*record_table == table && record["origin"] == **name
+					})
+					.count();
+				count as i32
+			};
+			let mut conditions = live
+				.iter()
+				.find(|peer| peer.name == *name)
+				.map(|peer| peer.conditions.clone())
+				.unwrap_or_default();
+			let condition = match errors.get(name) {
+				Some(error) => Condition::new(READY, false, "Unreachable", error),
+				None => {
+					Condition::new(READY, true, "Synced", "The published metadata is replicated.")
+				}
+			};
+			set_condition(&mut conditions, condition);
+
+			PeerStatus {
+				name: name.clone(),
+				products: replicated(PRODUCT_TABLE),
+				contracts: replicated(CONTRACT_TABLE),
+				conflicts: conflicts.get(name).cloned().unwrap_or_default(),
+				conditions,
+			}
+		})
+		.collect()
+}
+
+/// Pulls the published metadata of the peers of every federated DappMesh, hands the records
+/// to replicate to the catalog sync and reports the state of each peer.
+pub struct FederationSync {
+	client: Client,
+	sender: watch::Sender<Records>,
+	/// What each peer of each mesh published when last reached.
+	pulled: BTreeMap<(String, String), Records>,
+}
+
+impl FederationSync {
+	pub fn new(client: Client) -> (Self, watch::Receiver<Records>) {
+		let (sender, receiver) = watch::channel(Records::new());
+		let sync = Self {
+			client,
+			sender,
+			pulled: BTreeMap::new(),
+		};
+		(sync, receiver)
+	}
+
+	pub async fn run(mut self) {
+		loop {
+			if let Err(error) = self.sync().await {
+				error!("Federation sync failed: {}", error);
+			}
+			tokio::time::sleep(FEDERATION_INTERVAL).await;
+		}
+	}
+
+	async fn sync(&mut self) -> Result<(), FederationError> {
+		let local = self.local_keys().await?;
+		let meshes: Api<DappMesh> = Api::all(self.client.clone());
+		let mut federated = Records::new();
+		let mut reached = BTreeSet::new();
+
+		for mesh in meshes.list(&ListParams::default()).await? {
+			let namespace = mesh.namespace().unwrap_or_default();
+			let mesh_id = record_id(&[&namespace, &mesh.name_any()]);
+			let federation = mesh.spec.federation.clone().unwrap_or_default();
+
+			let mut peers = Vec::new();
+			let mut errors = BTreeMap::new();
+			for peer in &federation.peers {
+				let key = (mesh_id.clone(), peer.name.clone());
+				match self.pull(&namespace, peer).await {
+					// Unreachable peers keep what they published before.
+					Ok(records) => {
+						self.pulled.insert(key.clone(), records);
+					}
+					Err(error) => {
+						errors.insert(peer.name.clone(), error.to_string());
+					}
+				}
+				peers.push((peer.name.clone(), self.pulled.get(&key).cloned().unwrap_or_default()));
+				reached.insert(key);
+			}
+
+			let (records, conflicts) = federate(&local, &peers, federation.conflict_policy);
+			let live =
+				mesh.status.as_ref().map(|status| status.peers.as_slice()).unwrap_or_default();
+			let status = peer_statuses(live, &peers, &records, &conflicts, &errors);
+			if status != live && !dry_run_requested(&mesh) {
+				let api: Api<DappMesh> = Api::namespaced(self.client.clone(), &namespace);
+				patch_status(&api, &mesh.name_any(), &json!({ "peers": status })).await?;
+			}
+			for (key, record) in records {
+				federated.entry(key).or_insert(record);
+			}
+		}
+
+		self.pulled.retain(|key, _| reached.contains(key));
+		self.sender.send_replace(federated);
+		Ok(())
+	}
+
+	async fn pull(&self, namespace: &str, peer: &PeerMesh) -> Result<Records, FederationError> {
+		PeerCatalog::connect(&self.client, namespace, peer).await?.published().await
+	}
+
+	/// The keys of the products and contracts of this cluster.
+	async fn local_keys(&self) -> Result<BTreeSet<Key>, kube::Error> {
+		let mut keys = BTreeSet::new();
+		for (kind, table) in [(Kind::Product, PRODUCT_TABLE), (Kind::Contract, CONTRACT_TABLE)] {
+			let api: Api<DynamicObject> = Api::all_with(self.client.clone(), &kind.api_resource());
+			for object in api.list_metadata(&ListParams::default()).await? {
+				let namespace = object.namespace().unwrap_or_default();
+				keys.insert((table, record_id(&[&namespace, &object.name_any()])));
+			}
+		}
+		Ok(keys)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::api::{CatalogApi, DEFAULT_API_PORT};
+	use dapp_platform::catalog::{testing, Catalog};
+	use http_body_util::BodyExt;
+	use hyper::Response;
+	use kube::client::Body;
+
+	/// A peer cluster whose API server proxies to a catalog API serving `catalog`.
+	fn peer(catalog: Catalog) -> PeerCatalog {
+		let (service, mut handle) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+		let api = CatalogApi::new(DEFAULT_API_PORT, catalog);
+		tokio::spawn(async move {
+			let proxy = "/api/v1/namespaces/dappmesh/services/dapp-mesh-operator:api/proxy";
+			while let Some((request, send)) = handle.next_request().await {
+				let uri = request.uri();
+				let path = uri.path().strip_prefix(proxy).expect("requests go through the proxy");
+				let response = api.handle(request.method(), path, uri.query()).await;
+				let (parts, body) = response.into_parts();
+				let body = body.collect().await.unwrap().to_bytes();
+				send.send_response(Response::from_parts(parts, Body::from(body.to_vec())));
+			}
+		});
+		PeerCatalog::new(Client::new(service, "default"))
+	}
+
+	async fn catalog(products: &[(&str, bool)]) -> Catalog {
+		let catalog = testing::catalog().await;
+		for (id, published) in products {
+			let (namespace, name) = id.split_once('/').unwrap();
+			let product = json!({
+				"namespace": namespace,
+				"name": name,
+				"labels": { PUBLISHED_LABEL: published.to_string() },
+			});
+			catalog.upsert(PRODUCT_TABLE, id, &product).await.unwrap();
+
+			let contract =
+				json!({ "namespace": namespace, "name": format!("{}-v1", name), "product": id });
+			catalog.upsert(CONTRACT_TABLE, &format!("{}-v1", id), &contract).await.unwrap();
+		}
+		catalog
+	}
+
+	fn keys(records: &Records) -> Vec<(&str, &str)> {
+		records.keys().map(|(table, id)| (*table, id.as_str())).collect()
+	}
+
+	#[tokio::test]
+	async fn peers_publish_their_own_labelled_products_with_their_contracts() {
+		let catalog = catalog(&[("shop/orders", true), ("shop/stock", false)]).await;
+		let replicated = json!({ "namespace": "crm", "name": "leads", "origin": "marketing", "labels": { PUBLISHED_LABEL: "true" } });
+		catalog.upsert(PRODUCT_TABLE, "crm/leads", &replicated).await.unwrap();
+
+		let published = peer(catalog).published().await.unwrap();
+
+		assert_eq!(
+			keys(&published),
+			vec![("contract", "shop/orders-v1"), ("product", "shop/orders")]
+		);
+		let product = &published[&(PRODUCT_TABLE, "shop/orders".to_string())];
+		assert!(product.get("contracts").is_none());
+		assert!(product.get("ports").is_none());
+	}
+
+	#[tokio::test]
+	async fn two_peers_are_federated_by_precedence() {
+		let product = peer(catalog(&[("shop/orders", true), ("shop/stock", true)]).await);
+		let marketing = peer(catalog(&[("shop/orders", true), ("crm/leads", true)]).await);
+		let peers = vec![
+			("product".to_string(), product.published().await.unwrap()),
+			("marketing".to_string(), marketing.published().await.unwrap()),
+		];
+		let local = BTreeSet::from([(PRODUCT_TABLE, "shop/stock".to_string())]);
+
+		let (federated, conflicts) = federate(&local, &peers, ConflictPolicy::PreferLocal);
+
+		assert_eq!(
+			keys(&federated),
+			vec![
+				("contract", "crm/leads-v1"),
+				("contract", "shop/orders-v1"),
+				("product", "crm/leads"),
+				("product", "shop/orders"),
+			]
+		);
+		assert_eq!(federated[&(PRODUCT_TABLE, "shop/orders".to_string())]["origin"], "product");
+		assert_eq!(federated[&(CONTRACT_TABLE, "shop/orders-v1".to_string())]["origin"], "product");
+		assert_eq!(
+			conflicts["product"],
+			vec![
+				"DappProduct shop/stock is published by this mesh",
+				"DataContract shop/stock-v1 belongs to DappProduct shop/stock, which is not replicated from this peer",
+			]
+		);
+		assert_eq!(
+			conflicts["marketing"],
+			vec![
+				"DappProduct shop/orders is published by product as well",
+				"DataContract shop/orders-v1 is published by product as well",
+			]
+		);
+	}
+
+	#[test]
+	fn contracts_are_replicated_from_the_peer_of_their_product() {
+		let contract = json!({ "product": "shop/orders" });
+		let peers = vec![
+			(
+				"product".to_string(),
+				Records::from([((CONTRACT_TABLE, "shop/orders-v1".to_string()), contract.clone())]),
+			),
+			(
+				"marketing".to_string(),
+				Records::from([
+					((CONTRACT_TABLE, "shop/orders-v1".to_string()), contract),
+					((PRODUCT_TABLE, "shop/orders".to_string()), json!({})),
+				]),
+			),
+		];
+
+		let (federated, conflicts) =
+			federate(&BTreeSet::new(), &peers, ConflictPolicy::PreferLocal);
+
+		assert_eq!(
+			federated[&(CONTRACT_TABLE, "shop/orders-v1".to_string())]["origin"],
+			"marketing"
+		);
+		assert_eq!(
+			conflicts["product"],
+			vec!["DataContract shop/orders-v1 is published by marketing as well"]
+		);
+		assert!(conflicts["marketing"].is_empty());
+	}
+
+	#[test]
+	fn rejected_conflicts_are_replicated_from_no_peer() {
+		let records = |ids: &[&str]| -> Records {
+			ids.iter().map(|id| ((PRODUCT_TABLE, id.to_string()), json!({}))).collect()
+		};
+		let peers = vec![
+			("product".to_string(), records(&["shop/orders", "shop/stock"])),
+			("marketing".to_string(), records(&["shop/orders"])),
+		];
+
+		let (federated, conflicts) = federate(&BTreeSet::new(), &peers, ConflictPolicy::Reject);
+
+		assert_eq!(keys(&federated), vec![("product", "shop/stock")]);
+		assert_eq!(
+			conflicts["product"],
+			vec!["DappProduct shop/orders is published by marketing as well"]
+		);
+		assert_eq!(
+			conflicts["marketing"],
+			vec!["DappProduct shop/orders is published by product as well"]
+		);
+
+		let errors = BTreeMap::from([("marketing".to_string(), "connection refused".to_string())]);
+		let statuses = peer_statuses(&[], &peers, &federated, &conflicts, &errors);
+		assert_eq!(statuses[0].products, 1);
+		assert!(statuses[0].conditions[0].is_true());
+		assert_eq!(statuses[1].products, 0);
+		assert_eq!(statuses[1].conditions[0].reason, "Unreachable");
+	}
+}
@@ -0,0 +1,685 @@
+pub mod resources;
+
+use std::{collections::BTreeMap, fmt::Debug, time::Duration};
+
+use http_body_util::{BodyExt, Empty};
+use hyper::{body::Bytes, StatusCode};
+use hyper_util::{client::legacy::Client as HttpClient, rt::TokioExecutor};
+use k8s_openapi::apimachinery::pkg::{
+	apis::meta::v1::{LabelSelector, ObjectMeta},
+	util::intstr::IntOrString,
+};
+use kube::{api::DynamicObject, Api, Client, Resource};
+use schemars::JsonSchema;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use dapp_platform::{
+	core::{
+		constants::MESH_OPERATOR,
+		hierarchy::Level,
+		labels::{Labels, MANAGED_BY_LABEL},
+		operator::OperatorError,
+		plan::Plan,
+		status::{publish_condition, retract_condition, Condition},
+		validation::{DNS_LABEL, DURATION},
+	},
+	surrealdb::config::{MetricsConfig, SurrealDBConfig},
+};
+
+use self::resources::{
+	Bundle, BundleSource, BundleSpec, BundleTarget, Certificate, CertificateSpec, ClusterIssuer,
+	ClusterIssuerSpec, ComponentCondition, ComponentStatus, Endpoint, IssuerReference, KeySelector,
+	NamespaceSelector, PrivateKey, ServiceMonitor, ServiceMonitorSpec, TargetKey,
+};
+
+pub const ISSUER: &str = "dappmesh-issuer";
+pub const CERTIFICATE_AUTHORITY: &str = "dappmesh-tls";
+pub const TRUST_BUNDLE: &str = "dappmesh-bundle";
+pub const TRUST_BUNDLE_KEY: &str = "trust-bundle.pem";
+pub const DEFAULT_TRUST_NAMESPACE: &str = "cert-manager";
+pub const DEFAULT_SCRAPE_INTERVAL: &str = "30s";
+pub const DEFAULT_METRICS_PATH: &str = "/metrics";
+/// The condition the operator reports on the ServiceMonitor once it scraped samples from its
+/// target.
+pub const SERVING_CONDITION: &str = "Serving";
+const SCRAPE_TIMEOUT: Duration = Duration::from_secs(5);
+/// Set to the DappMesh, as `namespace/name`, that created a component.
+pub const MESH_ANNOTATION: &str = "dappmesh.io/mesh";
+
+/// Core infrastructure the mesh operator provisions for the mesh.
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct InfrastructureSpec {
+	/// A self-signed cert-manager ClusterIssuer, the CA it issues and a trust-manager Bundle
+	/// distributing the CA to every namespace. They are cluster-wide and shared by the meshes
+	/// enabling them, and kept by the mesh that created them.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub certificates: Option<CertificatesSpec>,
+	/// A Prometheus ServiceMonitor scraping the mesh database, which exports its metrics to an
+	/// OpenTelemetry Collector running next to it.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub monitoring: Option<MonitoringSpec>,
+}
+
+impl InfrastructureSpec {
+	/// Whether the mesh database serves its metrics.
+	pub fn monitors(&self) -> bool {
+		self.monitoring.is_some()
+	}
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CertificatesSpec {
+	/// The trust namespace of trust-manager, which holds the secret of the CA. Defaults to
+	/// `cert-manager`.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schemars(regex = "DNS_LABEL", length(max = 63))]
+	pub namespace: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MonitoringSpec {
+	/// Defaults to `30s`.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schemars(regex = "DURATION")]
+	pub interval: Option<String>,
+	/// Defaults to `/metrics`.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub path: Option<String>,
+	/// Labels of the ServiceMonitor, e.g. the ones the `serviceMonitorSelector` of Prometheus
+	/// matches.
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	pub labels: BTreeMap<String, String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Component {
+	Issuer,
+	CertificateAuthority,
+	TrustBundle,
+	Monitoring,
+}
+
+impl Component {
+	pub const ALL: [Component; 4] = [
+		Component::Issuer,
+		Component::CertificateAuthority,
+		Component::TrustBundle,
+		Component::Monitoring,
+	];
+
+	/// The type of the condition reporting the health of the component on the DappMesh.
+	pub fn condition_type(self) -> &'static str {
+		match self {
+			Component::Issuer => "IssuerReady",
+			Component::CertificateAuthority => "CertificateAuthorityReady",
+			Component::TrustBundle => "TrustBundleReady",
+			Component::Monitoring => "MonitoringReady",
+		}
+	}
+
+	pub fn kind(self) -> &'static str {
+		match self {
+			Component::Issuer => "ClusterIssuer",
+			Component::CertificateAuthority => "Certificate",
+			Component::TrustBundle => "Bundle",
+			Component::Monitoring => "ServiceMonitor",
+		}
+	}
+
+	/// The condition of the object marking it healthy. ServiceMonitors report none, so the
+	/// operator scrapes their target itself.
+	fn ready_condition(self) -> &'static str {
+		match self {
+			Component::Issuer | Component::CertificateAuthority => "Ready",
+			Component::TrustBundle => "Synced",
+			Component::Monitoring => SERVING_CONDITION,
+		}
+	}
+
+	fn enabled(self, spec: &InfrastructureSpec) -> bool {
+		match self {
+			Component::Monitoring => spec.monitors(),
+			_ => spec.certificates.is_some(),
+		}
+	}
+}
+
+/// Creates or adopts the components enabled on a DappMesh and reports their health in its
+/// conditions. Components created by the operator record the mesh creating them, which deletes
+/// them once disabled or with the mesh. Components created for another mesh are used as they
+/// are, adopted ones are left alone.
+pub struct Infrastructure {
+	client: Client,
+	plan: Plan,
+	mesh: String,
+	/// The mesh as `namespace/name`.
+	owner: String,
+	namespace: String,
+	database: String,
+	spec: InfrastructureSpec,
+}
+
+impl Infrastructure {
+	pub fn new(
+		client: Client,
+		mesh: &str,
+		database: &SurrealDBConfig,
+		spec: Option<&InfrastructureSpec>,
+	) -> Self {
+		Self {
+			client,
+			plan: Plan::default(),
+			mesh: mesh.to_string(),
+			owner: format!("{}/{}", database.namespace, mesh),
+			namespace: database.namespace.clone(),
+			database: database.name.clone(),
+			spec: spec.cloned().unwrap_or_default(),
+		}
+	}
+
+	/// Records the changes in `plan`, which decides whether they are persisted.
+	pub fn plan(mut self, plan: Plan) -> Self {
+		self.plan = plan;
+		self
+	}
+
+	pub async fn provision(&self) -> Result<(), OperatorError> {
+		let meshes: Api<DynamicObject> =
+			Api::namespaced_with(self.client.clone(), &self.namespace, &Level::Mesh.api_resource());
+
+		for component in Component::ALL {
+			if !component.enabled(&self.spec) {
+				self.remove(component).await?;
+				if !self.plan.is_dry_run() {
+					retract_condition(&meshes, &self.mesh, component.condition_type()).await?;
+				}
+				continue;
+			}
+
+			let condition = self.ensure(component).await?;
+			if !self.plan.is_dry_run() {
+				publish_condition(&meshes, &self.mesh, condition).await?;
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Deletes the components the operator created.
+	pub async fn delete(&self) -> Result<(), OperatorError> {
+		for component in Component::ALL {
+			self.remove(component).await?;
+		}
+		Ok(())
+	}
+
+	async fn ensure(&self, component: Component) -> Result<Condition, OperatorError> {
+		let namespace = self.trust_namespace();
+		let (name, conditions) = match component {
+			Component::Issuer => {
+				let api: Api<ClusterIssuer> = Api::all(self.client.clone());
+				let live = apply(&self.plan, &api, &self.owner, self.issuer()).await?;
+				(ISSUER, live.map(|live| conditions(&live.status)))
+			}
+			Component::CertificateAuthority => {
+				let api: Api<Certificate> = Api::namespaced(self.client.clone(), namespace);
+				let live =
+					apply(&self.plan, &api, &self.owner, self.certificate_authority()).await?;
+				(CERTIFICATE_AUTHORITY, live.map(|live| conditions(&live.status)))
+			}
+			Component::TrustBundle => {
+				let api: Api<Bundle> = Api::all(self.client.clone());
+				let live = apply(&self.plan, &api, &self.owner, self.trust_bundle()).await?;
+				(TRUST_BUNDLE, live.map(|live| conditions(&live.status)))
+			}
+			Component::Monitoring => {
+				let api: Api<ServiceMonitor> =
+					Api::namespaced(self.client.clone(), &self.namespace);
+				let conditions =
+					match apply(&self.plan, &api, &self.owner, self.service_monitor()).await? {
+						Some(_) => Some(vec![scraped(self.scrape().await)]),
+						None => None,
+					};
+				(self.database.as_str(), conditions)
+			}
+		};
+
+		Ok(health(component, name, conditions.as_deref()))
+	}
+
+	async fn remove(&self, component: Component) -> Result<(), OperatorError> {
+		match component {
+			Component::Issuer => {
+				let api: Api<ClusterIssuer> = Api::all(self.client.clone());
+				remove(&self.plan, &api, &self.owner, ISSUER).await
+			}
+			Component::CertificateAuthority => {
+				let api: Api<Certificate> =
+					Api::namespaced(self.client.clone(), self.trust_namespace());
+				remove(&self.plan, &api, &self.owner, CERTIFICATE_AUTHORITY).await
+			}
+			Component::TrustBundle => {
+				let api: Api<Bundle> = Api::all(self.client.clone());
+				remove(&self.plan, &api, &self.owner, TRUST_BUNDLE).await
+			}
+			Component::Monitoring => {
+				let api: Api<ServiceMonitor> =
+					Api::namespaced(self.client.clone(), &self.namespace);
+				remove(&self.plan, &api, &self.owner, &self.database).await
+			}
+		}
+	}
+
+	/// Labelled as managed and annotated with the mesh creating the object.
+	fn metadata(
+		&self,
+		name: &str,
+		namespace: Option<&str>,
+		mut labels: BTreeMap<String, String>,
+	) -> ObjectMeta {
+		labels.insert(MANAGED_BY_LABEL.to_string(), MESH_OPERATOR.to_string());
+
+		ObjectMeta {
+			name: Some(name.to_string()),
+			namespace: namespace.map(str::to_string),
+			labels: Some(labels),
+			annotations: Some([(MESH_ANNOTATION.to_string(), self.owner.clone())].into()),
+			..ObjectMeta::default()
+		}
+	}
+
+	fn trust_namespace(&self) -> &str {
+		let certificates = self.spec.certificates.as_ref();
+		certificates
+			.and_then(|certificates| certificates.namespace.as_deref())
+			.unwrap_or(DEFAULT_TRUST_NAMESPACE)
+	}
+
+	pub fn issuer(&self) -> ClusterIssuer {
+		ClusterIssuer {
+			metadata: self.metadata(ISSUER, None, BTreeMap::new()),
+			spec: ClusterIssuerSpec {
+				self_signed: Some(BTreeMap::new()),
+			},
+			status: None,
+		}
+	}
+
+	/// The CA of the mesh, signed by the self-signed issuer.
+	pub fn certificate_authority(&self) -> Certificate {
+		let namespace = Some(self.trust_namespace());
+		Certificate {
+			metadata: self.metadata(CERTIFICATE_AUTHORITY, namespace, BTreeMap::new()),
+			spec: CertificateSpec {
+				is_ca: true,
+				common_name: CERTIFICATE_AUTHORITY.to_string(),
+				secret_name: CERTIFICATE_AUTHORITY.to_string(),
+				private_key: PrivateKey {
+					algorithm: "ECDSA".to_string(),
+					size: 256,
+				},
+				issuer_ref: IssuerReference {
+					name: ISSUER.to_string(),
+					kind: "ClusterIssuer".to_string(),
+					group: "cert-manager.io".to_string(),
+				},
+			},
+			status: None,
+		}
+	}
+
+	/// Distributes the default CAs and the CA of the mesh as a ConfigMap in every namespace.
+	pub fn trust_bundle(&self) -> Bundle {
+		Bundle {
+			metadata: self.metadata(TRUST_BUNDLE, None, BTreeMap::new()),
+			spec: BundleSpec {
+				sources: vec![
+					BundleSource {
+						use_default_cas: Some(true),
+						secret: None,
+					},
+					BundleSource {
+						use_default_cas: None,
+						secret: Some(KeySelector {
+							name: CERTIFICATE_AUTHORITY.to_string(),
+							key: "tls.crt".to_string(),
+						}),
+					},
+				],
+				target: BundleTarget {
+					config_map: TargetKey {
+						key: TRUST_BUNDLE_KEY.to_string(),
+					},
+				},
+			},
+			status: None,
+		}
+	}
+
+	/// Scrapes the collector serving the metrics of the mesh database.
+	pub fn service_monitor(&self) -> ServiceMonitor {
+		let monitoring = self.spec.monitoring.clone().unwrap_or_default();
+		ServiceMonitor {
+			metadata: self.metadata(&self.database, Some(&self.namespace), monitoring.labels),
+			spec: ServiceMonitorSpec {
+				selector: LabelSelector {
+					match_labels: Some(Labels::labels(vec![("part-of", &self.database)])),
+					..LabelSelector::default()
+				},
+				namespace_selector: NamespaceSelector {
+					match_names: vec![self.namespace.clone()],
+				},
+				endpoints: vec![Endpoint {
+					target_port: IntOrString::String(MetricsConfig::PORT_NAME.to_string()),
+					path: self.metrics_path(),
+					interval: monitoring
+						.interval
+						.unwrap_or_else(|| DEFAULT_SCRAPE_INTERVAL.to_string()),
+				}],
+			},
+		}
+	}
+
+	fn metrics_path(&self) -> String {
+		let monitoring = self.spec.monitoring.as_ref();
+		let path = monitoring.and_then(|monitoring| monitoring.path.clone());
+		path.unwrap_or_else(|| DEFAULT_METRICS_PATH.to_string())
+	}
+
+	/// Scrapes the metrics the ServiceMonitor points Prometheus to and returns the body served.
+	async fn scrape(&self) -> Result<String, String> {
+		let url = format!(
+			"http://{}.{}.svc:{}{}",
+			self.database,
+			self.namespace,
+			MetricsConfig::PORT,
+			self.metrics_path()
+		);
+		let client = HttpClient::builder(TokioExecutor::new()).build_http::<Empty<Bytes>>();
+		let uri = url.parse().map_err(|error| format!("{}: {}", url, error))?;
+
+		let scrape = async {
+			let response = client.get(uri).await.map_err(|error| error.to_string())?;
+			let status = response.status();
+			let body = response.into_body().collect().await.map_err(|error| error.to_string())?;
+			match status {
+				StatusCode::OK => Ok(String::from_utf8_lossy(&body.to_bytes()).to_string()),
+				status => Err(format!("{} answered {}", url, status)),
+			}
+		};
+		match tokio::time::timeout(SCRAPE_TIMEOUT, scrape).await {
+			Ok(result) => result,
+			Err(_) => Err(format!("{} did not answer within {:?}", url, SCRAPE_TIMEOUT)),
+		}
+	}
+}
+
+/// The condition of a ServiceMonitor, given the body its target served or why it served none.
+/// The target serves samples once the database exported its first metrics.
+fn scraped(body: Result<String, String>) -> ComponentCondition {
+	let (status, reason, message) = match body {
+		Ok(body) if body.lines().any(|line| !line.is_empty() && !line.starts_with('#')) => {
+			("True", None, None)
+		}
+		Ok(_) => {
+			("False", Some("NoSamples"), Some("The target serves no samples yet.".to_string()))
+		}
+		Err(error) => ("False", Some("Unreachable"), Some(error)),
+	};
+
+	ComponentCondition {
+		type_: SERVING_CONDITION.to_string(),
+		status: status.to_string(),
+		reason: reason.map(str::to_string),
+		message,
+	}
+}
+
+/// The condition reporting on `component`, given the conditions of its live object, which
+/// are `None` when the API of the component is not installed.
+pub fn health(
+	component: Component,
+	name: &str,
+	conditions: Option<&[ComponentCondition]>,
+) -> Condition {
+	let type_ = component.condition_type();
+	let kind = component.kind();
+	let Some(conditions) = conditions else {
+		let message = format!("The {} API is not installed in the cluster.", kind);
+		return Condition::new(type_, false, "NotInstalled", &message);
+	};
+	let ready = component.ready_condition();
+
+	match conditions.iter().find(|condition| condition.type_ == ready) {
+		Some(condition) if condition.status == "True" => {
+			Condition::new(type_, true, ready, &format!("{} {} is {}.", kind, name, ready))
+		}
+		Some(condition) => Condition::new(
+			type_,
+			false,
+			condition.reason.as_deref().unwrap_or("NotReady"),
+			condition.message.as_deref().unwrap_or_default(),
+		),
+		None => {
+			let message = format!("{} {} has not reported whether it is {}.", kind, name, ready);
+			Condition::new(type_, false, "Pending", &message)
+		}
+	}
+}
+
+/// Applies `desired` if the operator creates the object or created it for the mesh `owner`,
+/// and returns the live object. Objects created for another mesh, and the ones adopted from
+/// outside the operator, are used as they are. `None` if the API of the object is not
+/// installed.
+async fn apply<K>(
+	plan: &Plan,
+	api: &Api<K>,
+	owner: &str,
+	desired: K,
+) -> Result<Option<K>, OperatorError>
+where
+	K: Resource<DynamicType = ()> + Clone + DeserializeOwned + Serialize + Debug,
+{
+	let name = desired.meta().name.clone().unwrap_or_default();
+	if let Some(live) = api.get_opt(&name).await? {
+		if creator(live.meta(), owner) != Some(owner) {
+			return Ok(Some(live));
+		}
+	}
+	match plan.apply(api, &desired).await {
+		Err(kube::Error::Api(response)) if response.code == 404 => return Ok(None),
+		result => result?,
+	}
+
+	// Objects are not persisted in dry-run mode.
+	Ok(Some(api.get_opt(&name).await?.unwrap_or(desired)))
+}
+
+/// Deletes the object called `name` if the operator created it for the mesh `owner`.
+async fn remove<K>(plan: &Plan, api: &Api<K>, owner: &str, name: &str) -> Result<(), OperatorError>
+where
+	K: Resource<DynamicType = ()> + Clone + DeserializeOwned + Debug,
+{
+	match api.get_opt(name).await? {
+		Some(live) if creator(live.meta(), owner) == Some(owner) => {
+			Ok(plan.delete(api, name).await?)
+		}
+		_ => Ok(()),
+	}
+}
+
+fn conditions(status: &Option<ComponentStatus>) -> Vec<ComponentCondition> {
+	status.as_ref().map(|status| status.conditions.clone()).unwrap_or_default()
+}
+
+/// The mesh that created the object, if the operator did. Objects created before the mesh was
+/// recorded belong to `mesh`, which reconciles them first.
+fn creator<'a>(metadata: &'a ObjectMeta, mesh: &'a str) -> Option<&'a str> {
+	if !is_managed(metadata) {
+		return None;
+	}
+	let annotations = metadata.annotations.as_ref();
+	Some(
+		annotations
+			.and_then(|annotations| annotations.get(MESH_ANNOTATION))
+			.map_or(mesh, String::as_str),
+	)
+}
+
+fn is_managed(metadata: &ObjectMeta) -> bool {
+	metadata.labels.as_ref().and_then(|labels| labels.get(MANAGED_BY_LABEL)).map(String::as_str)
+		== Some(MESH_OPERATOR)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use hyper::{Request, Response};
+	use kube::client::Body;
+	use serde_json::json;
+
+	fn infrastructure(spec: &InfrastructureSpec) -> Infrastructure {
+		let (service, _) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+		let database = SurrealDBConfig::new("product-db".to_string(), "dappmesh".to_string());
+		Infrastructure::new(Client::new(service, "default"), "product", &database, Some(spec))
+	}
+
+	fn condition(type_: &str, status: &str, reason: Option<&str>) -> ComponentCondition {
+		ComponentCondition {
+			type_: type_.to_string(),
+			status: status.to_string(),
+			reason: reason.map(str::to_string),
+			message: None,
+		}
+	}
+
+	#[tokio::test]
+	async fn the_trust_bundle_distributes_the_ca_of_the_issuer() {
+		let spec = InfrastructureSpec {
+			certificates: Some(CertificatesSpec::default()),
+			..InfrastructureSpec::default()
+		};
+		let infrastructure = infrastructure(&spec);
+
+		let ca = infrastructure.certificate_authority();
+		let bundle = infrastructure.trust_bundle();
+
+		assert_eq!(ca.metadata.namespace.as_deref(), Some(DEFAULT_TRUST_NAMESPACE));
+		assert_eq!(ca.spec.issuer_ref.name, infrastructure.issuer().metadata.name.unwrap());
+		assert!(ca.spec.is_ca);
+		let secret = bundle.spec.sources[1].secret.as_ref().unwrap();
+		assert_eq!(secret.name, ca.spec.secret_name);
+		assert_eq!(bundle.spec.target.config_map.key, TRUST_BUNDLE_KEY);
+	}
+
+	#[tokio::test]
+	async fn adopted_components_are_used_as_they_are() {
+		let infrastructure = infrastructure(&InfrastructureSpec::default());
+		let (service, mut handle) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+		let api: Api<ClusterIssuer> = Api::all(Client::new(service, "default"));
+		let server = tokio::spawn(async move {
+			let (request, send) = handle.next_request().await.unwrap();
+			assert_eq!(request.method(), "GET");
+			let adopted = json!({
+				"apiVersion": "cert-manager.io/v1",
+				"kind": "ClusterIssuer",
+				"metadata": { "name": ISSUER },
+				"spec": { "selfSigned": {} },
+			});
+			send.send_response(Response::new(Body::from(serde_json::to_vec(&adopted).unwrap())));
+			// Nothing is applied to the adopted issuer.
+			handle.next_request().await.is_none()
+		});
+
+		let live = apply(&Plan::default(), &api, &infrastructure.owner, infrastructure.issuer())
+			.await
+			.unwrap()
+			.unwrap();
+		drop(api);
+
+		assert!(server.await.unwrap());
+		assert!(!is_managed(&live.metadata));
+		assert!(Component::ALL.iter().all(|component| !component.enabled(&infrastructure.spec)));
+	}
+
+	#[tokio::test]
+	async fn created_components_belong_to_the_mesh_creating_them() {
+		let spec = InfrastructureSpec {
+			certificates: Some(CertificatesSpec::default()),
+			..InfrastructureSpec::default()
+		};
+		let infrastructure = infrastructure(&spec);
+		let issuer = infrastructure.issuer().metadata;
+		let mut other = issuer.clone();
+		other.annotations = Some([(MESH_ANNOTATION.to_string(), "retail/mesh".to_string())].into());
+		let mut legacy = issuer.clone();
+		legacy.annotations = None;
+
+		assert_eq!(issuer.annotations.as_ref().unwrap()[MESH_ANNOTATION], "dappmesh/product");
+		assert_eq!(creator(&issuer, &infrastructure.owner), Some("dappmesh/product"));
+		assert_eq!(creator(&other, &infrastructure.owner), Some("retail/mesh"));
+		assert_eq!(creator(&legacy, &infrastructure.owner), Some("dappmesh/product"));
+		assert_eq!(creator(&ObjectMeta::default(), &infrastructure.owner), None);
+	}
+
+	#[tokio::test]
+	async fn the_service_monitor_scrapes_the_mesh_database() {
+		let spec = InfrastructureSpec {
+			monitoring: Some(MonitoringSpec {
+				interval: Some("1m".to_string()),
+				labels: [("release".to_string(), "prometheus".to_string())].into(),
+				..MonitoringSpec::default()
+			}),
+			..InfrastructureSpec::default()
+		};
+
+		let monitor = infrastructure(&spec).service_monitor();
+
+		let labels = monitor.metadata.labels.unwrap();
+		assert_eq!(labels["release"], "prometheus");
+		assert_eq!(labels[MANAGED_BY_LABEL], MESH_OPERATOR);
+		assert_eq!(monitor.spec.selector.match_labels.unwrap()["part-of"], "product-db");
+		assert_eq!(monitor.spec.endpoints[0].target_port, IntOrString::String("metrics".into()));
+		assert_eq!(monitor.spec.endpoints[0].interval, "1m");
+		assert_eq!(monitor.spec.endpoints[0].path, DEFAULT_METRICS_PATH);
+	}
+
+	#[test]
+	fn monitoring_is_healthy_once_its_target_serves_samples() {
+		let empty = scraped(Ok("# HELP surrealdb_http_requests_total\n".to_string()));
+		let unreachable = scraped(Err("connection refused".to_string()));
+
+		assert_eq!(empty.reason.as_deref(), Some("NoSamples"));
+		assert_eq!(unreachable.reason.as_deref(), Some("Unreachable"));
+		assert_eq!(unreachable.message.as_deref(), Some("connection refused"));
+		assert!(!health(Component::Monitoring, "product-db", Some(&[empty])).is_true());
+		assert!(!health(Component::Monitoring, "product-db", Some(&[])).is_true());
+	}
+
+	#[test]
+	fn health_follows_the_conditions_of_the_component() {
+		let ready = [condition("Ready", "True", None)];
+		let failed = [condition("Ready", "False", Some("ErrInitIssuer"))];
+		let synced = [condition("Synced", "True", None)];
+
+		let issuer = health(Component::Issuer, ISSUER, Some(&ready));
+		let failed = health(Component::Issuer, ISSUER, Some(&failed));
+		let pending = health(Component::TrustBundle, TRUST_BUNDLE, Some(&ready));
+		let bundle = health(Component::TrustBundle, TRUST_BUNDLE, Some(&synced));
+		let serving = [scraped(Ok("surrealdb_http_requests_total 3\n".to_string()))];
+		let monitor = health(Component::Monitoring, "product-db", Some(&serving));
+		let missing = health(Component::Monitoring, "product-db", None);
+
+		assert!(issuer.is_true());
+		assert_eq!(issuer.type_, "IssuerReady");
+		assert_eq!((failed.is_true(), failed.reason.as_str()), (false, "ErrInitIssuer"));
+		assert_eq!((pending.is_true(), pending.reason.as_str()), (false, "Pending"));
+		assert!(bundle.is_true());
+		assert!(monitor.is_true());
+		assert_eq!(monitor.type_, "MonitoringReady");
+		assert_eq!((missing.is_true(), missing.reason.as_str()), (false, "NotInstalled"));
+	}
+}
@@ -0,0 +1,136 @@
+use std::collections::BTreeMap;
+
+use k8s_openapi::apimachinery::pkg::{apis::meta::v1::LabelSelector, util::intstr::IntOrString};
+use kube::CustomResource;
+use serde::{Deserialize, Serialize};
+
+/// The part of a cert-manager or trust-manager status the operator checks the health of.
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone)]
+pub struct ComponentStatus {
+	#[serde(default)]
+	pub conditions: Vec<ComponentCondition>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone)]
+pub struct ComponentCondition {
+	#[serde(rename = "type")]
+	pub type_: String,
+	pub status: String,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub reason: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub message: Option<String>,
+}
+
+#[derive(CustomResource, Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone)]
+#[kube(
+	group = "cert-manager.io",
+	version = "v1",
+	kind = "ClusterIssuer",
+	status = "ComponentStatus",
+	schema = "disabled"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct ClusterIssuerSpec {
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub self_signed: Option<BTreeMap<String, String>>,
+}
+
+#[derive(CustomResource, Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone)]
+#[kube(
+	group = "cert-manager.io",
+	version = "v1",
+	kind = "Certificate",
+	namespaced,
+	status = "ComponentStatus",
+	schema = "disabled"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct CertificateSpec {
+	#[serde(rename = "isCA")]
+	pub is_ca: bool,
+	pub common_name: String,
+	pub secret_name: String,
+	pub private_key: PrivateKey,
+	pub issuer_ref: IssuerReference,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone)]
+pub struct PrivateKey {
+	pub algorithm: String,
+	pub size: i32,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone)]
+pub struct IssuerReference {
+	pub name: String,
+	pub kind: String,
+	pub group: String,
+}
+
+#[derive(CustomResource, Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone)]
+#[kube(
+	group = "trust.cert-manager.io",
+	version = "v1alpha1",
+	kind = "Bundle",
+	status = "ComponentStatus",
+	schema = "disabled"
+)]
+pub struct BundleSpec {
+	pub sources: Vec<BundleSource>,
+	pub target: BundleTarget,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone)]
+pub struct BundleSource {
+	#[serde(rename = "useDefaultCAs", default, skip_serializing_if = "Option::is_none")]
+	pub use_default_cas: Option<bool>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub secret: Option<KeySelector>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone)]
+pub struct KeySelector {
+	pub name: String,
+	pub key: String,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleTarget {
+	pub config_map: TargetKey,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone)]
+pub struct TargetKey {
+	pub key: String,
+}
+
+#[derive(CustomResource, Serialize, Deserialize, Default, Debug, PartialEq, Clone)]
+#[kube(
+	group = "monitoring.coreos.com",
+	version = "v1",
+	kind = "ServiceMonitor",
+	namespaced,
+	schema = "disabled"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceMonitorSpec {
+	pub selector: LabelSelector,
+	pub namespace_selector: NamespaceSelector,
+	pub endpoints: Vec<Endpoint>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct NamespaceSelector {
+	pub match_names: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Endpoint {
+	pub target_port: IntOrString,
+	pub path: String,
+	pub interval: String,
+}
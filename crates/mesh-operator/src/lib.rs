@@ -1,4 +1,8 @@
+pub mod api;
+pub mod catalog;
 pub mod crd;
+pub mod federation;
+pub mod infrastructure;
 pub mod operator;
 
 #[cfg(test)]
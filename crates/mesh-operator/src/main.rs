@@ -1,12 +1,25 @@
-use kube::{runtime::controller::Action, ResourceExt};
+use kube::{runtime::controller::Action, Client, ResourceExt};
 use std::sync::Arc;
+use tracing::error;
 
-use dapp_mesh_operator::{crd::DappMesh, operator::MeshOperatorController};
-use dapp_platform::core::operator::{Operator, OperatorContext, OperatorController, OperatorError};
+use dapp_mesh_operator::{
+	api::{CatalogApi, DEFAULT_API_PORT},
+	catalog::CatalogSync,
+	crd::{convert, DappMesh, CONVERSION_PATH},
+	federation::FederationSync,
+	operator::MeshOperatorController,
+};
+use dapp_platform::{
+	catalog::Catalog,
+	core::{
+		operator::{Operator, OperatorContext, OperatorController, OperatorError},
+		webhook::{WebhookServer, DEFAULT_WEBHOOK_PORT},
+	},
+};
 
 #[tokio::main]
 async fn main() {
-	Operator::run(reconcile).await;
+	tokio::join!(Operator::run(reconcile), serve_webhooks(), sync_catalog(), serve_api());
 }
 
 async fn reconcile(
@@ -14,10 +27,58 @@ async fn reconcile(
 	context: Arc<OperatorContext>,
 ) -> Result<Action, OperatorError> {
 	if let Some(namespace) = resource.namespace() {
-		let controller =
-			MeshOperatorController::new(resource.name_any(), namespace, context.client.clone());
+		let controller = MeshOperatorController::new(
+			resource.name_any(),
+			namespace,
+			&resource.spec,
+			context.client.clone(),
+		);
 		controller.reconcile(resource, context).await
 	} else {
 		Err(OperatorError::UserInputError("Expected resource to be namespaced.".to_string()))
 	}
 }
+
+/// Serves the conversion webhook of the CRD, leaving the operator running if it cannot start.
+async fn serve_webhooks() {
+	let webhooks = WebhookServer::new(DEFAULT_WEBHOOK_PORT).conversion(CONVERSION_PATH, convert);
+	if let Err(error) = webhooks.run().await {
+		error!("Webhook server stopped: {}", error);
+	}
+}
+
+/// Keeps the mesh catalog in line with the resources of the cluster and the peer meshes, if a
+/// catalog is configured through `DAPPMESH_CATALOG_URL`.
+async fn sync_catalog() {
+	let catalog = match Catalog::from_env() {
+		Ok(Some(catalog)) => catalog,
+		Ok(None) => return,
+		Err(error) => {
+			error!("Catalog sync not started: {}", error);
+			return;
+		}
+	};
+	match Client::try_default().await {
+		Ok(client) => {
+			let (federation, federated) = FederationSync::new(client.clone());
+			let sync = CatalogSync::new(catalog).federated(federated);
+			tokio::join!(sync.run(client), federation.run());
+		}
+		Err(error) => error!("Catalog sync not started: {}", error),
+	}
+}
+
+/// Serves the read-only catalog API, if a catalog is configured through `DAPPMESH_CATALOG_URL`.
+async fn serve_api() {
+	let catalog = match Catalog::from_env() {
+		Ok(Some(catalog)) => catalog,
+		Ok(None) => return,
+		Err(error) => {
+			error!("Catalog API not started: {}", error);
+			return;
+		}
+	};
+	if let Err(error) = CatalogApi::new(DEFAULT_API_PORT, catalog).run().await {
+		error!("Catalog API stopped: {}", error);
+	}
+}
@@ -1,35 +1,103 @@
 use kube::Client;
 
 use dapp_platform::{
-	core::operator::{OperatorController, OperatorError},
-	surrealdb::app::SurrealDBApp,
+	catalog::CatalogConfig,
+	core::{
+		hierarchy::{Hierarchy, Level},
+		operator::{OperatorController, OperatorError},
+		plan::Plan,
+	},
+	surrealdb::{app::SurrealDBApp, client::SurrealDBClient, config::SurrealDBConfig},
 };
 
-use crate::crd::DappMesh;
+use crate::{
+	crd::{DappMesh, DappMeshSpec},
+	infrastructure::{Infrastructure, InfrastructureSpec},
+};
 
 pub struct MeshOperatorController {
 	pub surrealdb_app: SurrealDBApp,
+	pub hierarchy: Hierarchy,
+	pub infrastructure: Infrastructure,
+	pub database: SurrealDBConfig,
 }
 
 impl MeshOperatorController {
 	pub const FINALIZER: &'static str = "dappmeshs.dappmesh.io/finalizer";
 
-	pub fn new(name: String, namespace: String, client: Client) -> Self {
+	pub fn new(name: String, namespace: String, spec: &DappMeshSpec, client: Client) -> Self {
+		let hierarchy = Hierarchy::new(client.clone(), Level::Mesh, &name, &namespace, None);
+		let config = SurrealDBConfig::new(SurrealDBApp::db_name(&name), namespace)
+			.with_security_overrides(spec.security_context.as_ref())
+			.with_spec(spec.database.as_ref())
+			.with_metrics(spec.infrastructure.as_ref().is_some_and(InfrastructureSpec::monitors));
+		let infrastructure =
+			Infrastructure::new(client.clone(), &name, &config, spec.infrastructure.as_ref());
+
+		let surrealdb_app = SurrealDBApp::with_config(config.clone(), client);
+		let infrastructure = infrastructure.plan(surrealdb_app.plan().clone());
+
 		Self {
-			surrealdb_app: SurrealDBApp::new(name, namespace, client),
+			surrealdb_app,
+			hierarchy,
+			infrastructure,
+			database: config,
 		}
 	}
+
+	/// Defines the user the operators reach the catalog as, if the mesh database keeps it.
+	async fn define_catalog_user(&self) -> Result<(), OperatorError> {
+		let Some(catalog) = CatalogConfig::from_env()? else {
+			return Ok(());
+		};
+		let database = SurrealDBClient::from_config(&self.database, &catalog.database);
+		if database.url() != catalog.url || self.plan().is_dry_run() {
+			return Ok(());
+		}
+
+		let container = &self.database.statefulset.container;
+		let root = SurrealDBClient::new(
+			&catalog.url,
+			&catalog.namespace,
+			&catalog.database,
+			&container.user,
+			&container.password,
+		);
+		Ok(catalog.define_user(&root).await?)
+	}
 }
 
 impl OperatorController<DappMesh> for MeshOperatorController {
 	async fn create_resources(&self) -> Result<(), OperatorError> {
 		self.surrealdb_app.create().await?;
-		Ok(())
+		self.hierarchy.publish_ready().await?;
+		self.infrastructure.provision().await?;
+		self.define_catalog_user().await
 	}
 
+	/// Keeps the resource, and its finalizer, while children still refer to it.
 	async fn delete_resources(&self) -> Result<(), OperatorError> {
+		self.hierarchy.ensure_no_children().await?;
 		self.surrealdb_app.delete().await?;
-		Ok(())
+		self.infrastructure.delete().await
+	}
+
+	async fn update_resources(&self) -> Result<(), OperatorError> {
+		self.surrealdb_app.update().await?;
+		self.hierarchy.publish_ready().await?;
+		self.infrastructure.provision().await?;
+		self.define_catalog_user().await
+	}
+
+	fn dry_run(mut self, dry_run: bool) -> Self {
+		self.surrealdb_app = self.surrealdb_app.dry_run(dry_run);
+		self.hierarchy = self.hierarchy.dry_run(dry_run);
+		self.infrastructure = self.infrastructure.plan(self.surrealdb_app.plan().clone());
+		self
+	}
+
+	fn plan(&self) -> Plan {
+		self.surrealdb_app.plan().clone()
 	}
 
 	fn finalizer(&self) -> &str {
@@ -25,16 +25,19 @@ mod tests {
 		let mesh_api: Api<DappMesh> = Api::namespaced(client.clone(), MESH_NAMESPACE);
 		let mesh_resource = DappMesh::new(MESH_NAME, DappMeshSpec::default());
 
-		let controller = MeshOperatorController::new(
-			MESH_NAME.to_string(),
-			MESH_NAMESPACE.to_string(),
-			client.clone(),
-		);
+		let controller = || {
+			MeshOperatorController::new(
+				MESH_NAME.to_string(),
+				MESH_NAMESPACE.to_string(),
+				&mesh_resource.spec,
+				client.clone(),
+			)
+		};
 
 		let context: Arc<OperatorContext> = Arc::new(OperatorContext::new(client.clone()));
 		let mesh = mesh_api.create(&PostParams::default(), &mesh_resource).await?;
 
-		let action = controller.reconcile(Arc::new(mesh), context.clone()).await?;
+		let action = controller().reconcile(Arc::new(mesh), context.clone()).await?;
 		ensure!(action == Action::requeue(Duration::from_secs(5)));
 
 		let finalizers = mesh_api.get(MESH_NAME).await?.metadata.finalizers;
@@ -42,7 +45,7 @@ mod tests {
 
 		let mesh = mesh_api.delete(MESH_NAME, &DeleteParams::default()).await?;
 
-		let action = controller.reconcile(Arc::new(mesh.left_or_default()), context).await?;
+		let action = controller().reconcile(Arc::new(mesh.left_or_default()), context).await?;
 		ensure!(action == Action::await_change());
 
 		Ok(())
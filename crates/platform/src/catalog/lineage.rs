@@ -0,0 +1,458 @@
+use std::collections::BTreeSet;
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use super::{
+	query::{CatalogReader, Select, Traversal},
+	thing, Catalog, DERIVES_TABLE, FEEDS_TABLE, PRODUCT_TABLE, SCHEMA_TABLE,
+};
+use crate::surrealdb::client::SurrealDBError;
+
+/// How far lineage is walked at most, which also cuts cycles short.
+pub const MAX_LINEAGE_DEPTH: usize = 10;
+
+/// Data of an upstream product consumed by another one through an input port, related as
+/// `product:upstream->feeds->product:consumer`.
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Feed {
+	/// The InputPort as `namespace/name`.
+	pub port: String,
+	/// The consuming product.
+	pub product: String,
+	/// The upstream OutputPort.
+	pub source: String,
+	/// The product serving the upstream OutputPort.
+	pub upstream: String,
+	/// The DataContract governing the data.
+	pub contract: Option<String>,
+	/// The DataSchema of the contract.
+	pub schema: Option<String>,
+	pub schema_version: Option<String>,
+}
+
+/// A schema version produced from another one by a step of a DataFlow, related as
+/// `schema:input->derives->schema:output`.
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Derivation {
+	/// The DataFlow as `namespace/name`.
+	pub flow: String,
+	pub step: String,
+	pub product: String,
+	pub schema: String,
+	pub version: String,
+	pub derived: String,
+	pub derived_version: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Direction {
+	/// Towards the products consuming the data.
+	Downstream,
+	/// Towards the products the data comes from.
+	Upstream,
+}
+
+/// A version of a DataSchema.
+#[derive(Serialize, Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+pub struct SchemaVersion {
+	pub schema: String,
+	pub version: String,
+}
+
+/// What a change to a product or schema version affects downstream.
+#[derive(Serialize, Debug, Default, PartialEq)]
+pub struct Impact {
+	/// The schema versions derived from the changed one, starting with the changed one.
+	pub schemas: Vec<SchemaVersion>,
+	/// The feeds carrying the change, each with its `depth` from the change.
+	pub feeds: Vec<Value>,
+	/// The affected products.
+	pub consumers: Vec<String>,
+}
+
+impl Catalog {
+	/// Replaces the feed of the input port `port`, removing it if there is none. Leaves the
+	/// catalog alone if it already holds that feed.
+	pub async fn relate_feed(&self, port: &str, feed: Option<&Feed>) -> Result<(), SurrealDBError> {
+		let desired = feed.map(serde_json::to_value).transpose()?;
+		let held = self.client.query(&edges_statement(FEEDS_TABLE, "port", port)).await?;
+		if contents(held.into_iter().next()) == Vec::from_iter(desired) {
+			return Ok(());
+		}
+
+		let edges = match feed {
+			Some(feed) => vec![(
+				thing(PRODUCT_TABLE, &feed.upstream),
+				thing(PRODUCT_TABLE, &feed.product),
+				serde_json::to_value(feed)?,
+			)],
+			None => Vec::new(),
+		};
+		self.client.query(&relate_statement(FEEDS_TABLE, "port", port, &edges)).await?;
+		Ok(())
+	}
+
+	/// Replaces the derivations of the DataFlow `flow`.
+	pub async fn relate_derivations(
+		&self,
+		flow: &str,
+		derivations: &[Derivation],
+	) -> Result<(), SurrealDBError> {
+		let mut edges = Vec::new();
+		for derivation in derivations {
+			edges.push((
+				thing(SCHEMA_TABLE, &derivation.schema),
+				thing(SCHEMA_TABLE, &derivation.derived),
+				serde_json::to_value(derivation)?,
+			));
+		}
+		self.client.query(&relate_statement(DERIVES_TABLE, "flow", flow, &edges)).await?;
+		Ok(())
+	}
+}
+
+/// Replaces the edges of `table` whose `key` field is `value` by `edges`, given as the records
+/// they relate and their content, in one transaction.
+fn edges_statement(table: &str, key: &str, value: &str) -> String {
+	format!(
+		"SELECT * FROM type::table({}) WHERE {} = {};",
+		Value::from(table),
+		key,
+		Value::from(value)
+	)
+}
+
+/// The content the edges were related with, without the fields SurrealDB adds.
+fn contents(edges: Option<Value>) -> Vec<Value> {
+	let Some(Value::Array(edges)) = edges else {
+		return Vec::new();
+	};
+	edges
+		.into_iter()
+		.map(|mut edge| {
+			if let Some(edge) = edge.as_object_mut() {
+				for field in ["id", "in", "out"] {
+					edge.remove(field);
+				}
+			}
+			edge
+		})
+		.collect()
+}
+
+fn relate_statement(
+	table: &str,
+	key: &str,
+	value: &str,
+	edges: &[(String, String, Value)],
+) -> String {
+	let mut statement = format!(
+		"BEGIN TRANSACTION; DELETE type::table({}) WHERE {} = {};",
+		Value::from(table),
+		key,
+		Value::from(value)
+	);
+	for (from, to, content) in edges {
+		statement.push_str(&format!(
+			" LET $from = {}; LET $to = {}; RELATE $from->{}->$to CONTENT {};",
+			from, to, table, content
+		));
+	}
+	statement.push_str(" COMMIT TRANSACTION;");
+	statement
+}
+
+/// The feeds reached from `products` in `direction`, each with its `depth`, up to `depth` products
+/// away. Feeds reached again further away, e.g. through a cycle, keep the shortest depth.
+pub async fn walk<R: CatalogReader>(
+	reader: &R,
+	products: &[String],
+	direction: Direction,
+	depth: usize,
+) -> Result<Vec<Value>, SurrealDBError> {
+	let traversal =
+		Traversal::new(PRODUCT_TABLE, products, FEEDS_TABLE, direction, depth).order_by(&["port"]);
+	Ok(by_depth(reader.traverse(&traversal).await?))
+}
+
+/// The products consuming `product`, directly or through other products.
+pub async fn product_impact<R: CatalogReader>(
+	reader: &R,
+	product: &str,
+) -> Result<Impact, SurrealDBError> {
+	let feeds =
+		walk(reader, &[product.to_string()], Direction::Downstream, MAX_LINEAGE_DEPTH).await?;
+	let mut consumers = consumers(&feeds);
+	// Lineage cycles lead back to the product itself.
+	consumers.retain(|consumer| consumer != product);
+	Ok(Impact {
+		schemas: Vec::new(),
+		consumers,
+		feeds,
+	})
+}
+
+/// The schema versions derived from `version` of `schema` and the products consuming any of
+/// them, directly or through other products.
+pub async fn schema_impact<R: CatalogReader>(
+	reader: &R,
+	schema: &str,
+	version: &str,
+) -> Result<Impact, SurrealDBError> {
+	let changed = SchemaVersion {
+		schema: schema.to_string(),
+		version: version.to_string(),
+	};
+	// Derivations relate schemas, so the versions derived from the changed one are followed
+	// through the derivations reachable from its schema.
+	let traversal = Traversal::new(
+		SCHEMA_TABLE,
+		&[schema.to_string()],
+		DERIVES_TABLE,
+		Direction::Downstream,
+		MAX_LINEAGE_DEPTH,
+	)
+	.order_by(&["flow", "step"]);
+	let derivations = by_depth(reader.traverse(&traversal).await?);
+
+	let mut seen = BTreeSet::from([changed.clone()]);
+	let mut schemas = vec![changed];
+	let mut index = 0;
+	while index < schemas.len() {
+		for derivation in &derivations {
+			let derived = match (
+				derivation["schema"] == schemas[index].schema,
+				derivation["version"] == schemas[index].version,
+				derivation["derived"].as_str(),
+				derivation["derivedVersion"].as_str(),
+			) {
+				(true, true, Some(schema), Some(version)) => SchemaVersion {
+					schema: schema.to_string(),
+					version: version.to_string(),
+				},
+				_ => continue,
+			};
+			if seen.insert(derived.clone()) {
+				schemas.push(derived);
+			}
+		}
+		index += 1;
+	}
+
+	let mut feeds = Vec::new();
+	for SchemaVersion {
+		schema,
+		version,
+	} in &schemas
+	{
+		let select = Select::from(FEEDS_TABLE)
+			.equals("schema", schema.as_str())
+			.equals("schemaVersion", version.as_str())
+			.order_by(&["port"]);
+		for mut feed in select_all(reader, select).await? {
+			feed["depth"] = json!(1);
+			feeds.push(feed);
+		}
+	}
+
+	let direct = consumers(&feeds);
+	for mut feed in walk(reader, &direct, Direction::Downstream, MAX_LINEAGE_DEPTH - 1).await? {
+		feed["depth"] = json!(feed["depth"].as_u64().unwrap_or_default() + 1);
+		feeds.push(feed);
+	}
+
+	Ok(Impact {
+		schemas,
+		consumers: consumers(&feeds),
+		feeds,
+	})
+}
+
+/// The edges reached at each depth, each once with the first depth it is reached at and without
+/// its record ids.
+fn by_depth(depths: Vec<Vec<Value>>) -> Vec<Value> {
+	let mut seen = BTreeSet::new();
+	let mut edges = Vec::new();
+	for (depth, reached) in depths.into_iter().enumerate() {
+		for mut edge in reached {
+			if !seen.insert(edge["id"].to_string()) {
+				continue;
+			}
+			if let Some(edge) = edge.as_object_mut() {
+				for field in ["id", "in", "out"] {
+					edge.remove(field);
+				}
+			}
+			edge["depth"] = json!(depth + 1);
+			edges.push(edge);
+		}
+	}
+	edges
+}
+
+/// Every record matching `select`, read page by page.
+async fn select_all<R: CatalogReader>(
+	reader: &R,
+	select: Select,
+) -> Result<Vec<Value>, SurrealDBError> {
+	let limit = select.limit;
+	let mut records = Vec::new();
+	loop {
+		let page = reader.select(&select.clone().page(records.len(), limit)).await?;
+		let done = page.len() < limit;
+		records.extend(page);
+		if done {
+			return Ok(records);
+		}
+	}
+}
+
+fn consumers(feeds: &[Value]) -> Vec<String> {
+	let consumers: BTreeSet<&str> =
+		feeds.iter().filter_map(|feed| feed["product"].as_str()).collect();
+	consumers.into_iter().map(str::to_string).collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::catalog::testing;
+
+	fn feed(port: &str, upstream: &str, product: &str, schema: Option<(&str, &str)>) -> Feed {
+		Feed {
+			port: port.to_string(),
+			product: product.to_string(),
+			source: format!("{}-api", upstream),
+			upstream: upstream.to_string(),
+			contract: None,
+			schema: schema.map(|(schema, _)| schema.to_string()),
+			schema_version: schema.map(|(_, version)| version.to_string()),
+		}
+	}
+
+	async fn catalog() -> Catalog {
+		let catalog = testing::catalog().await;
+		let feeds = [
+			feed("crm/orders", "shop/orders", "crm/leads", Some(("shop/orders", "v1"))),
+			feed("crm/stock", "shop/orders", "crm/leads", Some(("shop/stock", "v1"))),
+			feed("bi/leads", "crm/leads", "bi/revenue", None),
+			feed("shop/revenue", "bi/revenue", "shop/orders", None),
+			feed("ops/orders", "shop/orders", "ops/shipping", Some(("shop/orders", "v2"))),
+		];
+		for feed in feeds {
+			catalog.relate_feed(&feed.port, Some(&feed)).await.unwrap();
+		}
+
+		let derivation = Derivation {
+			flow: "shop/daily".to_string(),
+			step: "enrich".to_string(),
+			product: "shop/orders".to_string(),
+			schema: "shop/raw".to_string(),
+			version: "v3".to_string(),
+			derived: "shop/stock".to_string(),
+			derived_version: "v1".to_string(),
+		};
+		catalog
+			.relate_derivations(&derivation.flow, std::slice::from_ref(&derivation))
+			.await
+			.unwrap();
+		catalog
+	}
+
+	#[test]
+	fn edges_are_replaced_in_one_transaction() {
+		let edge = (
+			thing(PRODUCT_TABLE, "shop/orders"),
+			thing(PRODUCT_TABLE, "crm/leads"),
+			json!({ "port": "crm/orders" }),
+		);
+
+		assert_eq!(
+			relate_statement(FEEDS_TABLE, "port", "crm/orders", &[edge]),
+			r#"BEGIN TRANSACTION; DELETE type::table("feeds") WHERE port = "crm/orders"; LET $from = type::thing("product", "shop/orders"); LET $to = type::thing("product", "crm/leads"); RELATE $from->feeds->$to CONTENT {"port":"crm/orders"}; COMMIT TRANSACTION;"#
+		);
+		assert_eq!(
+			relate_statement(DERIVES_TABLE, "flow", "shop/daily", &[]),
+			r#"BEGIN TRANSACTION; DELETE type::table("derives") WHERE flow = "shop/daily"; COMMIT TRANSACTION;"#
+		);
+	}
+
+	#[tokio::test]
+	async fn feeds_are_only_related_again_when_they_change() {
+		let catalog = catalog().await;
+		let held = feed("crm/orders", "shop/orders", "crm/leads", Some(("shop/orders", "v1")));
+		let statement = edges_statement(FEEDS_TABLE, "port", "crm/orders");
+		let edge = |results: Vec<Value>| results[0][0]["id"].clone();
+
+		let before = edge(catalog.client.query(&statement).await.unwrap());
+		catalog.relate_feed(&held.port, Some(&held)).await.unwrap();
+		let unchanged = edge(catalog.client.query(&statement).await.unwrap());
+		let changed = feed("crm/orders", "shop/orders", "crm/leads", Some(("shop/orders", "v2")));
+		catalog.relate_feed(&changed.port, Some(&changed)).await.unwrap();
+		let after = edge(catalog.client.query(&statement).await.unwrap());
+		catalog.relate_feed(&changed.port, None).await.unwrap();
+
+		assert_eq!(before, unchanged);
+		assert_ne!(before, after);
+		assert_eq!(catalog.client.query(&statement).await.unwrap()[0], json!([]));
+	}
+
+	#[tokio::test]
+	async fn walks_stop_at_products_already_reached() {
+		let catalog = catalog().await;
+
+		let downstream =
+			walk(&catalog, &["crm/leads".to_string()], Direction::Downstream, MAX_LINEAGE_DEPTH)
+				.await
+				.unwrap();
+		let ports: Vec<&Value> = downstream.iter().map(|feed| &feed["port"]).collect();
+		assert_eq!(
+			ports,
+			vec!["bi/leads", "shop/revenue", "crm/orders", "crm/stock", "ops/orders"]
+		);
+		assert_eq!(downstream[4]["depth"], 3);
+
+		let upstream =
+			walk(&catalog, &["crm/leads".to_string()], Direction::Upstream, 1).await.unwrap();
+		let sources: Vec<&Value> = upstream.iter().map(|feed| &feed["upstream"]).collect();
+		assert_eq!(sources, vec!["shop/orders", "shop/orders"]);
+	}
+
+	#[tokio::test]
+	async fn schema_changes_reach_the_consumers_of_derived_schemas() {
+		let impact = schema_impact(&catalog().await, "shop/raw", "v3").await.unwrap();
+
+		assert_eq!(
+			impact.schemas,
+			vec![
+				SchemaVersion {
+					schema: "shop/raw".to_string(),
+					version: "v3".to_string(),
+				},
+				SchemaVersion {
+					schema: "shop/stock".to_string(),
+					version: "v1".to_string(),
+				},
+			]
+		);
+		assert_eq!(impact.feeds[0]["port"], "crm/stock");
+		assert_eq!(
+			impact.consumers,
+			vec!["bi/revenue", "crm/leads", "ops/shipping", "shop/orders"]
+		);
+
+		let unused = schema_impact(&catalog().await, "shop/orders", "v3").await.unwrap();
+		assert!(unused.consumers.is_empty());
+	}
+
+	#[tokio::test]
+	async fn product_changes_reach_every_downstream_product() {
+		let impact = product_impact(&catalog().await, "bi/revenue").await.unwrap();
+
+		assert!(impact.schemas.is_empty());
+		assert_eq!(impact.consumers, vec!["crm/leads", "ops/shipping", "shop/orders"]);
+	}
+}
@@ -0,0 +1,193 @@
+pub mod lineage;
+pub mod query;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+
+use std::env;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{
+	core::{constants::OPERATOR_NAMESPACE, operator::OperatorError},
+	surrealdb::client::{SurrealDBClient, SurrealDBError},
+};
+
+pub const CATALOG_URL_ENV: &str = "DAPPMESH_CATALOG_URL";
+pub const CATALOG_NAMESPACE_ENV: &str = "DAPPMESH_CATALOG_NAMESPACE";
+pub const CATALOG_DATABASE_ENV: &str = "DAPPMESH_CATALOG_DATABASE";
+pub const CATALOG_USER_ENV: &str = "DAPPMESH_CATALOG_USER";
+pub const CATALOG_PASSWORD_ENV: &str = "DAPPMESH_CATALOG_PASSWORD";
+
+pub const DOMAIN_TABLE: &str = "domain";
+pub const PRODUCT_TABLE: &str = "product";
+pub const SCHEMA_TABLE: &str = "schema";
+pub const CONTRACT_TABLE: &str = "contract";
+pub const PORT_TABLE: &str = "port";
+/// The tables the mesh operator keeps from the resources of the cluster.
+pub const TABLES: [&str; 5] =
+	[DOMAIN_TABLE, PRODUCT_TABLE, SCHEMA_TABLE, CONTRACT_TABLE, PORT_TABLE];
+/// Edges relating products, written by the product operator from InputPorts.
+pub const FEEDS_TABLE: &str = "feeds";
+/// Edges relating schemas, written by the product operator from DataFlows.
+pub const DERIVES_TABLE: &str = "derives";
+
+/// The mesh metadata catalog, kept in the SurrealDB instance of the mesh.
+#[derive(Clone)]
+pub struct Catalog {
+	client: SurrealDBClient,
+}
+
+/// Where the catalog is kept and the user the operators reach it as, from the environment.
+#[derive(Clone, Debug)]
+pub struct CatalogConfig {
+	pub url: String,
+	pub namespace: String,
+	pub database: String,
+	pub user: String,
+	pub password: String,
+}
+
+impl CatalogConfig {
+	/// The configuration, if `DAPPMESH_CATALOG_URL` is set. Namespace and database default to
+	/// `dappmesh` and `catalog`, user and password have to be set.
+	pub fn from_env() -> Result<Option<Self>, OperatorError> {
+		let Some(url) = env::var(CATALOG_URL_ENV).ok().filter(|url| !url.is_empty()) else {
+			return Ok(None);
+		};
+		let var = |name, default: &str| env::var(name).unwrap_or_else(|_| default.to_string());
+		let required = |name| {
+			env::var(name).ok().filter(|value| !value.is_empty()).ok_or_else(|| {
+				let message = format!("{} is required along with {}", name, CATALOG_URL_ENV);
+				OperatorError::ConfigurationError(message)
+			})
+		};
+
+		Ok(Some(Self {
+			url: url.trim_end_matches('/').to_string(),
+			namespace: var(CATALOG_NAMESPACE_ENV, OPERATOR_NAMESPACE),
+			database: var(CATALOG_DATABASE_ENV, "catalog"),
+			user: required(CATALOG_USER_ENV)?,
+			password: required(CATALOG_PASSWORD_ENV)?,
+		}))
+	}
+
+	/// Defines the user through `root`, a client of the instance keeping the catalog with root
+	/// access to it. The user may edit the catalog database and nothing else.
+	pub async fn define_user(&self, root: &SurrealDBClient) -> Result<(), SurrealDBError> {
+		root.query(&define_user_statement(&self.user, &self.password)?).await?;
+		Ok(())
+	}
+
+	fn client(&self) -> SurrealDBClient {
+		SurrealDBClient::new(&self.url, &self.namespace, &self.database, &self.user, &self.password)
+	}
+}
+
+impl Catalog {
+	pub fn new(client: SurrealDBClient) -> Self {
+		Self {
+			client,
+		}
+	}
+
+	/// The catalog at `DAPPMESH_CATALOG_URL`, if set. See [`CatalogConfig::from_env`].
+	pub fn from_env() -> Result<Option<Self>, OperatorError> {
+		Ok(CatalogConfig::from_env()?.map(|config| Self::new(config.client())))
+	}
+
+	/// Creates or replaces the record `table:id`.
+	pub async fn upsert<T: Serialize>(
+		&self,
+		table: &str,
+		id: &str,
+		record: &T,
+	) -> Result<(), SurrealDBError> {
+		let statement =
+			format!("UPDATE {} CONTENT {};", thing(table, id), serde_json::to_string(record)?);
+		self.client.query(&statement).await?;
+		Ok(())
+	}
+
+	pub async fn remove(&self, table: &str, id: &str) -> Result<(), SurrealDBError> {
+		self.client.query(&format!("DELETE {};", thing(table, id))).await?;
+		Ok(())
+	}
+
+	/// Removes the records of `table` except those in `keep`.
+	pub async fn prune(&self, table: &str, keep: &[String]) -> Result<(), SurrealDBError> {
+		self.client.query(&prune_statement(table, keep)?).await?;
+		Ok(())
+	}
+}
+
+/// Identifies a record of a namespaced resource, e.g. `shop/orders/api`.
+pub fn record_id(parts: &[&str]) -> String {
+	parts.join("/")
+}
+
+/// A record id built with `type::thing`, so that ids need no escaping beyond JSON strings.
+fn thing(table: &str, id: &str) -> String {
+	format!("type::thing({}, {})", Value::from(table), Value::from(id))
+}
+
+fn define_user_statement(user: &str, password: &str) -> Result<String, serde_json::Error> {
+	Ok(format!(
+		"DEFINE USER `{}` ON DATABASE PASSWORD {} ROLES EDITOR;",
+		user.replace('`', ""),
+		serde_json::to_string(password)?
+	))
+}
+
+fn prune_statement(table: &str, keep: &[String]) -> Result<String, serde_json::Error> {
+	Ok(format!(
+		"DELETE type::table({}) WHERE meta::id(id) NOT IN {};",
+		Value::from(table),
+		serde_json::to_string(keep)?
+	))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn record_ids_are_quoted_as_strings() {
+		let id = record_id(&["shop", "orders", "it's"]);
+
+		assert_eq!(thing(PORT_TABLE, &id), r#"type::thing("port", "shop/orders/it's")"#);
+	}
+
+	#[tokio::test]
+	async fn the_catalog_user_may_only_edit_the_catalog_database() {
+		let catalog = testing::catalog().await;
+		let config = CatalogConfig {
+			url: String::new(),
+			namespace: OPERATOR_NAMESPACE.to_string(),
+			database: "catalog".to_string(),
+			user: "catalog".to_string(),
+			password: "it's \"secret\"".to_string(),
+		};
+
+		config.define_user(&catalog.client).await.unwrap();
+		let info = catalog.client.query("INFO FOR DB;").await.unwrap();
+
+		let user = info[0]["users"]["catalog"].as_str().unwrap();
+
+		assert!(user.contains("ON DATABASE") && user.contains("ROLES EDITOR"));
+		assert_eq!(
+			define_user_statement("cata`log", "it's").unwrap(),
+			r#"DEFINE USER `catalog` ON DATABASE PASSWORD "it's" ROLES EDITOR;"#
+		);
+	}
+
+	#[test]
+	fn pruning_keeps_the_listed_records() {
+		let statement = prune_statement(DOMAIN_TABLE, &["shop/sales".to_string()]);
+
+		assert_eq!(
+			statement.unwrap(),
+			r#"DELETE type::table("domain") WHERE meta::id(id) NOT IN ["shop/sales"];"#
+		);
+	}
+}
@@ -0,0 +1,223 @@
+use std::future::Future;
+
+use serde_json::Value;
+
+use super::{lineage::Direction, thing, Catalog};
+use crate::surrealdb::client::SurrealDBError;
+
+/// A condition on the records of a table.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Filter {
+	/// The field at the dotted `path`, e.g. `owner.team`, equals the value.
+	Equals(String, Value),
+	/// The record carries the label, with the value if one is given.
+	Label(String, Option<String>),
+}
+
+/// A page of the records of a table matching every filter, ordered by `order_by`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Select {
+	pub table: String,
+	pub filters: Vec<Filter>,
+	pub order_by: Vec<String>,
+	pub start: usize,
+	pub limit: usize,
+}
+
+impl Select {
+	/// The first 50 records of `table` ordered by namespace and name.
+	pub fn from(table: &str) -> Self {
+		Self {
+			table: table.to_string(),
+			filters: Vec::new(),
+			order_by: vec!["namespace".to_string(), "name".to_string()],
+			start: 0,
+			limit: 50,
+		}
+	}
+
+	pub fn filter(mut self, filter: Filter) -> Self {
+		self.filters.push(filter);
+		self
+	}
+
+	pub fn equals(self, path: &str, value: impl Into<Value>) -> Self {
+		self.filter(Filter::Equals(path.to_string(), value.into()))
+	}
+
+	pub fn order_by(mut self, fields: &[&str]) -> Self {
+		self.order_by = fields.iter().map(|field| field.to_string()).collect();
+		self
+	}
+
+	pub fn page(mut self, start: usize, limit: usize) -> Self {
+		self.start = start;
+		self.limit = limit;
+		self
+	}
+
+	/// The statement selecting the records, leaving out their record ids.
+	pub fn to_surrealql(&self) -> String {
+		let mut statement =
+			format!("SELECT * OMIT id, in, out FROM type::table({})", Value::from(&*self.table));
+		let conditions: Vec<String> = self
+			.filters
+			.iter()
+			.map(|filter| match filter {
+				Filter::Equals(path, value) => format!("{} = {}", path, value),
+				Filter::Label(key, Some(value)) => {
+					format!("labels[{}] = {}", Value::from(&**key), Value::from(&**value))
+				}
+				Filter::Label(key, None) => format!("labels[{}] != NONE", Value::from(&**key)),
+			})
+			.collect();
+		if !conditions.is_empty() {
+			statement.push_str(&format!(" WHERE {}", conditions.join(" AND ")));
+		}
+		if !self.order_by.is_empty() {
+			statement.push_str(&format!(" ORDER BY {}", self.order_by.join(", ")));
+		}
+		format!("{} LIMIT {} START {};", statement, self.limit, self.start)
+	}
+}
+
+/// The edges of `edge` reached from the records `from` of `table` in `direction`, up to `depth`
+/// edges away.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Traversal {
+	pub table: String,
+	pub from: Vec<String>,
+	pub edge: String,
+	pub direction: Direction,
+	pub depth: usize,
+	pub order_by: Vec<String>,
+}
+
+impl Traversal {
+	pub fn new(
+		table: &str,
+		from: &[String],
+		edge: &str,
+		direction: Direction,
+		depth: usize,
+	) -> Self {
+		Self {
+			table: table.to_string(),
+			from: from.to_vec(),
+			edge: edge.to_string(),
+			direction,
+			depth,
+			order_by: Vec::new(),
+		}
+	}
+
+	pub fn order_by(mut self, fields: &[&str]) -> Self {
+		self.order_by = fields.iter().map(|field| field.to_string()).collect();
+		self
+	}
+
+	/// Selects the edges of each depth, with their record ids, by stepping from the records the
+	/// edges of the previous depth reached, which need not exist themselves. The select of each
+	/// depth follows the statement collecting its edges, see [`Traversal::selects`].
+	pub fn to_surrealql(&self) -> String {
+		let (arrow, reached) = match self.direction {
+			Direction::Downstream => ("->", "out"),
+			Direction::Upstream => ("<-", "in"),
+		};
+		let from: Vec<String> = self.from.iter().map(|id| thing(&self.table, id)).collect();
+		let order = if self.order_by.is_empty() {
+			String::new()
+		} else {
+			format!(" ORDER BY {}", self.order_by.join(", "))
+		};
+
+		let mut statement = format!("LET $reached = [{}];", from.join(", "));
+		for _ in 0..self.depth {
+			statement.push_str(&format!(
+				" LET $edges = array::flatten($reached{}{});",
+				arrow, self.edge
+			));
+			statement.push_str(&format!(" SELECT * FROM $edges{};", order));
+			statement.push_str(&format!(" LET $reached = array::distinct($edges.{});", reached));
+		}
+		statement
+	}
+
+	/// The results of the selects among the results of every statement of the traversal.
+	pub fn selects(results: Vec<Value>) -> impl Iterator<Item = Value> {
+		results.into_iter().skip(2).step_by(3)
+	}
+}
+
+/// Read access to the catalog.
+pub trait CatalogReader: Send + Sync {
+	fn select(
+		&self,
+		select: &Select,
+	) -> impl Future<Output = Result<Vec<Value>, SurrealDBError>> + Send;
+
+	/// The edges reached at each depth of the traversal, starting with those one step away.
+	fn traverse(
+		&self,
+		traversal: &Traversal,
+	) -> impl Future<Output = Result<Vec<Vec<Value>>, SurrealDBError>> + Send;
+}
+
+impl CatalogReader for Catalog {
+	async fn select(&self, select: &Select) -> Result<Vec<Value>, SurrealDBError> {
+		let results = self.client.query(&select.to_surrealql()).await?;
+		Ok(match results.into_iter().next() {
+			Some(Value::Array(records)) => records,
+			_ => Vec::new(),
+		})
+	}
+
+	async fn traverse(&self, traversal: &Traversal) -> Result<Vec<Vec<Value>>, SurrealDBError> {
+		if traversal.from.is_empty() || traversal.depth == 0 {
+			return Ok(Vec::new());
+		}
+		let results = self.client.query(&traversal.to_surrealql()).await?;
+		Ok(Traversal::selects(results)
+			.map(|result| match result {
+				Value::Array(edges) => edges,
+				_ => Vec::new(),
+			})
+			.collect())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn selects_are_rendered_with_quoted_values() {
+		let select = Select::from("product")
+			.equals("owner.team", "it's")
+			.filter(Filter::Label("tier".to_string(), Some("gold".to_string())))
+			.filter(Filter::Label("pii".to_string(), None))
+			.page(100, 51);
+
+		assert_eq!(
+			select.to_surrealql(),
+			r#"SELECT * OMIT id, in, out FROM type::table("product") WHERE owner.team = "it's" AND labels["tier"] = "gold" AND labels["pii"] != NONE ORDER BY namespace, name LIMIT 51 START 100;"#
+		);
+	}
+
+	#[test]
+	fn traversals_step_from_the_records_reached_before() {
+		let traversal = Traversal::new(
+			"product",
+			&["shop/orders".to_string()],
+			"feeds",
+			Direction::Upstream,
+			2,
+		)
+		.order_by(&["port"]);
+
+		assert_eq!(
+			traversal.to_surrealql(),
+			r#"LET $reached = [type::thing("product", "shop/orders")]; LET $edges = array::flatten($reached<-feeds); SELECT * FROM $edges ORDER BY port; LET $reached = array::distinct($edges.in); LET $edges = array::flatten($reached<-feeds); SELECT * FROM $edges ORDER BY port; LET $reached = array::distinct($edges.in);"#
+		);
+	}
+}
@@ -0,0 +1,123 @@
+use std::convert::Infallible;
+
+use ::surrealdb::{
+	engine::local::{Db, Mem},
+	sql, Surreal,
+};
+use http_body_util::{BodyExt, Full};
+use hyper::{
+	body::{Bytes, Incoming},
+	server::conn::http1,
+	service::service_fn,
+	Request, Response, StatusCode,
+};
+use hyper_util::rt::TokioIo;
+use serde_json::{json, Value};
+use tokio::net::TcpListener;
+
+use super::Catalog;
+use crate::{core::constants::OPERATOR_NAMESPACE, surrealdb::client::SurrealDBClient};
+
+/// An empty catalog kept by an in-memory SurrealDB, which answers the statements of the catalog
+/// on an HTTP `/sql` endpoint the way the mesh SurrealDB does.
+pub async fn catalog() -> Catalog {
+	let db = Surreal::new::<Mem>(()).await.expect("in-memory SurrealDB starts");
+	let listener = TcpListener::bind("127.0.0.1:0").await.expect("a local port is free");
+	let url = format!("http://{}", listener.local_addr().expect("listener is bound"));
+
+	tokio::spawn(async move {
+		while let Ok((stream, _)) = listener.accept().await {
+			let db = db.clone();
+			tokio::spawn(async move {
+				let service = service_fn(|request| sql(db.clone(), request));
+				let _ = http1::Builder::new().serve_connection(TokioIo::new(stream), service).await;
+			});
+		}
+	});
+
+	Catalog::new(SurrealDBClient::new(&url, OPERATOR_NAMESPACE, "catalog", "root", "root"))
+}
+
+/// Runs the statements of the request body in the namespace and database of its headers.
+async fn sql(
+	db: Surreal<Db>,
+	request: Request<Incoming>,
+) -> Result<Response<Full<Bytes>>, Infallible> {
+	let header = |name| {
+		let value = request.headers().get(name).and_then(|value| value.to_str().ok());
+		value.unwrap_or_default().to_string()
+	};
+	let (namespace, database) = (header("NS"), header("DB"));
+	let body = match request.into_body().collect().await {
+		Ok(body) => body.to_bytes(),
+		Err(error) => return Ok(respond(StatusCode::BAD_REQUEST, error.to_string())),
+	};
+
+	if let Err(error) = db.use_ns(namespace).use_db(database).await {
+		return Ok(respond(StatusCode::BAD_REQUEST, error.to_string()));
+	}
+	let mut response = match db.query(String::from_utf8_lossy(&body).to_string()).await {
+		Ok(response) => response,
+		Err(error) => return Ok(respond(StatusCode::BAD_REQUEST, error.to_string())),
+	};
+
+	let results: Vec<Value> = (0..response.num_statements())
+		.map(|index| match response.take::<sql::Value>(index) {
+			Ok(result) => json!({ "status": "OK", "result": result.into_json() }),
+			Err(error) => json!({ "status": "ERR", "result": error.to_string() }),
+		})
+		.collect();
+	Ok(respond(StatusCode::OK, Value::from(results).to_string()))
+}
+
+fn respond(status: StatusCode, body: String) -> Response<Full<Bytes>> {
+	let mut response = Response::new(Full::new(Bytes::from(body)));
+	*response.status_mut() = status;
+	response
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::catalog::{
+		query::{CatalogReader, Filter, Select},
+		PRODUCT_TABLE,
+	};
+
+	#[tokio::test]
+	async fn selects_are_filtered_ordered_and_paged() {
+		let catalog = catalog().await;
+		for (namespace, name, team) in
+			[("shop", "orders", "sales"), ("crm", "leads", "sales"), ("shop", "stock", "ops")]
+		{
+			let record = json!({
+				"namespace": namespace,
+				"name": name,
+				"owner": { "team": team },
+				"labels": { "tier": "gold" },
+			});
+			catalog
+				.upsert(PRODUCT_TABLE, &format!("{}/{}", namespace, name), &record)
+				.await
+				.unwrap();
+		}
+		catalog.remove(PRODUCT_TABLE, "shop/stock").await.unwrap();
+
+		let all = catalog.select(&Select::from(PRODUCT_TABLE)).await.unwrap();
+		let names: Vec<&Value> = all.iter().map(|record| &record["name"]).collect();
+		assert_eq!(names, vec!["leads", "orders"]);
+		assert!(all[0].get("id").is_none());
+
+		let page = catalog.select(&Select::from(PRODUCT_TABLE).page(1, 1)).await.unwrap();
+		assert_eq!(page.len(), 1);
+		assert_eq!(page[0]["name"], "orders");
+
+		let gold = Select::from(PRODUCT_TABLE)
+			.equals("owner.team", "sales")
+			.filter(Filter::Label("tier".to_string(), Some("gold".to_string())));
+		assert_eq!(catalog.select(&gold).await.unwrap().len(), 2);
+		let ops = Select::from(PRODUCT_TABLE).equals("owner.team", "ops");
+		assert!(catalog.select(&ops).await.unwrap().is_empty());
+		assert!(catalog.select(&Select::from("port")).await.unwrap().is_empty());
+	}
+}
@@ -0,0 +1,44 @@
+use k8s_openapi::{
+	api::core::v1::{Affinity, PodAffinityTerm, PodAntiAffinity, WeightedPodAffinityTerm},
+	apimachinery::pkg::apis::meta::v1::LabelSelector,
+};
+use std::collections::BTreeMap;
+
+pub const HOSTNAME_TOPOLOGY_KEY: &str = "kubernetes.io/hostname";
+pub const ZONE_TOPOLOGY_KEY: &str = "topology.kubernetes.io/zone";
+
+/// Prefers spreading the pods matched by `labels` across the given topology domains without
+/// ever blocking scheduling. Earlier keys weigh more, so the default spreads across nodes
+/// before zones. Returns `None` when no topology key is given.
+pub fn soft_pod_anti_affinity(
+	labels: &BTreeMap<String, String>,
+	topology_keys: &[String],
+) -> Option<Affinity> {
+	if topology_keys.is_empty() {
+		return None;
+	}
+
+	let terms = topology_keys
+		.iter()
+		.enumerate()
+		.map(|(index, topology_key)| WeightedPodAffinityTerm {
+			weight: 100 / (index as i32 + 1),
+			pod_affinity_term: PodAffinityTerm {
+				label_selector: Some(LabelSelector {
+					match_expressions: None,
+					match_labels: Some(labels.clone()),
+				}),
+				topology_key: topology_key.clone(),
+				..PodAffinityTerm::default()
+			},
+		})
+		.collect();
+
+	Some(Affinity {
+		pod_anti_affinity: Some(PodAntiAffinity {
+			preferred_during_scheduling_ignored_during_execution: Some(terms),
+			..PodAntiAffinity::default()
+		}),
+		..Affinity::default()
+	})
+}
@@ -0,0 +1,8 @@
+/// Name of the Deployment, Service and ServiceAccount of the mesh operator.
+pub const MESH_OPERATOR: &str = "dapp-mesh-operator";
+/// Name of the Deployment, Service and ServiceAccount of the domain operator.
+pub const DOMAIN_OPERATOR: &str = "dapp-domain-operator";
+/// Name of the Deployment, Service and ServiceAccount of the product operator.
+pub const PRODUCT_OPERATOR: &str = "dapp-product-operator";
+/// Namespace every DappMesh operator runs in.
+pub const OPERATOR_NAMESPACE: &str = "dappmesh";
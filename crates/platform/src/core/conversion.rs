@@ -0,0 +1,247 @@
+use std::collections::BTreeMap;
+
+use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::{
+	CustomResourceConversion, CustomResourceDefinition, ServiceReference, WebhookClientConfig,
+	WebhookConversion,
+};
+use kube::Resource;
+use kube_core::{
+	conversion::{ConversionRequest, ConversionResponse, ConversionReview},
+	Status,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+/// The annotation cert-manager reads to inject the CA of the webhook certificate.
+pub const INJECT_CA_FROM: &str = "cert-manager.io/inject-ca-from";
+pub const WEBHOOK_SERVICE_PORT: i32 = 443;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConversionError {
+	#[error("Object does not match its version: {0}")]
+	Json(#[from] serde_json::Error),
+	#[error("Cannot convert {from} to {to}")]
+	Unsupported {
+		from: String,
+		to: String,
+	},
+}
+
+/// Converts `object` to `desired_api_version`, where `Old` and `New` are the Rust types of
+/// two versions of the same kind.
+pub fn convert<Old, New>(object: Value, desired_api_version: &str) -> Result<Value, ConversionError>
+where
+	Old: Resource<DynamicType = ()> + Serialize + DeserializeOwned + From<New>,
+	New: Resource<DynamicType = ()> + Serialize + DeserializeOwned + From<Old>,
+{
+	let api_version = object.get("apiVersion").and_then(Value::as_str).unwrap_or_default();
+	if api_version == desired_api_version {
+		return Ok(object);
+	}
+
+	let (old, new) = (Old::api_version(&()), New::api_version(&()));
+	if api_version == old && desired_api_version == new {
+		Ok(serde_json::to_value(New::from(serde_json::from_value::<Old>(object)?))?)
+	} else if api_version == new && desired_api_version == old {
+		Ok(serde_json::to_value(Old::from(serde_json::from_value::<New>(object)?))?)
+	} else {
+		Err(ConversionError::Unsupported {
+			from: api_version.to_string(),
+			to: desired_api_version.to_string(),
+		})
+	}
+}
+
+/// Answers a ConversionReview of the API server, failing the whole review if one of its
+/// objects cannot be converted.
+pub fn review<F>(review: ConversionReview, convert: F) -> ConversionReview
+where
+	F: Fn(Value, &str) -> Result<Value, ConversionError>,
+{
+	let request = match ConversionRequest::from_review(review) {
+		Ok(request) => request,
+		Err(error) => {
+			return ConversionResponse::invalid(Status::failure(&error.to_string(), "BadRequest"))
+				.into_review()
+		}
+	};
+
+	let desired_api_version = request.desired_api_version.clone();
+	let converted: Result<Vec<Value>, ConversionError> = request
+		.objects
+		.iter()
+		.map(|object| convert(object.clone(), &desired_api_version))
+		.collect();
+
+	let response = ConversionResponse::for_request(request);
+	match converted {
+		Ok(objects) => response.success(objects),
+		Err(error) => response.failure(Status::failure(&error.to_string(), "ConversionFailed")),
+	}
+	.into_review()
+}
+
+/// Makes the API server convert between the versions of `crd` through the webhook served at
+/// `path` by `service`, trusting the CA cert-manager injects from the certificate of the same
+/// name.
+pub fn with_webhook(
+	mut crd: CustomResourceDefinition,
+	service: &str,
+	namespace: &str,
+	path: &str,
+) -> CustomResourceDefinition {
+	crd.metadata
+		.annotations
+		.get_or_insert_with(BTreeMap::new)
+		.insert(INJECT_CA_FROM.to_string(), format!("{}/{}", namespace, service));
+
+	crd.spec.conversion = Some(CustomResourceConversion {
+		strategy: "Webhook".to_string(),
+		webhook: Some(WebhookConversion {
+			client_config: Some(WebhookClientConfig {
+				service: Some(ServiceReference {
+					name: service.to_string(),
+					namespace: namespace.to_string(),
+					path: Some(path.to_string()),
+					port: Some(WEBHOOK_SERVICE_PORT),
+				}),
+				..WebhookClientConfig::default()
+			}),
+			conversion_review_versions: vec!["v1".to_string()],
+		}),
+	});
+
+	crd
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use kube::{CustomResource, CustomResourceExt};
+	use schemars::JsonSchema;
+	use serde::Deserialize;
+	use serde_json::json;
+
+	mod v1 {
+		use super::*;
+
+		#[derive(CustomResource, Serialize, Deserialize, Default, Debug, Clone, JsonSchema)]
+		#[kube(group = "example.io", version = "v1", kind = "Widget", namespaced)]
+		pub struct WidgetSpec {
+			pub size: i32,
+		}
+	}
+
+	mod v2 {
+		use super::*;
+
+		#[derive(CustomResource, Serialize, Deserialize, Default, Debug, Clone, JsonSchema)]
+		#[kube(group = "example.io", version = "v2", kind = "Widget", namespaced)]
+		pub struct WidgetSpec {
+			pub width: i32,
+			pub height: i32,
+		}
+	}
+
+	impl From<v1::Widget> for v2::Widget {
+		fn from(widget: v1::Widget) -> Self {
+			Self {
+				metadata: widget.metadata,
+				spec: v2::WidgetSpec {
+					width: widget.spec.size,
+					height: widget.spec.size,
+				},
+			}
+		}
+	}
+
+	impl From<v2::Widget> for v1::Widget {
+		fn from(widget: v2::Widget) -> Self {
+			Self {
+				metadata: widget.metadata,
+				spec: v1::WidgetSpec {
+					size: widget.spec.width.max(widget.spec.height),
+				},
+			}
+		}
+	}
+
+	fn widget(api_version: &str, spec: Value) -> Value {
+		json!({
+			"apiVersion": api_version,
+			"kind": "Widget",
+			"metadata": { "name": "w", "namespace": "shop", "resourceVersion": "7" },
+			"spec": spec,
+		})
+	}
+
+	fn convert_widget(object: Value, desired_api_version: &str) -> Result<Value, ConversionError> {
+		convert::<v1::Widget, v2::Widget>(object, desired_api_version)
+	}
+
+	#[test]
+	fn objects_are_converted_both_ways_keeping_their_metadata() {
+		let old = widget("example.io/v1", json!({ "size": 3 }));
+
+		let new = convert_widget(old, "example.io/v2").unwrap();
+		assert_eq!(new["apiVersion"], "example.io/v2");
+		assert_eq!(new["metadata"]["resourceVersion"], "7");
+		assert_eq!(new["spec"], json!({ "width": 3, "height": 3 }));
+
+		let old = convert_widget(new, "example.io/v1").unwrap();
+		assert_eq!(old, widget("example.io/v1", json!({ "size": 3 })));
+	}
+
+	#[test]
+	fn unknown_versions_are_rejected() {
+		let object = widget("example.io/v3", json!({}));
+
+		assert!(matches!(
+			convert_widget(object, "example.io/v1"),
+			Err(ConversionError::Unsupported { .. })
+		));
+	}
+
+	#[test]
+	fn a_review_fails_when_one_object_cannot_be_converted() {
+		let review = |objects: Vec<Value>| -> ConversionReview {
+			serde_json::from_value(json!({
+				"apiVersion": "apiextensions.k8s.io/v1",
+				"kind": "ConversionReview",
+				"request": { "uid": "42", "desiredAPIVersion": "example.io/v2", "objects": objects },
+			}))
+			.unwrap()
+		};
+
+		let converted = super::review(
+			review(vec![widget("example.io/v1", json!({ "size": 1 }))]),
+			convert_widget,
+		);
+		let response = converted.response.unwrap();
+		assert_eq!(response.uid, "42");
+		assert_eq!(response.converted_objects[0]["spec"]["width"], 1);
+
+		let failed = super::review(
+			review(vec![
+				widget("example.io/v1", json!({ "size": 1 })),
+				widget("example.io/v1", json!({ "size": "large" })),
+			]),
+			convert_widget,
+		);
+		let response = failed.response.unwrap();
+		assert!(response.converted_objects.is_empty());
+		assert_eq!(response.result.reason, "ConversionFailed");
+	}
+
+	#[test]
+	fn the_crd_points_the_api_server_at_the_webhook() {
+		let crd = with_webhook(v1::Widget::crd(), "widget-operator", "ops", "/convert/widgets");
+
+		let annotations = crd.metadata.annotations.unwrap();
+		assert_eq!(annotations[INJECT_CA_FROM], "ops/widget-operator");
+		let conversion = crd.spec.conversion.unwrap();
+		assert_eq!(conversion.strategy, "Webhook");
+		let service = conversion.webhook.unwrap().client_config.unwrap().service.unwrap();
+		assert_eq!(service.path.as_deref(), Some("/convert/widgets"));
+	}
+}
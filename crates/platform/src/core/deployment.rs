@@ -6,7 +6,7 @@ use k8s_openapi::{
 	apimachinery::pkg::apis::meta::v1::{LabelSelector, ObjectMeta},
 };
 
-use super::labels::Labels;
+use super::{labels::Labels, security::SecurityContextConfig};
 
 pub struct NoMetaData;
 
@@ -61,6 +61,12 @@ impl<Spec> DeploymentBuilder<ObjectMeta, Spec> {
 		replicas: i32,
 		containers: Vec<Container>,
 	) -> DeploymentBuilder<ObjectMeta, DeploymentSpec> {
+		let mut pod_spec = PodSpec {
+			containers,
+			..PodSpec::default()
+		};
+		SecurityContextConfig::default().harden(&mut pod_spec);
+
 		let stateful_set_spec = DeploymentSpec {
 			replicas: Some(replicas),
 			selector: LabelSelector {
@@ -68,10 +74,7 @@ impl<Spec> DeploymentBuilder<ObjectMeta, Spec> {
 				match_labels: self.metadata.labels.clone(),
 			},
 			template: PodTemplateSpec {
-				spec: Some(PodSpec {
-					containers,
-					..PodSpec::default()
-				}),
+				spec: Some(pod_spec),
 				metadata: Some(self.metadata.clone()),
 			},
 			..DeploymentSpec::default()
@@ -85,6 +88,19 @@ impl<Spec> DeploymentBuilder<ObjectMeta, Spec> {
 }
 
 impl DeploymentBuilder<ObjectMeta, DeploymentSpec> {
+	/// Replaces the hardened defaults applied by `spec` with the given security context.
+	pub fn security_context(&self, security: &SecurityContextConfig) -> Self {
+		let mut spec = self.spec.clone();
+		if let Some(pod_spec) = spec.template.spec.as_mut() {
+			security.enforce(pod_spec);
+		}
+
+		DeploymentBuilder {
+			metadata: self.metadata.clone(),
+			spec,
+		}
+	}
+
 	pub fn manifest(&self) -> Deployment {
 		Deployment {
 			metadata: self.metadata.clone(),
@@ -0,0 +1,177 @@
+use std::{collections::BTreeMap, fmt};
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// Fields owned by the API server, which never belong to a desired manifest.
+const IGNORED_PATHS: [&str; 7] = [
+	"status",
+	"metadata.creationTimestamp",
+	"metadata.generation",
+	"metadata.managedFields",
+	"metadata.resourceVersion",
+	"metadata.selfLink",
+	"metadata.uid",
+];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldChange {
+	/// Dotted path of the field, with list indices in brackets, e.g. `spec.ports[0].port`.
+	pub path: String,
+	/// `None` when the field is missing from the live object.
+	pub old: Option<Value>,
+	/// `None` when the desired manifest clears the field.
+	pub new: Option<Value>,
+}
+
+/// The fields of a live object that differ from its desired manifest.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ManifestDiff {
+	pub changes: Vec<FieldChange>,
+}
+
+impl ManifestDiff {
+	pub fn is_empty(&self) -> bool {
+		self.changes.is_empty()
+	}
+}
+
+impl fmt::Display for ManifestDiff {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		for change in &self.changes {
+			let old = change.old.as_ref().map_or("<unset>".to_string(), Value::to_string);
+			let new = change.new.as_ref().map_or("<unset>".to_string(), Value::to_string);
+			writeln!(f, "{}: {} -> {}", change.path, old, new)?;
+		}
+		Ok(())
+	}
+}
+
+/// Compares a desired manifest with the live object.
+///
+/// Only fields set in `desired` are compared, so fields the API server fills in with defaults
+/// do not show up as changes. A `null` in `desired` asks for the field to be cleared.
+pub fn diff<K: Serialize>(desired: &K, live: &K) -> ManifestDiff {
+	let desired = serde_json::to_value(desired).unwrap_or_default();
+	let live = serde_json::to_value(live).unwrap_or_default();
+	diff_values(&desired, &live)
+}
+
+/// Like [`diff`], for manifests that are already JSON, e.g. merge patches.
+pub fn diff_values(desired: &Value, live: &Value) -> ManifestDiff {
+	let mut changes = Vec::new();
+	compare(String::new(), desired, Some(live), &mut changes);
+	ManifestDiff {
+		changes,
+	}
+}
+
+fn compare(path: String, desired: &Value, live: Option<&Value>, changes: &mut Vec<FieldChange>) {
+	if IGNORED_PATHS.contains(&path.as_str()) {
+		return;
+	}
+
+	match (desired, live) {
+		(Value::Null, None | Some(Value::Null)) => {}
+		(Value::Null, Some(live)) => changes.push(FieldChange {
+			path,
+			old: Some(live.clone()),
+			new: None,
+		}),
+		(Value::Object(desired), Some(Value::Object(live))) => {
+			compare_objects(&path, desired, live, changes)
+		}
+		(Value::Array(desired), Some(Value::Array(live))) if desired.len() == live.len() => {
+			for (index, (desired, live)) in desired.iter().zip(live).enumerate() {
+				compare(format!("{}[{}]", path, index), desired, Some(live), changes);
+			}
+		}
+		(desired, live) if live != Some(desired) => changes.push(FieldChange {
+			path,
+			old: live.cloned(),
+			new: Some(desired.clone()),
+		}),
+		_ => {}
+	}
+}
+
+fn compare_objects(
+	path: &str,
+	desired: &Map<String, Value>,
+	live: &Map<String, Value>,
+	changes: &mut Vec<FieldChange>,
+) {
+	// Sorted, whether or not serde_json keeps the keys in insertion order.
+	let desired: BTreeMap<&String, &Value> = desired.iter().collect();
+	for (key, value) in desired {
+		let path = if path.is_empty() {
+			key.clone()
+		} else {
+			format!("{}.{}", path, key)
+		};
+		compare(path, value, live.get(key), changes);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json::json;
+
+	#[test]
+	fn server_defaulted_fields_are_ignored() {
+		let desired = json!({ "spec": { "replicas": 1 } });
+		let live = json!({
+			"metadata": { "resourceVersion": "42", "uid": "abc" },
+			"spec": { "replicas": 1, "revisionHistoryLimit": 10 },
+			"status": { "readyReplicas": 1 }
+		});
+
+		assert!(diff_values(&desired, &live).is_empty());
+	}
+
+	#[test]
+	fn changed_fields_are_reported_with_their_path() {
+		let desired = json!({
+			"spec": { "replicas": 3, "ports": [{ "port": 8000 }], "affinity": null }
+		});
+		let live = json!({
+			"spec": { "replicas": 1, "ports": [{ "port": 80 }], "affinity": { "a": 1 } }
+		});
+
+		let changes = diff_values(&desired, &live).changes;
+
+		assert_eq!(
+			changes,
+			vec![
+				FieldChange {
+					path: "spec.affinity".to_string(),
+					old: Some(json!({ "a": 1 })),
+					new: None,
+				},
+				FieldChange {
+					path: "spec.ports[0].port".to_string(),
+					old: Some(json!(80)),
+					new: Some(json!(8000)),
+				},
+				FieldChange {
+					path: "spec.replicas".to_string(),
+					old: Some(json!(1)),
+					new: Some(json!(3)),
+				},
+			]
+		);
+	}
+
+	#[test]
+	fn lists_of_different_length_are_replaced_as_a_whole() {
+		let desired = json!({ "peers": ["a", "b"] });
+		let live = json!({ "peers": ["a"] });
+
+		let changes = diff_values(&desired, &live).changes;
+
+		assert_eq!(changes.len(), 1);
+		assert_eq!(changes[0].path, "peers");
+		assert_eq!(changes[0].new, Some(json!(["a", "b"])));
+	}
+}
@@ -0,0 +1,149 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::{status::Condition, validation::DURATION};
+
+pub const COMPLIANT: &str = "Compliant";
+
+/// Sensitivity of the data a product holds, from least to most sensitive.
+#[derive(
+	Serialize, Deserialize, Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, JsonSchema,
+)]
+pub enum DataClassification {
+	Public,
+	Internal,
+	Confidential,
+	Restricted,
+}
+
+/// What a product declares about its data, checked against the policies of its domain.
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DataGovernance {
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub classification: Option<DataClassification>,
+	/// Whether the data contains personally identifiable information.
+	#[serde(default)]
+	pub pii: bool,
+	/// How long records are kept, e.g. `30d`.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schemars(regex = "DURATION")]
+	pub retention: Option<String>,
+}
+
+/// A check the policy engine runs against every subject, e.g. a product of a domain.
+/// Organization specific rules implement it next to the built-in ones.
+pub trait GovernanceRule<S>: Send + Sync {
+	fn name(&self) -> &str;
+
+	/// Why `subject` breaks the rule, empty if it follows it.
+	fn check(&self, subject: &S) -> Vec<String>;
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Violation {
+	pub rule: String,
+	pub message: String,
+}
+
+/// Runs its rules against subjects, collecting every violation.
+pub struct PolicyEngine<S> {
+	rules: Vec<Box<dyn GovernanceRule<S>>>,
+}
+
+impl<S> Default for PolicyEngine<S> {
+	fn default() -> Self {
+		Self {
+			rules: Vec::new(),
+		}
+	}
+}
+
+impl<S> PolicyEngine<S> {
+	pub fn rule(mut self, rule: impl GovernanceRule<S> + 'static) -> Self {
+		self.rules.push(Box::new(rule));
+		self
+	}
+
+	pub fn evaluate(&self, subject: &S) -> Vec<Violation> {
+		self.rules
+			.iter()
+			.flat_map(|rule| {
+				rule.check(subject).into_iter().map(|message| Violation {
+					rule: rule.name().to_string(),
+					message,
+				})
+			})
+			.collect()
+	}
+}
+
+/// The Compliant condition of a subject with `violations`.
+pub fn compliance(violations: &[Violation]) -> Condition {
+	if violations.is_empty() {
+		return Condition::new(COMPLIANT, true, "PoliciesMet", "All policies are met.");
+	}
+
+	let messages: Vec<String> = violations
+		.iter()
+		.map(|violation| format!("{}: {}", violation.rule, violation.message))
+		.collect();
+	Condition::new(COMPLIANT, false, "PolicyViolation", &messages.join("; "))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	struct MaxLength(usize);
+
+	impl GovernanceRule<String> for MaxLength {
+		fn name(&self) -> &str {
+			"MaxLength"
+		}
+
+		fn check(&self, subject: &String) -> Vec<String> {
+			match subject.len() > self.0 {
+				true => vec![format!("{} is longer than {}", subject, self.0)],
+				false => Vec::new(),
+			}
+		}
+	}
+
+	#[test]
+	fn every_rule_is_evaluated() {
+		let engine = PolicyEngine::default().rule(MaxLength(8)).rule(MaxLength(4));
+
+		let violations = engine.evaluate(&"orders".to_string());
+
+		assert_eq!(
+			violations,
+			vec![Violation {
+				rule: "MaxLength".to_string(),
+				message: "orders is longer than 4".to_string(),
+			}]
+		);
+		assert!(engine.evaluate(&"crm".to_string()).is_empty());
+	}
+
+	#[test]
+	fn violations_make_the_subject_non_compliant() {
+		let violation = Violation {
+			rule: "Retention".to_string(),
+			message: "90d exceeds 30d".to_string(),
+		};
+
+		let condition = compliance(&[violation]);
+
+		assert!(!condition.is_true());
+		assert_eq!(condition.reason, "PolicyViolation");
+		assert_eq!(condition.message, "Retention: 90d exceeds 30d");
+		assert!(compliance(&[]).is_true());
+	}
+
+	#[test]
+	fn classifications_are_ordered_by_sensitivity() {
+		assert!(DataClassification::Public < DataClassification::Internal);
+		assert!(DataClassification::Confidential < DataClassification::Restricted);
+	}
+}
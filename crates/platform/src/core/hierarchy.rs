@@ -0,0 +1,304 @@
+use kube::{
+	api::{ApiResource, DynamicObject, GroupVersionKind, ListParams, Patch, PatchParams},
+	Api, Client, ResourceExt,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::{
+	operator::OperatorError,
+	status::{find_condition, set_condition, Condition, READY},
+	validation::{DNS_LABEL, DNS_SUBDOMAIN},
+};
+
+pub const GROUP: &str = "dappmesh.io";
+pub const VERSION: &str = "v1alpha1";
+
+/// A parent in the mesh → domain → product tree.
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+pub struct ParentReference {
+	#[schemars(regex = "DNS_SUBDOMAIN", length(max = 253))]
+	pub name: String,
+	/// Defaults to the namespace of the child.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schemars(regex = "DNS_LABEL", length(max = 63))]
+	pub namespace: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Level {
+	Mesh,
+	Domain,
+	Product,
+}
+
+impl Level {
+	pub fn kind(&self) -> &'static str {
+		match self {
+			Self::Mesh => "DappMesh",
+			Self::Domain => "DappDomain",
+			Self::Product => "DappProduct",
+		}
+	}
+
+	fn plural(&self) -> &'static str {
+		match self {
+			Self::Mesh => "dappmeshs",
+			Self::Domain => "dappdomains",
+			Self::Product => "dappproducts",
+		}
+	}
+
+	pub fn parent(&self) -> Option<Level> {
+		match self {
+			Self::Mesh => None,
+			Self::Domain => Some(Self::Mesh),
+			Self::Product => Some(Self::Domain),
+		}
+	}
+
+	pub fn child(&self) -> Option<Level> {
+		match self {
+			Self::Mesh => Some(Self::Domain),
+			Self::Domain => Some(Self::Product),
+			Self::Product => None,
+		}
+	}
+
+	/// The spec field through which a child of this level refers to it.
+	pub fn reference_field(&self) -> Option<&'static str> {
+		match self {
+			Self::Mesh => Some("meshRef"),
+			Self::Domain => Some("domainRef"),
+			Self::Product => None,
+		}
+	}
+
+	/// The status field holding the number of children, `domains` or `products`.
+	pub fn children_field(&self) -> Option<&'static str> {
+		self.child().map(|child| match child {
+			Self::Domain => "domains",
+			_ => "products",
+		})
+	}
+
+	pub fn api_resource(&self) -> ApiResource {
+		let gvk = GroupVersionKind::gvk(GROUP, VERSION, self.kind());
+		ApiResource::from_gvk_with_plural(&gvk, self.plural())
+	}
+}
+
+/// The place of a resource in the tree, used to gate provisioning on the parent, to protect
+/// parents with children from deletion and to publish readiness and child counts.
+#[derive(Clone)]
+pub struct Hierarchy {
+	client: Client,
+	level: Level,
+	name: String,
+	namespace: String,
+	parent: Option<ParentReference>,
+	dry_run: bool,
+}
+
+impl Hierarchy {
+	pub fn new(
+		client: Client,
+		level: Level,
+		name: &str,
+		namespace: &str,
+		parent: Option<ParentReference>,
+	) -> Self {
+		Self {
+			client,
+			level,
+			name: name.to_string(),
+			namespace: namespace.to_string(),
+			parent,
+			dry_run: false,
+		}
+	}
+
+	/// Leaves the status alone, checks on parents and children are still made.
+	pub fn dry_run(mut self, dry_run: bool) -> Self {
+		self.dry_run = dry_run;
+		self
+	}
+
+	/// Fails unless the referenced parent exists and is Ready. Resources without a parent
+	/// reference are not gated.
+	pub async fn ensure_parent_ready(&self) -> Result<(), OperatorError> {
+		let (Some(parent), Some(level)) = (&self.parent, self.level.parent()) else {
+			return Ok(());
+		};
+		let namespace = parent.namespace.as_deref().unwrap_or(&self.namespace);
+		let api: Api<DynamicObject> =
+			Api::namespaced_with(self.client.clone(), namespace, &level.api_resource());
+
+		let message = match api.get_opt(&parent.name).await? {
+			Some(object) if is_ready(&object) => return Ok(()),
+			Some(_) => format!("{} {}/{} is not Ready.", level.kind(), namespace, parent.name),
+			None => format!("{} {}/{} does not exist.", level.kind(), namespace, parent.name),
+		};
+
+		self.publish_status(Condition::new(READY, false, "ParentNotReady", &message), None).await?;
+		Err(OperatorError::UserInputError(message))
+	}
+
+	/// Names of the children referring to this resource, as `namespace/name`.
+	pub async fn children(&self) -> Result<Vec<String>, OperatorError> {
+		let (Some(level), Some(field)) = (self.level.child(), self.level.reference_field()) else {
+			return Ok(Vec::new());
+		};
+		let api: Api<DynamicObject> = Api::all_with(self.client.clone(), &level.api_resource());
+
+		Ok(api
+			.list(&ListParams::default())
+			.await?
+			.into_iter()
+			.filter(|child| refers_to(child, field, &self.name, &self.namespace))
+			.map(|child| format!("{}/{}", child.namespace().unwrap_or_default(), child.name_any()))
+			.collect())
+	}
+
+	/// Fails while children still refer to this resource, which keeps its finalizer.
+	pub async fn ensure_no_children(&self) -> Result<(), OperatorError> {
+		let children = self.children().await?;
+		match (children.is_empty(), self.level.child()) {
+			(false, Some(child)) => Err(OperatorError::UserInputError(format!(
+				"{} {} still has {} {}s: {}.",
+				self.level.kind(),
+				self.name,
+				children.len(),
+				child.kind(),
+				children.join(", ")
+			))),
+			_ => Ok(()),
+		}
+	}
+
+	/// Marks the resource Ready and records its number of children.
+	pub async fn publish_ready(&self) -> Result<(), OperatorError> {
+		let children = match self.level.child() {
+			Some(_) => Some(self.children().await?.len()),
+			None => None,
+		};
+		let condition = Condition::new(READY, true, "Provisioned", "Resources are provisioned.");
+
+		self.publish_status(condition, children).await
+	}
+
+	async fn publish_status(
+		&self,
+		condition: Condition,
+		children: Option<usize>,
+	) -> Result<(), OperatorError> {
+		if self.dry_run {
+			return Ok(());
+		}
+		let api: Api<DynamicObject> =
+			Api::namespaced_with(self.client.clone(), &self.namespace, &self.level.api_resource());
+		let Some(live) = api.get_opt(&self.name).await? else {
+			return Ok(());
+		};
+
+		let mut conditions = conditions(&live);
+		let unchanged = find_condition(&conditions, READY).is_some_and(|existing| {
+			(existing.is_true(), &existing.reason, &existing.message)
+				== (condition.is_true(), &condition.reason, &condition.message)
+		});
+		let children_field = self.level.children_field();
+		let live_children = children_field.and_then(|field| live.data["status"][field].as_u64());
+		if unchanged && live_children == children.map(|children| children as u64) {
+			return Ok(());
+		}
+
+		set_condition(&mut conditions, condition);
+		let mut status = json!({ "conditions": conditions });
+		if let (Some(field), Some(children)) = (children_field, children) {
+			status[field] = json!(children);
+		}
+
+		let patch = json!({ "status": status });
+		api.patch_status(&self.name, &PatchParams::default(), &Patch::Merge(&patch)).await?;
+		Ok(())
+	}
+}
+
+/// Whether the Ready condition of `object` is true.
+pub fn is_ready(object: &DynamicObject) -> bool {
+	find_condition(&conditions(object), READY).is_some_and(Condition::is_true)
+}
+
+/// Whether `child` refers to the parent `name` in `namespace` through `field` of its spec.
+pub fn refers_to(child: &DynamicObject, field: &str, name: &str, namespace: &str) -> bool {
+	let Ok(reference) =
+		serde_json::from_value::<ParentReference>(child.data["spec"][field].clone())
+	else {
+		return false;
+	};
+	let child_namespace = child.namespace().unwrap_or_default();
+
+	reference.name == name
+		&& reference.namespace.as_deref().unwrap_or(&child_namespace) == namespace
+}
+
+fn conditions(object: &DynamicObject) -> Vec<Condition> {
+	serde_json::from_value(object.data["status"]["conditions"].clone()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json::Value;
+
+	fn object(level: Level, namespace: &str, data: Value) -> DynamicObject {
+		let mut object = DynamicObject::new("child", &level.api_resource()).data(data);
+		object.metadata.namespace = Some(namespace.to_string());
+		object
+	}
+
+	#[test]
+	fn references_default_to_the_namespace_of_the_child() {
+		let local =
+			object(Level::Product, "shop", json!({ "spec": { "domainRef": { "name": "sales" } } }));
+		let remote = object(
+			Level::Product,
+			"shop",
+			json!({ "spec": { "domainRef": { "name": "sales", "namespace": "domains" } } }),
+		);
+		let unrelated = object(Level::Product, "shop", json!({ "spec": {} }));
+
+		assert!(refers_to(&local, "domainRef", "sales", "shop"));
+		assert!(!refers_to(&local, "domainRef", "sales", "domains"));
+		assert!(refers_to(&remote, "domainRef", "sales", "domains"));
+		assert!(!refers_to(&unrelated, "domainRef", "sales", "shop"));
+	}
+
+	#[test]
+	fn readiness_follows_the_ready_condition() {
+		let ready = Condition::new(READY, true, "Provisioned", "");
+		let not_ready = Condition::new(READY, false, "ParentNotReady", "");
+
+		assert!(is_ready(&object(
+			Level::Mesh,
+			"mesh",
+			json!({ "status": { "conditions": [ready] } })
+		)));
+		assert!(!is_ready(&object(
+			Level::Mesh,
+			"mesh",
+			json!({ "status": { "conditions": [not_ready] } })
+		)));
+		assert!(!is_ready(&object(Level::Mesh, "mesh", json!({}))));
+	}
+
+	#[test]
+	fn levels_form_a_tree() {
+		assert_eq!(Level::Domain.parent(), Some(Level::Mesh));
+		assert_eq!(Level::Domain.child(), Some(Level::Product));
+		assert_eq!(Level::Mesh.children_field(), Some("domains"));
+		assert_eq!(Level::Product.children_field(), None);
+		assert_eq!(Level::Mesh.api_resource().plural, "dappmeshs");
+	}
+}
@@ -0,0 +1,117 @@
+use k8s_openapi::{
+	api::networking::v1::{
+		HTTPIngressPath, HTTPIngressRuleValue, Ingress, IngressBackend, IngressRule,
+		IngressServiceBackend, IngressSpec, IngressTLS, ServiceBackendPort,
+	},
+	apimachinery::pkg::apis::meta::v1::ObjectMeta,
+};
+
+use super::labels::Labels;
+
+pub struct NoMetaData;
+
+#[derive(Clone)]
+pub struct NoSpec;
+
+pub struct IngressBuilder<Meta, Spec> {
+	pub metadata: Meta,
+	pub spec: Spec,
+}
+
+impl Default for IngressBuilder<NoMetaData, NoSpec> {
+	fn default() -> Self {
+		Self {
+			metadata: NoMetaData,
+			spec: NoSpec,
+		}
+	}
+}
+
+impl<Spec: Clone> IngressBuilder<NoMetaData, Spec> {
+	pub fn metadata(
+		&self,
+		name: &str,
+		owner: &str,
+		namespace: &str,
+	) -> IngressBuilder<ObjectMeta, Spec> {
+		let labels = Labels::labels(vec![
+			("component", "networking"),
+			("version", "latest"),
+			("part-of", owner),
+			("name", name),
+		]);
+
+		let metadata = ObjectMeta {
+			name: Some(name.to_string()),
+			namespace: Some(namespace.to_string()),
+			labels: Some(labels),
+			..ObjectMeta::default()
+		};
+
+		IngressBuilder {
+			metadata,
+			spec: self.spec.clone(),
+		}
+	}
+}
+
+impl<Spec> IngressBuilder<ObjectMeta, Spec> {
+	/// Routes `host` and everything below `path` to `port` of `service`, terminating TLS with
+	/// the certificate in `tls_secret` when given.
+	pub fn rule_spec(
+		&self,
+		host: &str,
+		path: &str,
+		service: &str,
+		port: i32,
+		class_name: Option<&str>,
+		tls_secret: Option<&str>,
+	) -> IngressBuilder<ObjectMeta, IngressSpec> {
+		let backend = IngressBackend {
+			service: Some(IngressServiceBackend {
+				name: service.to_string(),
+				port: Some(ServiceBackendPort {
+					number: Some(port),
+					name: None,
+				}),
+			}),
+			resource: None,
+		};
+
+		let spec = IngressSpec {
+			ingress_class_name: class_name.map(str::to_string),
+			rules: Some(vec![IngressRule {
+				host: Some(host.to_string()),
+				http: Some(HTTPIngressRuleValue {
+					paths: vec![HTTPIngressPath {
+						path: Some(path.to_string()),
+						path_type: "Prefix".to_string(),
+						backend,
+					}],
+				}),
+			}]),
+			tls: tls_secret.map(|secret| {
+				vec![IngressTLS {
+					hosts: Some(vec![host.to_string()]),
+					secret_name: Some(secret.to_string()),
+				}]
+			}),
+			..IngressSpec::default()
+		};
+
+		IngressBuilder {
+			metadata: self.metadata.clone(),
+			spec,
+		}
+	}
+}
+
+impl IngressBuilder<ObjectMeta, IngressSpec> {
+	pub fn manifest(&self) -> Ingress {
+		Ingress {
+			metadata: self.metadata.clone(),
+			spec: Some(self.spec.clone()),
+			status: None,
+		}
+	}
+}
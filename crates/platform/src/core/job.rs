@@ -0,0 +1,119 @@
+use k8s_openapi::{
+	api::{
+		batch::v1::{Job, JobSpec},
+		core::v1::{PodSpec, PodTemplateSpec},
+	},
+	apimachinery::pkg::apis::meta::v1::ObjectMeta,
+};
+
+use super::{labels::Labels, security::SecurityContextConfig};
+
+pub const BACKOFF_LIMIT: i32 = 2;
+
+pub struct NoMetaData;
+
+#[derive(Clone)]
+pub struct NoSpec;
+
+pub struct JobBuilder<Meta, Spec> {
+	pub metadata: Meta,
+	pub spec: Spec,
+}
+
+impl Default for JobBuilder<NoMetaData, NoSpec> {
+	fn default() -> Self {
+		Self {
+			metadata: NoMetaData,
+			spec: NoSpec,
+		}
+	}
+}
+
+impl<Spec: Clone> JobBuilder<NoMetaData, Spec> {
+	pub fn metadata(
+		&self,
+		name: &str,
+		owner: &str,
+		namespace: &str,
+	) -> JobBuilder<ObjectMeta, Spec> {
+		let labels = Labels::labels(vec![
+			("component", "job"),
+			("version", "latest"),
+			("part-of", owner),
+			("name", name),
+		]);
+
+		let metadata = ObjectMeta {
+			name: Some(name.to_string()),
+			namespace: Some(namespace.to_string()),
+			labels: Some(labels),
+			..ObjectMeta::default()
+		};
+
+		JobBuilder {
+			metadata,
+			spec: self.spec.clone(),
+		}
+	}
+}
+
+impl<Spec> JobBuilder<ObjectMeta, Spec> {
+	/// Runs `pod` once to completion, its pods carry the job labels and `pod_labels`.
+	pub fn spec(
+		&self,
+		pod_labels: Vec<(&str, &str)>,
+		pod: PodSpec,
+	) -> JobBuilder<ObjectMeta, JobSpec> {
+		let mut pod_spec = PodSpec {
+			restart_policy: Some("Never".to_string()),
+			..pod
+		};
+		SecurityContextConfig::default().harden(&mut pod_spec);
+
+		let mut template_metadata = self.metadata.clone();
+		template_metadata
+			.labels
+			.get_or_insert_with(Default::default)
+			.extend(Labels::labels(pod_labels));
+
+		let job_spec = JobSpec {
+			backoff_limit: Some(BACKOFF_LIMIT),
+			template: PodTemplateSpec {
+				metadata: Some(ObjectMeta {
+					labels: template_metadata.labels,
+					..ObjectMeta::default()
+				}),
+				spec: Some(pod_spec),
+			},
+			..JobSpec::default()
+		};
+
+		JobBuilder {
+			metadata: self.metadata.clone(),
+			spec: job_spec,
+		}
+	}
+}
+
+impl JobBuilder<ObjectMeta, JobSpec> {
+	/// Replaces the hardened defaults applied by `spec` with the given security context.
+	pub fn security_context(&self, security: &SecurityContextConfig) -> Self {
+		let mut spec = self.spec.clone();
+		if let Some(pod_spec) = spec.template.spec.as_mut() {
+			security.enforce(pod_spec);
+		}
+
+		JobBuilder {
+			metadata: self.metadata.clone(),
+			spec,
+		}
+	}
+
+	pub fn manifest(&self) -> Job {
+		Job {
+			metadata: self.metadata.clone(),
+			spec: Some(self.spec.clone()),
+			status: None,
+		}
+	}
+}
@@ -1,4 +1,19 @@
 use std::collections::BTreeMap;
+
+/// Set to the name of a DappDomain on its workloads, which may reach the domain storage.
+pub const DOMAIN_LABEL: &str = "dappmesh.io/domain";
+/// Set to `<namespace>.<name>` of the DappDomain owning a namespace, on the namespace and on
+/// the objects the domain operator adds to it.
+pub const OWNER_DOMAIN_LABEL: &str = "dappmesh.io/owner-domain";
+pub const PRODUCT_LABEL: &str = "dappmesh.io/product";
+/// Set to the name of a DappProduct on the pods serving its OutputPorts. Batch pods of the
+/// product, e.g. export Jobs, only carry the product label.
+pub const SERVING_LABEL: &str = "dappmesh.io/serving";
+/// Set to the operator that created an object, which it may delete again.
+pub const MANAGED_BY_LABEL: &str = "app.kubernetes.io/managed-by";
+/// Marks a DappProduct as shared with the peers of a federated mesh when set to `true`.
+pub const PUBLISHED_LABEL: &str = "dappmesh.io/published";
+
 pub struct Labels {}
 
 impl Labels {
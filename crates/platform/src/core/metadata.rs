@@ -33,3 +33,33 @@ impl MetadataConfig {
 		}
 	}
 }
+
+/// Longest name of objects whose name also ends up in label values, e.g. Jobs and Services.
+pub const MAX_LABEL_NAME_LENGTH: usize = 63;
+
+/// `prefix-suffix`, with `prefix` shortened as much as needed to stay within `max_length`.
+/// A unique `suffix`, such as a hash, keeps shortened names apart.
+pub fn truncated_name(prefix: &str, suffix: &str, max_length: usize) -> String {
+	let length = max_length.saturating_sub(suffix.len() + 1);
+	let end = (0..=length.min(prefix.len())).rev().find(|&end| prefix.is_char_boundary(end));
+	let prefix = prefix[..end.unwrap_or_default()].trim_end_matches(['-', '.']);
+
+	format!("{}-{}", prefix, suffix)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn long_names_are_shortened_before_their_suffix() {
+		let long = format!("{}-ingest", "daily".repeat(20));
+
+		let name = truncated_name(&long, "1a2b3c4d", MAX_LABEL_NAME_LENGTH);
+
+		assert_eq!(name.len(), MAX_LABEL_NAME_LENGTH);
+		assert!(name.starts_with("dailydaily") && name.ends_with("l-1a2b3c4d"));
+		assert_eq!(truncated_name("daily-ingest", "1a2b3c4d", 63), "daily-ingest-1a2b3c4d");
+		assert_eq!(truncated_name("daily-.ingest", "1a2b3c4d", 15), "daily-1a2b3c4d");
+	}
+}
@@ -1,7 +1,24 @@
+pub mod affinity;
+pub mod constants;
+pub mod conversion;
 pub mod deployment;
+pub mod diff;
+pub mod governance;
+pub mod hierarchy;
+pub mod ingress;
+pub mod job;
 pub mod labels;
 pub mod metadata;
+pub mod network_policy;
 pub mod operator;
+pub mod ownership;
+pub mod plan;
+pub mod pod_disruption_budget;
+pub mod quantity;
+pub mod security;
 pub mod service;
 pub mod service_account;
 pub mod statefulset;
+pub mod status;
+pub mod validation;
+pub mod webhook;
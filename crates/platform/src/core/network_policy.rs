@@ -0,0 +1,137 @@
+use k8s_openapi::{
+	api::networking::v1::{
+		NetworkPolicy, NetworkPolicyIngressRule, NetworkPolicyPeer, NetworkPolicyPort,
+		NetworkPolicySpec,
+	},
+	apimachinery::pkg::{
+		apis::meta::v1::{LabelSelector, ObjectMeta},
+		util::intstr::IntOrString,
+	},
+};
+
+use super::labels::Labels;
+
+pub const NAMESPACE_NAME_LABEL: &str = "kubernetes.io/metadata.name";
+
+pub struct NoMetaData;
+
+#[derive(Clone)]
+pub struct NoSpec;
+
+pub struct NetworkPolicyBuilder<Meta, Spec> {
+	pub metadata: Meta,
+	pub spec: Spec,
+}
+
+impl Default for NetworkPolicyBuilder<NoMetaData, NoSpec> {
+	fn default() -> Self {
+		Self {
+			metadata: NoMetaData,
+			spec: NoSpec,
+		}
+	}
+}
+
+impl<Spec: Clone> NetworkPolicyBuilder<NoMetaData, Spec> {
+	pub fn metadata(
+		&self,
+		name: &str,
+		owner: &str,
+		namespace: &str,
+	) -> NetworkPolicyBuilder<ObjectMeta, Spec> {
+		let labels = Labels::labels(vec![
+			("component", "networking"),
+			("version", "latest"),
+			("part-of", owner),
+			("name", name),
+		]);
+
+		let metadata = ObjectMeta {
+			name: Some(name.to_string()),
+			namespace: Some(namespace.to_string()),
+			labels: Some(labels),
+			..ObjectMeta::default()
+		};
+
+		NetworkPolicyBuilder {
+			metadata,
+			spec: self.spec.clone(),
+		}
+	}
+}
+
+impl<Spec> NetworkPolicyBuilder<ObjectMeta, Spec> {
+	/// Restricts ingress to the pods matched by `pod_labels` so that only `peers` can reach
+	/// them, and only on the given TCP `port`. An empty peer list denies all ingress.
+	pub fn ingress_spec(
+		&self,
+		pod_labels: Vec<(&str, &str)>,
+		peers: Vec<NetworkPolicyPeer>,
+		port: i32,
+	) -> NetworkPolicyBuilder<ObjectMeta, NetworkPolicySpec> {
+		let ingress = if peers.is_empty() {
+			vec![]
+		} else {
+			vec![NetworkPolicyIngressRule {
+				from: Some(peers),
+				ports: Some(vec![NetworkPolicyPort {
+					protocol: Some("TCP".to_string()),
+					port: Some(IntOrString::Int(port)),
+					..NetworkPolicyPort::default()
+				}]),
+			}]
+		};
+
+		let spec = NetworkPolicySpec {
+			pod_selector: LabelSelector {
+				match_expressions: None,
+				match_labels: Some(Labels::labels(pod_labels)),
+			},
+			policy_types: Some(vec!["Ingress".to_string()]),
+			ingress: Some(ingress),
+			..NetworkPolicySpec::default()
+		};
+
+		NetworkPolicyBuilder {
+			metadata: self.metadata.clone(),
+			spec,
+		}
+	}
+}
+
+impl NetworkPolicyBuilder<ObjectMeta, NetworkPolicySpec> {
+	pub fn manifest(&self) -> NetworkPolicy {
+		NetworkPolicy {
+			metadata: self.metadata.clone(),
+			spec: Some(self.spec.clone()),
+			status: None,
+		}
+	}
+}
+
+/// Selects the pods carrying `labels`. Without a namespace the peer is limited to the
+/// namespace of the policy, otherwise to the namespace with the given name.
+pub fn pod_peer(labels: Vec<(&str, &str)>, namespace: Option<&str>) -> NetworkPolicyPeer {
+	NetworkPolicyPeer {
+		pod_selector: Some(LabelSelector {
+			match_expressions: None,
+			match_labels: Some(Labels::labels(labels)),
+		}),
+		namespace_selector: namespace.map(|namespace| LabelSelector {
+			match_expressions: None,
+			match_labels: Some(Labels::labels(vec![(NAMESPACE_NAME_LABEL, namespace)])),
+		}),
+		..NetworkPolicyPeer::default()
+	}
+}
+
+/// Selects every pod of the namespace with the given name.
+pub fn namespace_peer(namespace: &str) -> NetworkPolicyPeer {
+	NetworkPolicyPeer {
+		namespace_selector: Some(LabelSelector {
+			match_expressions: None,
+			match_labels: Some(Labels::labels(vec![(NAMESPACE_NAME_LABEL, namespace)])),
+		}),
+		..NetworkPolicyPeer::default()
+	}
+}
@@ -1,17 +1,32 @@
 use futures::{StreamExt, TryFuture};
 use kube::{
 	api::{Patch, PatchParams},
-	runtime::{controller::Action, watcher::Config, Controller},
+	runtime::{
+		controller::Action,
+		events::{Event, EventType, Recorder, Reporter},
+		watcher::Config,
+		Controller,
+	},
 	Api, Client, Resource, ResourceExt,
 };
 use kube_core::NamespaceResourceScope;
 use serde::de::DeserializeOwned;
 use serde_json::{json, Value};
-use std::{error::Error, fmt::Debug, future::Future, hash::Hash, marker::PhantomData, sync::Arc};
+use std::{
+	error::Error,
+	fmt::Debug,
+	future::Future,
+	hash::Hash,
+	marker::PhantomData,
+	sync::{Arc, Once},
+};
 use tokio::time::Duration;
 use tracing::{error, info, instrument};
 use tracing_subscriber::{prelude::*, EnvFilter, Registry};
 
+use super::plan::{dry_run_requested, Plan};
+use crate::surrealdb::client::SurrealDBError;
+
 #[derive(PartialEq)]
 pub enum OperatorAction {
 	Create,
@@ -31,30 +46,44 @@ where
 	Crd::DynamicType: Default + Eq + Hash + Clone + Debug + Unpin,
 	Self: Sync + Send,
 {
+	/// Reconciles `resource`, in dry-run mode when [`dry_run_requested`] for it.
 	#[instrument(skip(self, context), fields(trace_id))]
 	fn reconcile(
-		&self,
+		self,
 		resource: Arc<Crd>,
 		context: Arc<OperatorContext>,
 	) -> impl Future<Output = Result<Action, OperatorError>> + Send
 	where
 		Crd: OperatorResource,
+		Self: Sized,
 	{
-		async {
+		async move {
 			info!("Reconciling: {:?}", resource.name_any());
+			let controller = if dry_run_requested(resource.as_ref()) {
+				self.dry_run(true)
+			} else {
+				self
+			};
+			if controller.plan().is_dry_run() {
+				info!("Planning changes for: {:?}", resource.name_any());
+				controller.plan_changes(context.client.clone(), resource.clone()).await?;
+				return Ok(Action::await_change());
+			}
+
 			match Self::action(resource.clone()) {
 				OperatorAction::Create => {
 					info!("Creating resources for: {:?}", resource.name_any());
-					self.handle_creation(context.client.clone(), resource.clone()).await?;
+					controller.handle_creation(context.client.clone(), resource.clone()).await?;
 					Ok(Action::requeue(Duration::from_secs(5)))
 				}
 				OperatorAction::Delete => {
 					info!("Deleting resources for: {:?}", resource.name_any());
-					self.handle_deletion(context.client.clone(), resource.clone()).await?;
+					controller.handle_deletion(context.client.clone(), resource.clone()).await?;
 					Ok(Action::await_change())
 				}
 				OperatorAction::NoOp => {
-					info!("No operation, state has not changed");
+					info!("Synchronizing resources for: {:?}", resource.name_any());
+					controller.update_resources().await?;
 					Ok(Action::requeue(Duration::from_secs(10)))
 				}
 			}
@@ -74,6 +103,47 @@ where
 		}
 	}
 
+	/// Runs the action in dry-run mode and publishes the resulting plan as an Event on the
+	/// resource. Finalizers are left alone, so a deleted resource waits until the dry-run
+	/// mode is switched off.
+	fn plan_changes(
+		&self,
+		client: Client,
+		resource: Arc<Crd>,
+	) -> impl Future<Output = Result<(), OperatorError>> + Send
+	where
+		Crd: OperatorResource,
+	{
+		async move {
+			match Self::action(resource.clone()) {
+				OperatorAction::Create => self.create_resources().await?,
+				OperatorAction::Delete => self.delete_resources().await?,
+				OperatorAction::NoOp => self.update_resources().await?,
+			}
+
+			let plan = self.plan().to_string();
+			info!("Planned changes for {:?}:\n{}", resource.name_any(), plan);
+
+			let dynamic_type = Crd::DynamicType::default();
+			let reporter = Reporter {
+				controller: format!("{}-controller", Crd::kind(&dynamic_type).to_lowercase()),
+				instance: None,
+			};
+			let recorder = Recorder::new(client, reporter, resource.object_ref(&dynamic_type));
+			recorder
+				.publish(Event {
+					type_: EventType::Normal,
+					reason: "DryRun".to_string(),
+					note: Some(truncate(plan, MAX_EVENT_NOTE_LENGTH)),
+					action: "Plan".to_string(),
+					secondary: None,
+				})
+				.await?;
+
+			Ok(())
+		}
+	}
+
 	fn handle_creation(
 		&self,
 		client: Client,
@@ -152,9 +222,41 @@ where
 
 	fn create_resources(&self) -> impl Future<Output = Result<(), OperatorError>> + Send;
 	fn delete_resources(&self) -> impl Future<Output = Result<(), OperatorError>> + Send;
+
+	/// Called on every reconciliation of an already provisioned resource, so that resources
+	/// derived from its spec follow spec changes. Does nothing unless overridden.
+	fn update_resources(&self) -> impl Future<Output = Result<(), OperatorError>> + Send {
+		async { Ok(()) }
+	}
+
+	/// Switches the controller into dry-run mode, in which it submits its changes with
+	/// `dryRun=All` and records them in its [`Plan`], leaving statuses and the catalog alone.
+	fn dry_run(self, dry_run: bool) -> Self
+	where
+		Self: Sized;
+
+	/// The changes submitted by the last create, update or delete and whether they were
+	/// submitted in dry-run mode.
+	fn plan(&self) -> Plan;
+
 	fn finalizer(&self) -> &str;
 }
 
+/// The API server rejects Event notes longer than 1kB.
+const MAX_EVENT_NOTE_LENGTH: usize = 1024;
+
+fn truncate(mut note: String, max_length: usize) -> String {
+	if note.len() > max_length {
+		let mut end = max_length - 3;
+		while !note.is_char_boundary(end) {
+			end -= 1;
+		}
+		note.truncate(end);
+		note.push_str("...");
+	}
+	note
+}
+
 pub struct OperatorContext {
 	pub client: Client,
 }
@@ -194,10 +296,7 @@ where
 		ReconcileFut: TryFuture<Ok = Action> + Send + 'static,
 		ReconcileFut::Error: Error + Send + 'static,
 	{
-		let logger = tracing_subscriber::fmt::layer().compact();
-		let env_filter = EnvFilter::try_from_default_env().or(EnvFilter::try_new("info")).unwrap();
-		let collector = Registry::default().with(logger).with(env_filter);
-		tracing::subscriber::set_global_default(collector).unwrap();
+		init_tracing();
 
 		let client = Client::try_default().await.expect("Failed to create a Kubernetes client.");
 		let context: Arc<OperatorContext> = Arc::new(OperatorContext::new(client.clone()));
@@ -220,6 +319,18 @@ where
 	}
 }
 
+static TRACING: Once = Once::new();
+
+/// Installs the global log collector once, so that one binary can run several operators.
+pub fn init_tracing() {
+	TRACING.call_once(|| {
+		let logger = tracing_subscriber::fmt::layer().compact();
+		let env_filter = EnvFilter::try_from_default_env().or(EnvFilter::try_new("info")).unwrap();
+		let collector = Registry::default().with(logger).with(env_filter);
+		tracing::subscriber::set_global_default(collector).unwrap();
+	});
+}
+
 pub trait OperatorResource
 where
 	Self: Resource,
@@ -239,6 +350,13 @@ pub enum OperatorError {
 		#[from]
 		source: kube::Error,
 	},
+	#[error("SurrealDB reported error: {source}")]
+	DatabaseError {
+		#[from]
+		source: SurrealDBError,
+	},
 	#[error("Invalid CRD: {0}")]
 	UserInputError(String),
+	#[error("Invalid configuration: {0}")]
+	ConfigurationError(String),
 }
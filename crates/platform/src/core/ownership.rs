@@ -0,0 +1,108 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Who is accountable for a domain or product and how to reach them.
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Ownership {
+	/// The owning team. Products without one are owned by the team of their domain.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schemars(length(min = 1))]
+	pub team: Option<String>,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub contacts: Vec<Contact>,
+	/// Link to the on-call rotation of the team.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schemars(url)]
+	pub on_call: Option<String>,
+	/// The cost centre the resources are charged to.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub cost_centre: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub description: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+pub struct Contact {
+	#[schemars(length(min = 1))]
+	pub name: String,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schemars(email)]
+	pub email: Option<String>,
+	/// What to contact them for, e.g. `data steward`.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub role: Option<String>,
+}
+
+impl Ownership {
+	/// The ownership with the fields left unset taken from `parent`, e.g. a product from its
+	/// domain. The description stays with the resource it describes.
+	pub fn inherit(&self, parent: Option<&Ownership>) -> Ownership {
+		let Some(parent) = parent else {
+			return self.clone();
+		};
+
+		Ownership {
+			team: self.team.clone().or(parent.team.clone()),
+			contacts: match self.contacts.is_empty() {
+				true => parent.contacts.clone(),
+				false => self.contacts.clone(),
+			},
+			on_call: self.on_call.clone().or(parent.on_call.clone()),
+			cost_centre: self.cost_centre.clone().or(parent.cost_centre.clone()),
+			description: self.description.clone(),
+		}
+	}
+}
+
+/// The team owning a resource with `ownership` whose parent has `parent`, if any of them
+/// names one.
+pub fn owning_team(ownership: Option<&Ownership>, parent: Option<&Ownership>) -> Option<String> {
+	ownership.cloned().unwrap_or_default().inherit(parent).team
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn domain() -> Ownership {
+		Ownership {
+			team: Some("sales".to_string()),
+			contacts: vec![Contact {
+				name: "Ada".to_string(),
+				email: Some("ada@example.com".to_string()),
+				role: None,
+			}],
+			on_call: Some("https://oncall.example.com/sales".to_string()),
+			cost_centre: Some("CC-100".to_string()),
+			description: Some("Everything sold".to_string()),
+		}
+	}
+
+	#[test]
+	fn unset_fields_are_inherited_from_the_parent() {
+		let product = Ownership {
+			cost_centre: Some("CC-200".to_string()),
+			..Ownership::default()
+		};
+
+		let inherited = product.inherit(Some(&domain()));
+
+		assert_eq!(inherited.team.as_deref(), Some("sales"));
+		assert_eq!(inherited.contacts, domain().contacts);
+		assert_eq!(inherited.cost_centre.as_deref(), Some("CC-200"));
+		assert_eq!(inherited.description, None);
+	}
+
+	#[test]
+	fn teams_resolve_through_the_parent() {
+		let product = Ownership {
+			team: Some("orders".to_string()),
+			..Ownership::default()
+		};
+
+		assert_eq!(owning_team(Some(&product), Some(&domain())).as_deref(), Some("orders"));
+		assert_eq!(owning_team(None, Some(&domain())).as_deref(), Some("sales"));
+		assert_eq!(owning_team(None, None), None);
+	}
+}
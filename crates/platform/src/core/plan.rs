@@ -0,0 +1,246 @@
+use std::{
+	env,
+	fmt::{self, Debug},
+	sync::{Arc, Mutex},
+};
+
+use kube::{
+	api::{DeleteParams, Patch, PatchParams, PostParams},
+	Api, Resource, ResourceExt,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use tracing::debug;
+
+use super::diff::{diff, ManifestDiff};
+use crate::surrealdb::app::FIELD_MANAGER;
+
+/// Puts every controller of the operator into dry-run mode when set to `true`.
+pub const DRY_RUN_ENV: &str = "DAPPMESH_DRY_RUN";
+/// Puts the controller into dry-run mode for the annotated object only.
+pub const DRY_RUN_ANNOTATION: &str = "dappmesh.io/dry-run";
+
+/// Whether [`DRY_RUN_ENV`] puts the whole operator into dry-run mode.
+pub fn dry_run_enabled() -> bool {
+	env::var(DRY_RUN_ENV).is_ok_and(|value| value == "true")
+}
+
+pub fn dry_run_requested<K: Resource>(resource: &K) -> bool {
+	requested(dry_run_enabled(), resource)
+}
+
+/// Whether dry-run mode applies to `resource`, given whether the operator is in it.
+fn requested<K: Resource>(enabled: bool, resource: &K) -> bool {
+	enabled || resource.annotations().get(DRY_RUN_ANNOTATION).is_some_and(|value| value == "true")
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operation {
+	Create,
+	Patch,
+	Delete,
+}
+
+impl fmt::Display for Operation {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Operation::Create => write!(f, "create"),
+			Operation::Patch => write!(f, "patch"),
+			Operation::Delete => write!(f, "delete"),
+		}
+	}
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlannedChange {
+	pub operation: Operation,
+	pub kind: String,
+	pub name: String,
+	/// Only set for patches.
+	pub diff: ManifestDiff,
+}
+
+/// Records the changes a reconciliation submits to the API server. In dry-run mode the
+/// requests are sent with `dryRun=All`, so admission validates them but nothing is persisted.
+#[derive(Clone, Debug, Default)]
+pub struct Plan {
+	dry_run: bool,
+	changes: Arc<Mutex<Vec<PlannedChange>>>,
+}
+
+impl Plan {
+	pub fn new(dry_run: bool) -> Self {
+		Self {
+			dry_run,
+			..Self::default()
+		}
+	}
+
+	pub fn is_dry_run(&self) -> bool {
+		self.dry_run
+	}
+
+	pub fn record(&self, operation: Operation, kind: &str, name: &str, diff: ManifestDiff) {
+		self.changes.lock().unwrap().push(PlannedChange {
+			operation,
+			kind: kind.to_string(),
+			name: name.to_string(),
+			diff,
+		});
+	}
+
+	pub fn changes(&self) -> Vec<PlannedChange> {
+		self.changes.lock().unwrap().clone()
+	}
+
+	pub fn post_params(&self) -> PostParams {
+		PostParams {
+			dry_run: self.dry_run,
+			..PostParams::default()
+		}
+	}
+
+	pub fn patch_params(&self, params: PatchParams) -> PatchParams {
+		if self.dry_run {
+			params.dry_run()
+		} else {
+			params
+		}
+	}
+
+	/// Deletes in the background, so that dependents such as the pods of a Job go along.
+	pub fn delete_params(&self) -> DeleteParams {
+		DeleteParams {
+			dry_run: self.dry_run,
+			..DeleteParams::background()
+		}
+	}
+
+	/// Server-side applies `desired` unless the live object already matches it.
+	pub async fn apply<K>(&self, api: &Api<K>, desired: &K) -> Result<(), kube::Error>
+	where
+		K: Resource<DynamicType = ()> + Clone + DeserializeOwned + Serialize + Debug,
+	{
+		let name = desired.name_any();
+
+		let (operation, diff) = match api.get_opt(&name).await? {
+			Some(live) => {
+				let diff = diff(desired, &live);
+				if diff.is_empty() {
+					debug!("{} {} is up to date", K::kind(&()), name);
+					return Ok(());
+				}
+				debug!("{} {} differs from the desired state:\n{}", K::kind(&()), name, diff);
+				(Operation::Patch, diff)
+			}
+			None => (Operation::Create, ManifestDiff::default()),
+		};
+
+		let params = self.patch_params(PatchParams::apply(FIELD_MANAGER).force());
+		api.patch(&name, &params, &Patch::Apply(desired)).await?;
+		self.record(operation, &K::kind(&()), &name, diff);
+
+		Ok(())
+	}
+
+	/// Creates `object` as is, e.g. an object with a generated name.
+	pub async fn create<K>(&self, api: &Api<K>, object: &K) -> Result<(), kube::Error>
+	where
+		K: Resource<DynamicType = ()> + Clone + DeserializeOwned + Serialize + Debug,
+	{
+		let created = api.create(&self.post_params(), object).await?;
+		self.record(Operation::Create, &K::kind(&()), &created.name_any(), ManifestDiff::default());
+
+		Ok(())
+	}
+
+	/// Deletes the object called `name`, if there is one.
+	pub async fn delete<K>(&self, api: &Api<K>, name: &str) -> Result<(), kube::Error>
+	where
+		K: Resource<DynamicType = ()> + Clone + DeserializeOwned + Debug,
+	{
+		if api.get_opt(name).await?.is_none() {
+			return Ok(());
+		}
+
+		api.delete(name, &self.delete_params()).await?;
+		self.record(Operation::Delete, &K::kind(&()), name, ManifestDiff::default());
+
+		Ok(())
+	}
+}
+
+impl fmt::Display for Plan {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let changes = self.changes();
+		if changes.is_empty() {
+			return writeln!(f, "no changes");
+		}
+
+		for change in changes {
+			writeln!(f, "{} {} {}", change.operation, change.kind, change.name)?;
+			for line in change.diff.to_string().lines() {
+				writeln!(f, "  {}", line)?;
+			}
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::core::diff::diff_values;
+	use k8s_openapi::api::core::v1::ConfigMap;
+	use kube::api::ObjectMeta;
+	use serde_json::json;
+
+	fn annotated(value: &str) -> ConfigMap {
+		ConfigMap {
+			metadata: ObjectMeta {
+				annotations: Some([(DRY_RUN_ANNOTATION.to_string(), value.to_string())].into()),
+				..ObjectMeta::default()
+			},
+			..ConfigMap::default()
+		}
+	}
+
+	#[test]
+	fn dry_run_is_requested_by_annotation() {
+		assert!(requested(false, &annotated("true")));
+		assert!(!requested(false, &annotated("false")));
+		assert!(!requested(false, &ConfigMap::default()));
+	}
+
+	#[test]
+	fn dry_run_mode_of_the_operator_applies_to_every_resource() {
+		assert!(requested(true, &annotated("false")));
+		assert!(requested(true, &ConfigMap::default()));
+	}
+
+	#[test]
+	fn dry_run_plan_marks_requests_as_dry_run() {
+		let plan = Plan::new(true);
+
+		assert!(plan.post_params().dry_run);
+		assert!(plan.patch_params(PatchParams::default()).dry_run);
+		assert!(plan.delete_params().dry_run);
+		assert!(!Plan::default().post_params().dry_run);
+	}
+
+	#[test]
+	fn plan_lists_changes_with_their_diff() {
+		let plan = Plan::new(true);
+		plan.record(Operation::Create, "Service", "test-db", ManifestDiff::default());
+		plan.record(
+			Operation::Patch,
+			"StatefulSet",
+			"test-db",
+			diff_values(&json!({ "replicas": 3 }), &json!({ "replicas": 1 })),
+		);
+
+		assert_eq!(
+			plan.to_string(),
+			"create Service test-db\npatch StatefulSet test-db\n  replicas: 1 -> 3\n"
+		);
+	}
+}
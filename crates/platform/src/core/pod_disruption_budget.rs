@@ -0,0 +1,97 @@
+use k8s_openapi::{
+	api::policy::v1::{PodDisruptionBudget, PodDisruptionBudgetSpec},
+	apimachinery::pkg::{
+		apis::meta::v1::{LabelSelector, ObjectMeta},
+		util::intstr::IntOrString,
+	},
+};
+
+use super::labels::Labels;
+
+pub struct NoMetaData;
+
+#[derive(Clone)]
+pub struct NoSpec;
+
+pub struct PodDisruptionBudgetBuilder<Meta, Spec> {
+	pub metadata: Meta,
+	pub spec: Spec,
+}
+
+impl Default for PodDisruptionBudgetBuilder<NoMetaData, NoSpec> {
+	fn default() -> Self {
+		Self {
+			metadata: NoMetaData,
+			spec: NoSpec,
+		}
+	}
+}
+
+impl<Spec: Clone> PodDisruptionBudgetBuilder<NoMetaData, Spec> {
+	pub fn metadata(
+		&self,
+		name: &str,
+		owner: &str,
+		namespace: &str,
+	) -> PodDisruptionBudgetBuilder<ObjectMeta, Spec> {
+		let labels = Labels::labels(vec![
+			("component", "availability"),
+			("version", "latest"),
+			("part-of", owner),
+			("name", name),
+		]);
+
+		let metadata = ObjectMeta {
+			name: Some(name.to_string()),
+			namespace: Some(namespace.to_string()),
+			labels: Some(labels),
+			..ObjectMeta::default()
+		};
+
+		PodDisruptionBudgetBuilder {
+			metadata,
+			spec: self.spec.clone(),
+		}
+	}
+}
+
+impl<Spec> PodDisruptionBudgetBuilder<ObjectMeta, Spec> {
+	/// A budget may either keep `min_available` pods or tolerate `max_unavailable` evicted
+	/// pods, so `min_available` wins when both are given.
+	pub fn spec(
+		&self,
+		pod_labels: Vec<(&str, &str)>,
+		min_available: Option<IntOrString>,
+		max_unavailable: Option<IntOrString>,
+	) -> PodDisruptionBudgetBuilder<ObjectMeta, PodDisruptionBudgetSpec> {
+		let max_unavailable = if min_available.is_some() {
+			None
+		} else {
+			max_unavailable
+		};
+
+		let spec = PodDisruptionBudgetSpec {
+			selector: Some(LabelSelector {
+				match_expressions: None,
+				match_labels: Some(Labels::labels(pod_labels)),
+			}),
+			min_available,
+			max_unavailable,
+		};
+
+		PodDisruptionBudgetBuilder {
+			metadata: self.metadata.clone(),
+			spec,
+		}
+	}
+}
+
+impl PodDisruptionBudgetBuilder<ObjectMeta, PodDisruptionBudgetSpec> {
+	pub fn manifest(&self) -> PodDisruptionBudget {
+		PodDisruptionBudget {
+			metadata: self.metadata.clone(),
+			spec: Some(self.spec.clone()),
+			status: None,
+		}
+	}
+}
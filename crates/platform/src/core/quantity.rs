@@ -0,0 +1,83 @@
+const SUFFIXES: [(&str, f64); 12] = [
+	("Ki", 1024.0),
+	("Mi", 1024.0 * 1024.0),
+	("Gi", 1024.0 * 1024.0 * 1024.0),
+	("Ti", 1024.0 * 1024.0 * 1024.0 * 1024.0),
+	("Pi", 1024.0 * 1024.0 * 1024.0 * 1024.0 * 1024.0),
+	("Ei", 1024.0 * 1024.0 * 1024.0 * 1024.0 * 1024.0 * 1024.0),
+	("k", 1e3),
+	("M", 1e6),
+	("G", 1e9),
+	("T", 1e12),
+	("P", 1e15),
+	("E", 1e18),
+];
+
+/// The number of bytes of a storage quantity such as `10Gi` or `500M`, or `None` if it is
+/// not one.
+pub fn bytes(quantity: &str) -> Option<f64> {
+	let (number, multiplier) = SUFFIXES
+		.iter()
+		.find_map(|(suffix, multiplier)| {
+			quantity.strip_suffix(suffix).map(|number| (number, *multiplier))
+		})
+		.unwrap_or((quantity, 1.0));
+
+	if number.is_empty() || !number.chars().all(|c| c.is_ascii_digit() || c == '.') {
+		return None;
+	}
+	number.parse::<f64>().ok().map(|number| number * multiplier)
+}
+
+/// The number of seconds of a duration such as `90s`, `15m`, `25h` or `30d`, or `None` if it
+/// is not one.
+pub fn seconds(duration: &str) -> Option<u64> {
+	let unit = duration.chars().last()?;
+	let multiplier = match unit {
+		's' => 1,
+		'm' => 60,
+		'h' => 60 * 60,
+		'd' => 24 * 60 * 60,
+		_ => return None,
+	};
+	let number = &duration[..duration.len() - 1];
+
+	if number.is_empty() || !number.chars().all(|c| c.is_ascii_digit()) {
+		return None;
+	}
+	number.parse::<u64>().ok().and_then(|number| number.checked_mul(multiplier))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn binary_and_decimal_suffixes_are_understood() {
+		assert_eq!(bytes("1Ki"), Some(1024.0));
+		assert_eq!(bytes("1.5Gi"), Some(1.5 * 1024.0 * 1024.0 * 1024.0));
+		assert_eq!(bytes("500M"), Some(5e8));
+		assert_eq!(bytes("42"), Some(42.0));
+		assert!(bytes("10Gi").unwrap() > bytes("10G").unwrap());
+	}
+
+	#[test]
+	fn malformed_quantities_are_rejected() {
+		assert_eq!(bytes(""), None);
+		assert_eq!(bytes("Gi"), None);
+		assert_eq!(bytes("-1Gi"), None);
+		assert_eq!(bytes("ten"), None);
+	}
+
+	#[test]
+	fn durations_are_converted_to_seconds() {
+		assert_eq!(seconds("90s"), Some(90));
+		assert_eq!(seconds("15m"), Some(900));
+		assert_eq!(seconds("25h"), Some(90_000));
+		assert_eq!(seconds("30d"), Some(2_592_000));
+		assert_eq!(seconds("30"), None);
+		assert_eq!(seconds("d"), None);
+		assert_eq!(seconds("1.5h"), None);
+		assert_eq!(seconds("1w"), None);
+	}
+}
@@ -0,0 +1,236 @@
+use k8s_openapi::api::core::v1::{
+	Capabilities, Container, PodSecurityContext, PodSpec, SeccompProfile, SecurityContext,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_UID: i64 = 65532;
+pub const DEFAULT_GID: i64 = 65532;
+pub const SECCOMP_RUNTIME_DEFAULT: &str = "RuntimeDefault";
+
+/// Overrides for the hardened security context applied to every generated workload.
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SecurityContextOverrides {
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schemars(range(min = 1))]
+	pub run_as_user: Option<i64>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schemars(range(min = 1))]
+	pub run_as_group: Option<i64>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schemars(range(min = 1))]
+	pub fs_group: Option<i64>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub read_only_root_filesystem: Option<bool>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SecurityContextConfig {
+	pub run_as_user: i64,
+	pub run_as_group: i64,
+	pub fs_group: i64,
+	pub read_only_root_filesystem: bool,
+}
+
+impl Default for SecurityContextConfig {
+	fn default() -> Self {
+		Self {
+			run_as_user: DEFAULT_UID,
+			run_as_group: DEFAULT_GID,
+			fs_group: DEFAULT_GID,
+			read_only_root_filesystem: true,
+		}
+	}
+}
+
+impl SecurityContextConfig {
+	pub fn with_overrides(mut self, overrides: &SecurityContextOverrides) -> Self {
+		if let Some(run_as_user) = overrides.run_as_user {
+			self.run_as_user = run_as_user;
+		}
+		if let Some(run_as_group) = overrides.run_as_group {
+			self.run_as_group = run_as_group;
+		}
+		if let Some(fs_group) = overrides.fs_group {
+			self.fs_group = fs_group;
+		}
+		if let Some(read_only_root_filesystem) = overrides.read_only_root_filesystem {
+			self.read_only_root_filesystem = read_only_root_filesystem;
+		}
+		self
+	}
+
+	pub fn pod_security_context(&self) -> PodSecurityContext {
+		PodSecurityContext {
+			run_as_non_root: Some(true),
+			run_as_user: Some(self.run_as_user),
+			run_as_group: Some(self.run_as_group),
+			fs_group: Some(self.fs_group),
+			fs_group_change_policy: Some("OnRootMismatch".to_string()),
+			seccomp_profile: Some(Self::seccomp_profile()),
+			..PodSecurityContext::default()
+		}
+	}
+
+	pub fn container_security_context(&self) -> SecurityContext {
+		SecurityContext {
+			allow_privilege_escalation: Some(false),
+			privileged: Some(false),
+			run_as_non_root: Some(true),
+			run_as_user: Some(self.run_as_user),
+			run_as_group: Some(self.run_as_group),
+			read_only_root_filesystem: Some(self.read_only_root_filesystem),
+			capabilities: Some(Capabilities {
+				add: None,
+				drop: Some(vec!["ALL".to_string()]),
+			}),
+			seccomp_profile: Some(Self::seccomp_profile()),
+			..SecurityContext::default()
+		}
+	}
+
+	/// Sets the pod security context and a container security context on every container,
+	/// init containers included, that does not declare one yet.
+	pub fn harden(&self, pod: &mut PodSpec) {
+		pod.security_context = Some(self.pod_security_context());
+		let init_containers = pod.init_containers.iter_mut().flatten();
+		for container in init_containers.chain(pod.containers.iter_mut()) {
+			if container.security_context.is_none() {
+				container.security_context = Some(self.container_security_context());
+			}
+		}
+	}
+
+	/// Like [`SecurityContextConfig::harden`], but replaces existing container contexts.
+	pub fn enforce(&self, pod: &mut PodSpec) {
+		pod.security_context = Some(self.pod_security_context());
+		let init_containers = pod.init_containers.iter_mut().flatten();
+		for container in init_containers.chain(pod.containers.iter_mut()) {
+			container.security_context = Some(self.container_security_context());
+		}
+	}
+
+	fn seccomp_profile() -> SeccompProfile {
+		SeccompProfile {
+			type_: SECCOMP_RUNTIME_DEFAULT.to_string(),
+			localhost_profile: None,
+		}
+	}
+}
+
+/// Checks a pod against the `restricted` Pod Security Standard and returns one message per
+/// violated control, so an empty result means the pod would be admitted.
+pub fn restricted_profile_violations(pod: &PodSpec) -> Vec<String> {
+	let mut violations = Vec::new();
+	let pod_context = pod.security_context.clone().unwrap_or_default();
+
+	if pod.host_network == Some(true) || pod.host_pid == Some(true) || pod.host_ipc == Some(true) {
+		violations.push("host namespaces must not be shared".to_string());
+	}
+
+	for volume in pod.volumes.iter().flatten() {
+		if volume.host_path.is_some() {
+			violations.push(format!("volume {:?} must not use hostPath", volume.name));
+		}
+	}
+
+	if pod_context.run_as_user == Some(0) {
+		violations.push("pod must not set runAsUser=0".to_string());
+	}
+
+	let containers = pod.init_containers.iter().flatten().chain(pod.containers.iter());
+	for container in containers {
+		violations.extend(container_violations(container, &pod_context));
+	}
+
+	violations
+}
+
+fn container_violations(container: &Container, pod_context: &PodSecurityContext) -> Vec<String> {
+	let mut violations = Vec::new();
+	let context = container.security_context.clone().unwrap_or_default();
+	let name = &container.name;
+
+	if context.privileged == Some(true) {
+		violations.push(format!("container {:?} must not be privileged", name));
+	}
+
+	if context.allow_privilege_escalation != Some(false) {
+		violations.push(format!("container {:?} must set allowPrivilegeEscalation=false", name));
+	}
+
+	if context.run_as_non_root.or(pod_context.run_as_non_root) != Some(true) {
+		violations.push(format!("container {:?} must set runAsNonRoot=true", name));
+	}
+
+	if context.run_as_user == Some(0) {
+		violations.push(format!("container {:?} must not set runAsUser=0", name));
+	}
+
+	let capabilities = context.capabilities.clone().unwrap_or_default();
+	if !capabilities.drop.iter().flatten().any(|capability| capability == "ALL") {
+		violations.push(format!("container {:?} must drop ALL capabilities", name));
+	}
+	if capabilities.add.iter().flatten().any(|capability| capability != "NET_BIND_SERVICE") {
+		violations.push(format!("container {:?} may only add NET_BIND_SERVICE", name));
+	}
+
+	let seccomp = context.seccomp_profile.as_ref().or(pod_context.seccomp_profile.as_ref());
+	if !seccomp.is_some_and(|profile| {
+		profile.type_ == SECCOMP_RUNTIME_DEFAULT || profile.type_ == "Localhost"
+	}) {
+		violations.push(format!(
+			"container {:?} must use the RuntimeDefault or Localhost seccomp profile",
+			name
+		));
+	}
+
+	violations
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn pod() -> PodSpec {
+		PodSpec {
+			containers: vec![Container {
+				name: "app".to_string(),
+				..Container::default()
+			}],
+			..PodSpec::default()
+		}
+	}
+
+	#[test]
+	fn unhardened_pod_violates_restricted_profile() {
+		let violations = restricted_profile_violations(&pod());
+
+		assert_eq!(violations.len(), 4);
+	}
+
+	#[test]
+	fn hardened_pod_passes_restricted_profile() {
+		let mut pod = pod();
+		SecurityContextConfig::default().harden(&mut pod);
+
+		assert!(restricted_profile_violations(&pod).is_empty());
+	}
+
+	#[test]
+	fn overrides_replace_only_the_given_defaults() {
+		let overrides = SecurityContextOverrides {
+			run_as_user: Some(1000),
+			read_only_root_filesystem: Some(false),
+			..SecurityContextOverrides::default()
+		};
+
+		let config = SecurityContextConfig::default().with_overrides(&overrides);
+
+		assert_eq!(config.run_as_user, 1000);
+		assert_eq!(config.run_as_group, DEFAULT_GID);
+		assert_eq!(config.fs_group, DEFAULT_GID);
+		assert!(!config.read_only_root_filesystem);
+	}
+}
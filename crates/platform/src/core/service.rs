@@ -67,9 +67,46 @@ impl<Spec> ServiceBuilder<ObjectMeta, Spec> {
 			spec,
 		}
 	}
+
+	/// Exposes several ports, given as name, protocol and port, which services with more than
+	/// one port have to name.
+	pub fn named_ports(
+		&self,
+		ports: Vec<(&str, &str, i32)>,
+	) -> ServiceBuilder<ObjectMeta, ServiceSpec> {
+		let ports = ports.into_iter().map(|(name, protocol, port)| ServicePort {
+			name: Some(name.to_string()),
+			protocol: Some(protocol.to_string()),
+			port,
+			target_port: Some(IntOrString::Int(port)),
+			..ServicePort::default()
+		});
+		let spec = ServiceSpec {
+			ports: Some(ports.collect()),
+			..ServiceSpec::default()
+		};
+
+		ServiceBuilder {
+			metadata: self.metadata.clone(),
+			spec,
+		}
+	}
 }
 
 impl ServiceBuilder<ObjectMeta, ServiceSpec> {
+	/// Routes the service to the pods carrying `pod_labels`.
+	pub fn selector(&self, pod_labels: Vec<(&str, &str)>) -> Self {
+		let spec = ServiceSpec {
+			selector: Some(Labels::labels(pod_labels)),
+			..self.spec.clone()
+		};
+
+		ServiceBuilder {
+			metadata: self.metadata.clone(),
+			spec,
+		}
+	}
+
 	pub fn manifest(&self) -> Service {
 		Service {
 			metadata: self.metadata.clone(),
@@ -2,8 +2,8 @@ use k8s_openapi::{
 	api::{
 		apps::v1::{StatefulSet, StatefulSetSpec, StatefulSetStatus},
 		core::v1::{
-			Container, PersistentVolumeClaim, PersistentVolumeClaimSpec, PodSpec, PodTemplateSpec,
-			ResourceRequirements,
+			Affinity, Container, PersistentVolumeClaim, PersistentVolumeClaimSpec, PodSpec,
+			PodTemplateSpec, ResourceRequirements,
 		},
 	},
 	apimachinery::pkg::{
@@ -14,7 +14,7 @@ use k8s_openapi::{
 
 use std::collections::BTreeMap;
 
-use super::labels::Labels;
+use super::{labels::Labels, security::SecurityContextConfig};
 
 pub struct NoMetaData;
 
@@ -74,6 +74,12 @@ impl<Spec> StatefulSetBuilder<ObjectMeta, Spec> {
 		let mut pvc_resources: BTreeMap<String, Quantity> = BTreeMap::new();
 		pvc_resources.insert("storage".to_owned(), resource_quantity);
 
+		let mut pod_spec = PodSpec {
+			containers,
+			..PodSpec::default()
+		};
+		SecurityContextConfig::default().harden(&mut pod_spec);
+
 		let spec = StatefulSetSpec {
 			replicas: Some(replicas),
 			selector: LabelSelector {
@@ -81,10 +87,7 @@ impl<Spec> StatefulSetBuilder<ObjectMeta, Spec> {
 				match_labels: self.metadata.labels.clone(),
 			},
 			template: PodTemplateSpec {
-				spec: Some(PodSpec {
-					containers,
-					..PodSpec::default()
-				}),
+				spec: Some(pod_spec),
 				metadata: Some(self.metadata.clone()),
 			},
 			volume_claim_templates: Some(vec![PersistentVolumeClaim {
@@ -110,6 +113,46 @@ impl<Spec> StatefulSetBuilder<ObjectMeta, Spec> {
 }
 
 impl StatefulSetBuilder<ObjectMeta, StatefulSetSpec> {
+	/// Replaces the hardened defaults applied by `spec` with the given security context.
+	pub fn security_context(&self, security: &SecurityContextConfig) -> Self {
+		let mut spec = self.spec.clone();
+		if let Some(pod_spec) = spec.template.spec.as_mut() {
+			security.enforce(pod_spec);
+		}
+
+		StatefulSetBuilder {
+			metadata: self.metadata.clone(),
+			spec,
+		}
+	}
+
+	pub fn affinity(&self, affinity: Option<Affinity>) -> Self {
+		let mut spec = self.spec.clone();
+		if let Some(pod_spec) = spec.template.spec.as_mut() {
+			pod_spec.affinity = affinity;
+		}
+
+		StatefulSetBuilder {
+			metadata: self.metadata.clone(),
+			spec,
+		}
+	}
+
+	/// Provisions the volumes from `storage_class_name` instead of the default StorageClass.
+	pub fn storage_class_name(&self, storage_class_name: Option<String>) -> Self {
+		let mut spec = self.spec.clone();
+		for claim in spec.volume_claim_templates.iter_mut().flatten() {
+			if let Some(claim_spec) = claim.spec.as_mut() {
+				claim_spec.storage_class_name = storage_class_name.clone();
+			}
+		}
+
+		StatefulSetBuilder {
+			metadata: self.metadata.clone(),
+			spec,
+		}
+	}
+
 	pub fn manifest(&self) -> StatefulSet {
 		StatefulSet {
 			metadata: self.metadata.clone(),
@@ -0,0 +1,145 @@
+use k8s_openapi::chrono::{SecondsFormat, Utc};
+use kube::{
+	api::{DynamicObject, Patch, PatchParams},
+	Api, Resource,
+};
+use schemars::JsonSchema;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::json;
+use std::fmt::Debug;
+
+pub const READY: &str = "Ready";
+
+/// A status condition following the Kubernetes API conventions.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Condition {
+	#[serde(rename = "type")]
+	pub type_: String,
+	/// One of `True`, `False` or `Unknown`.
+	pub status: String,
+	pub reason: String,
+	pub message: String,
+	/// RFC 3339 time of the last change of `status`.
+	pub last_transition_time: String,
+}
+
+impl Condition {
+	pub fn new(type_: &str, status: bool, reason: &str, message: &str) -> Self {
+		Self {
+			type_: type_.to_string(),
+			status: if status {
+				"True"
+			} else {
+				"False"
+			}
+			.to_string(),
+			reason: reason.to_string(),
+			message: message.to_string(),
+			last_transition_time: now(),
+		}
+	}
+
+	pub fn is_true(&self) -> bool {
+		self.status == "True"
+	}
+}
+
+/// Adds or replaces the condition of the same type. The transition time is kept when the
+/// status did not change.
+pub fn set_condition(conditions: &mut Vec<Condition>, mut condition: Condition) {
+	match conditions.iter_mut().find(|existing| existing.type_ == condition.type_) {
+		Some(existing) => {
+			if existing.status == condition.status {
+				condition.last_transition_time = existing.last_transition_time.clone();
+			}
+			*existing = condition;
+		}
+		None => conditions.push(condition),
+	}
+}
+
+pub fn find_condition<'a>(conditions: &'a [Condition], type_: &str) -> Option<&'a Condition> {
+	conditions.iter().find(|condition| condition.type_ == type_)
+}
+
+/// The current time in the format used for condition transition times.
+pub fn now() -> String {
+	Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// Replaces the status of a resource through its status subresource.
+pub async fn patch_status<K, S>(api: &Api<K>, name: &str, status: &S) -> Result<(), kube::Error>
+where
+	K: Resource + Clone + DeserializeOwned + Debug,
+	S: Serialize,
+{
+	let patch = json!({ "status": status });
+	api.patch_status(name, &PatchParams::default(), &Patch::Merge(&patch)).await?;
+	Ok(())
+}
+
+/// Sets `condition` on the live object, leaving its status alone when the condition is
+/// unchanged or the object is gone.
+pub async fn publish_condition(
+	api: &Api<DynamicObject>,
+	name: &str,
+	condition: Condition,
+) -> Result<(), kube::Error> {
+	let Some(live) = api.get_opt(name).await? else {
+		return Ok(());
+	};
+	let mut conditions: Vec<Condition> =
+		serde_json::from_value(live.data["status"]["conditions"].clone()).unwrap_or_default();
+
+	let unchanged = find_condition(&conditions, &condition.type_).is_some_and(|existing| {
+		(&existing.status, &existing.reason, &existing.message)
+			== (&condition.status, &condition.reason, &condition.message)
+	});
+	if unchanged {
+		return Ok(());
+	}
+
+	set_condition(&mut conditions, condition);
+	patch_status(api, name, &json!({ "conditions": conditions })).await
+}
+
+/// Removes the condition of type `type_` from the live object, if it has one.
+pub async fn retract_condition(
+	api: &Api<DynamicObject>,
+	name: &str,
+	type_: &str,
+) -> Result<(), kube::Error> {
+	let Some(live) = api.get_opt(name).await? else {
+		return Ok(());
+	};
+	let mut conditions: Vec<Condition> =
+		serde_json::from_value(live.data["status"]["conditions"].clone()).unwrap_or_default();
+	if find_condition(&conditions, type_).is_none() {
+		return Ok(());
+	}
+
+	conditions.retain(|condition| condition.type_ != type_);
+	patch_status(api, name, &json!({ "conditions": conditions })).await
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn set_condition_keeps_transition_time_while_status_is_unchanged() {
+		let mut ready = Condition::new(READY, true, "Applied", "");
+		ready.last_transition_time = "2024-01-01T00:00:00Z".to_string();
+		let mut conditions = vec![ready];
+
+		set_condition(&mut conditions, Condition::new(READY, true, "Applied", "again"));
+		assert_eq!(conditions[0].last_transition_time, "2024-01-01T00:00:00Z");
+		assert_eq!(conditions[0].message, "again");
+
+		set_condition(&mut conditions, Condition::new(READY, false, "Failed", ""));
+		assert_ne!(conditions[0].last_transition_time, "2024-01-01T00:00:00Z");
+		assert_eq!(conditions.len(), 1);
+		assert!(!conditions[0].is_true());
+	}
+}
@@ -0,0 +1,104 @@
+use schemars::{gen::SchemaGenerator, schema::Schema, JsonSchema};
+use serde::Serialize;
+
+/// A DNS-1123 label, e.g. the name of a namespace.
+pub const DNS_LABEL: &str = r"^[a-z0-9]([-a-z0-9]*[a-z0-9])?$";
+/// A DNS-1123 subdomain, e.g. the name of most resources.
+pub const DNS_SUBDOMAIN: &str =
+	r"^[a-z0-9]([-a-z0-9]*[a-z0-9])?(\.[a-z0-9]([-a-z0-9]*[a-z0-9])?)*$";
+/// A resource quantity with an optional binary or decimal suffix, e.g. `10Gi`.
+pub const QUANTITY: &str = r"^[0-9]+(\.[0-9]+)?(Ki|Mi|Gi|Ti|Pi|Ei|k|M|G|T|P|E)?$";
+/// A duration in seconds, minutes, hours or days, e.g. `30d`.
+pub const DURATION: &str = r"^[0-9]+(s|m|h|d)$";
+
+pub const VALIDATIONS: &str = "x-kubernetes-validations";
+
+/// A CEL expression the API server evaluates against an object, rejecting it with `message`
+/// when the expression is false. Rules referring to `oldSelf` only apply to updates.
+#[derive(Serialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Rule {
+	pub rule: &'static str,
+	pub message: &'static str,
+}
+
+impl Rule {
+	pub const fn new(rule: &'static str, message: &'static str) -> Self {
+		Self {
+			rule,
+			message,
+		}
+	}
+}
+
+/// A type whose invariants span several of its fields.
+pub trait Validated: JsonSchema {
+	const RULES: &'static [Rule];
+}
+
+/// The schema of `T` with its rules, for `#[schemars(schema_with = "schema::<T>")]`.
+pub fn schema<T: Validated>(gen: &mut SchemaGenerator) -> Schema {
+	with_rules(T::json_schema(gen), T::RULES)
+}
+
+/// The schema of an optional `T` with its rules, which only apply when it is set.
+pub fn optional_schema<T: Validated>(gen: &mut SchemaGenerator) -> Schema {
+	with_rules(<Option<T>>::json_schema(gen), T::RULES)
+}
+
+fn with_rules(schema: Schema, rules: &[Rule]) -> Schema {
+	let mut schema = schema.into_object();
+	schema.extensions.insert(
+		VALIDATIONS.to_string(),
+		serde_json::to_value(rules).expect("rules serialize to JSON"),
+	);
+	Schema::Object(schema)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use schemars::gen::SchemaSettings;
+	use serde::Deserialize;
+	use serde_json::json;
+
+	#[derive(Deserialize, JsonSchema)]
+	struct Range {
+		#[allow(dead_code)]
+		min: i32,
+		#[allow(dead_code)]
+		max: i32,
+	}
+
+	impl Validated for Range {
+		const RULES: &'static [Rule] = &[Rule::new("self.min <= self.max", "min exceeds max")];
+	}
+
+	fn generator() -> SchemaGenerator {
+		SchemaSettings::openapi3()
+			.with(|settings| {
+				settings.inline_subschemas = true;
+				settings.option_nullable = true;
+				settings.option_add_null_type = false;
+			})
+			.into_generator()
+	}
+
+	#[test]
+	fn rules_are_emitted_next_to_the_properties() {
+		let schema = serde_json::to_value(schema::<Range>(&mut generator())).unwrap();
+
+		assert_eq!(
+			schema[VALIDATIONS],
+			json!([{ "rule": "self.min <= self.max", "message": "min exceeds max" }])
+		);
+		assert!(schema["properties"]["min"].is_object());
+	}
+
+	#[test]
+	fn optional_values_keep_their_rules() {
+		let schema = serde_json::to_value(optional_schema::<Range>(&mut generator())).unwrap();
+
+		assert_eq!(schema["nullable"], true);
+		assert_eq!(schema[VALIDATIONS][0]["message"], "min exceeds max");
+	}
+}
@@ -1,2 +1,4 @@
+pub mod catalog;
 pub mod core;
+pub mod schema;
 pub mod surrealdb;
@@ -0,0 +1,191 @@
+use serde_json::Value;
+
+use super::{FieldDefinition, FieldType, SchemaError, TableDefinition};
+
+/// Parses an Avro record schema, or a list of them, into one table per record.
+pub fn parse(definition: &str) -> Result<Vec<TableDefinition>, SchemaError> {
+	let schema: Value = serde_json::from_str(definition)?;
+	let records = match &schema {
+		Value::Array(records) => records.iter().collect(),
+		record => vec![record],
+	};
+
+	records.into_iter().map(table).collect()
+}
+
+fn table(record: &Value) -> Result<TableDefinition, SchemaError> {
+	if record.get("type").and_then(Value::as_str) != Some("record") {
+		return Err(SchemaError::Invalid("expected an Avro record".to_string()));
+	}
+	let name = record
+		.get("name")
+		.and_then(Value::as_str)
+		.ok_or_else(|| SchemaError::Invalid("an Avro record needs a name".to_string()))?;
+
+	let mut fields = Vec::new();
+	record_fields("", record, &mut fields)?;
+
+	Ok(TableDefinition {
+		name: name.to_string(),
+		fields,
+		indexes: Vec::new(),
+	})
+}
+
+fn record_fields(
+	prefix: &str,
+	record: &Value,
+	fields: &mut Vec<FieldDefinition>,
+) -> Result<(), SchemaError> {
+	let definitions = record
+		.get("fields")
+		.and_then(Value::as_array)
+		.ok_or_else(|| SchemaError::Invalid("an Avro record needs fields".to_string()))?;
+
+	for field in definitions {
+		let name = field
+			.get("name")
+			.and_then(Value::as_str)
+			.ok_or_else(|| SchemaError::Invalid("an Avro field needs a name".to_string()))?;
+		let name = format!("{}{}", prefix, name);
+		let schema = field.get("type").unwrap_or(&Value::Null);
+		let (kind, optional) = field_type(schema)?;
+
+		fields.push(FieldDefinition {
+			name: name.clone(),
+			kind: kind.clone(),
+			optional,
+		});
+
+		match (&kind, non_null(schema)) {
+			(FieldType::Object, Some(nested)) if is_record(nested) => {
+				record_fields(&format!("{}.", name), nested, fields)?
+			}
+			(FieldType::Array(_), Some(array)) if is_record(&array["items"]) => {
+				record_fields(&format!("{}.*.", name), &array["items"], fields)?
+			}
+			_ => {}
+		}
+	}
+
+	Ok(())
+}
+
+fn is_record(schema: &Value) -> bool {
+	schema.get("type").and_then(Value::as_str) == Some("record")
+}
+
+/// The non-null branch of a `["null", T]` union, or the schema itself.
+fn non_null(schema: &Value) -> Option<&Value> {
+	match schema {
+		Value::Array(branches) => {
+			let mut branches = branches.iter().filter(|branch| branch.as_str() != Some("null"));
+			match (branches.next(), branches.next()) {
+				(Some(branch), None) => Some(branch),
+				_ => None,
+			}
+		}
+		schema => Some(schema),
+	}
+}
+
+/// The type of a field schema and whether it accepts `null`.
+fn field_type(schema: &Value) -> Result<(FieldType, bool), SchemaError> {
+	if let Value::Array(branches) = schema {
+		let nullable = branches.iter().any(|branch| branch.as_str() == Some("null"));
+		return match non_null(schema) {
+			Some(branch) => Ok((field_type(branch)?.0, nullable)),
+			None => Ok((FieldType::Any, nullable)),
+		};
+	}
+
+	let logical_type = schema.get("logicalType").and_then(Value::as_str);
+	let type_name = match schema {
+		Value::String(type_name) => type_name.as_str(),
+		Value::Object(object) => object.get("type").and_then(Value::as_str).unwrap_or_default(),
+		other => return Err(SchemaError::UnsupportedType(other.to_string())),
+	};
+
+	let kind = match (type_name, logical_type) {
+		("null", _) => return Ok((FieldType::Any, true)),
+		(_, Some("timestamp-millis" | "timestamp-micros")) => FieldType::Datetime,
+		(_, Some("decimal")) => FieldType::Decimal,
+		("boolean", _) => FieldType::Bool,
+		("int" | "long", _) => FieldType::Int,
+		("float" | "double", _) => FieldType::Float,
+		("string" | "enum", _) => FieldType::String,
+		("bytes" | "fixed", _) => FieldType::Bytes,
+		("record" | "map", _) => FieldType::Object,
+		("array", _) => FieldType::Array(Box::new(field_type(&schema["items"])?.0)),
+		(other, _) => return Err(SchemaError::UnsupportedType(other.to_string())),
+	};
+
+	Ok((kind, false))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::schema::{Schema, SchemaFormat};
+
+	#[test]
+	fn record_fields_become_table_fields() {
+		let definition = r#"{
+			"type": "record",
+			"name": "customer",
+			"fields": [
+				{ "name": "id", "type": "long" },
+				{ "name": "email", "type": ["null", "string"] },
+				{ "name": "joined", "type": { "type": "long", "logicalType": "timestamp-millis" } },
+				{ "name": "tags", "type": { "type": "array", "items": "string" } },
+				{
+					"name": "address",
+					"type": {
+						"type": "record",
+						"name": "address",
+						"fields": [{ "name": "city", "type": "string" }]
+					}
+				}
+			]
+		}"#;
+
+		let tables = parse(definition).unwrap();
+		let fields: Vec<(&str, String, bool)> = tables[0]
+			.fields
+			.iter()
+			.map(|field| (field.name.as_str(), field.kind.to_string(), field.optional))
+			.collect();
+
+		assert_eq!(tables[0].name, "customer");
+		assert_eq!(
+			fields,
+			vec![
+				("id", "int".to_string(), false),
+				("email", "string".to_string(), true),
+				("joined", "datetime".to_string(), false),
+				("tags", "array<string>".to_string(), false),
+				("address", "object".to_string(), false),
+				("address.city", "string".to_string(), false),
+			]
+		);
+	}
+
+	#[test]
+	fn non_record_schema_is_rejected() {
+		assert!(matches!(parse(r#""string""#), Err(SchemaError::Invalid(_))));
+	}
+
+	#[test]
+	fn field_name_with_a_statement_is_rejected() {
+		let definition = r#"{
+			"type": "record",
+			"name": "customer",
+			"fields": [{ "name": "id TYPE int; REMOVE DATABASE shop", "type": "long" }]
+		}"#;
+
+		assert!(matches!(
+			Schema::parse(SchemaFormat::Avro, definition),
+			Err(SchemaError::Invalid(_))
+		));
+	}
+}
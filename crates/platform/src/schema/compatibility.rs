@@ -0,0 +1,226 @@
+use std::fmt;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::{FieldDefinition, FieldType, Schema, TableDefinition};
+
+/// Which readers must keep working across a schema change, as in a schema registry.
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, Copy, JsonSchema)]
+pub enum CompatibilityMode {
+	/// Any change is accepted.
+	None,
+	/// Readers of the new version can read data written with the previous one.
+	#[default]
+	Backward,
+	/// Readers of the previous version can read data written with the new one.
+	Forward,
+	/// Both backward and forward.
+	Full,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ChangeClass {
+	/// Adds to the schema without affecting any reader.
+	Additive,
+	/// Allowed by the compatibility mode.
+	Compatible,
+	/// Violates the compatibility mode.
+	Breaking,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SchemaChange {
+	/// `table` or `table.field`.
+	pub path: String,
+	pub description: String,
+	pub class: ChangeClass,
+}
+
+impl fmt::Display for SchemaChange {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}: {}", self.path, self.description)
+	}
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct CompatibilityReport {
+	pub changes: Vec<SchemaChange>,
+}
+
+impl CompatibilityReport {
+	pub fn breaking(&self) -> Vec<&SchemaChange> {
+		self.changes.iter().filter(|change| change.class == ChangeClass::Breaking).collect()
+	}
+
+	pub fn is_compatible(&self) -> bool {
+		self.breaking().is_empty()
+	}
+}
+
+/// Compares two versions of a schema and classifies every change under `mode`.
+pub fn check(previous: &Schema, next: &Schema, mode: CompatibilityMode) -> CompatibilityReport {
+	let mut changes = Vec::new();
+	let mut record = |path: String, description: String, backward: bool, forward: bool, added| {
+		changes.push(SchemaChange {
+			path,
+			description,
+			class: classify(mode, backward, forward, added),
+		});
+	};
+
+	for table in &previous.tables {
+		match next.table(&table.name) {
+			Some(next_table) => compare_tables(table, next_table, &mut record),
+			None => record(table.name.clone(), "table removed".to_string(), true, false, false),
+		}
+	}
+
+	for table in next.tables.iter().filter(|table| previous.table(&table.name).is_none()) {
+		record(table.name.clone(), "table added".to_string(), true, true, true);
+	}
+
+	CompatibilityReport {
+		changes,
+	}
+}
+
+fn compare_tables(
+	previous: &TableDefinition,
+	next: &TableDefinition,
+	record: &mut impl FnMut(String, String, bool, bool, bool),
+) {
+	for field in &previous.fields {
+		let path = format!("{}.{}", previous.name, field.name);
+		match next.field(&field.name) {
+			Some(next_field) => compare_fields(path, field, next_field, record),
+			// Readers of the new version ignore the field, old readers miss it unless optional.
+			None => record(path, "field removed".to_string(), true, field.optional, false),
+		}
+	}
+
+	for field in next.fields.iter().filter(|field| previous.field(&field.name).is_none()) {
+		let path = format!("{}.{}", next.name, field.name);
+		let description = if field.optional {
+			"optional field added"
+		} else {
+			"required field added"
+		};
+		// Data written before lacks the field, which only optional fields tolerate.
+		record(path, description.to_string(), field.optional, true, true);
+	}
+}
+
+fn compare_fields(
+	path: String,
+	previous: &FieldDefinition,
+	next: &FieldDefinition,
+	record: &mut impl FnMut(String, String, bool, bool, bool),
+) {
+	if previous.kind != next.kind {
+		let description = format!("type changed from {} to {}", previous.kind, next.kind);
+		let backward = widens(&previous.kind, &next.kind);
+		let forward = widens(&next.kind, &previous.kind);
+		record(path.clone(), description, backward, forward, false);
+	}
+
+	match (previous.optional, next.optional) {
+		(true, false) => record(path, "field became required".to_string(), false, true, false),
+		(false, true) => record(path, "field became optional".to_string(), true, false, false),
+		_ => {}
+	}
+}
+
+/// Whether every value of type `from` is also a valid value of type `to`.
+fn widens(from: &FieldType, to: &FieldType) -> bool {
+	match (from, to) {
+		(from, to) if from == to => true,
+		(_, FieldType::Any) => true,
+		(FieldType::Int, FieldType::Float | FieldType::Decimal) => true,
+		(FieldType::Float, FieldType::Decimal) => true,
+		(FieldType::Record(_), FieldType::Record(table)) => table.is_empty(),
+		(FieldType::Array(from), FieldType::Array(to)) => widens(from, to),
+		_ => false,
+	}
+}
+
+fn classify(mode: CompatibilityMode, backward: bool, forward: bool, added: bool) -> ChangeClass {
+	let allowed = match mode {
+		CompatibilityMode::None => true,
+		CompatibilityMode::Backward => backward,
+		CompatibilityMode::Forward => forward,
+		CompatibilityMode::Full => backward && forward,
+	};
+
+	match (allowed, backward && forward && added) {
+		(false, _) => ChangeClass::Breaking,
+		(true, true) => ChangeClass::Additive,
+		(true, false) => ChangeClass::Compatible,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::schema::SchemaFormat;
+
+	fn schema(definition: &str) -> Schema {
+		Schema::parse(SchemaFormat::SurrealQL, definition).unwrap()
+	}
+
+	fn classes(report: &CompatibilityReport) -> Vec<(&str, ChangeClass)> {
+		report.changes.iter().map(|change| (change.path.as_str(), change.class)).collect()
+	}
+
+	#[test]
+	fn optional_fields_and_tables_are_additive_in_every_mode() {
+		let previous = schema("DEFINE TABLE order; DEFINE FIELD id ON order TYPE int;");
+		let next = schema(
+			"DEFINE TABLE order; DEFINE FIELD id ON order TYPE int;
+			DEFINE FIELD note ON order TYPE option<string>; DEFINE TABLE line;",
+		);
+
+		let report = check(&previous, &next, CompatibilityMode::Full);
+
+		assert_eq!(
+			classes(&report),
+			vec![("order.note", ChangeClass::Additive), ("line", ChangeClass::Additive)]
+		);
+	}
+
+	#[test]
+	fn required_field_added_breaks_backward_but_not_forward() {
+		let previous = schema("DEFINE TABLE order;");
+		let next = schema("DEFINE TABLE order; DEFINE FIELD id ON order TYPE int;");
+
+		assert!(!check(&previous, &next, CompatibilityMode::Backward).is_compatible());
+		assert!(check(&previous, &next, CompatibilityMode::Forward).is_compatible());
+		assert!(check(&previous, &next, CompatibilityMode::None).is_compatible());
+	}
+
+	#[test]
+	fn removed_required_field_breaks_forward_but_not_backward() {
+		let previous = schema("DEFINE TABLE order; DEFINE FIELD id ON order TYPE int;");
+		let next = schema("DEFINE TABLE order;");
+
+		assert!(check(&previous, &next, CompatibilityMode::Backward).is_compatible());
+		assert_eq!(
+			check(&previous, &next, CompatibilityMode::Forward).breaking()[0].to_string(),
+			"order.id: field removed"
+		);
+	}
+
+	#[test]
+	fn widening_a_type_is_only_backward_compatible() {
+		let previous = schema("DEFINE TABLE order; DEFINE FIELD total ON order TYPE int;");
+		let next = schema("DEFINE TABLE order; DEFINE FIELD total ON order TYPE float;");
+		let unrelated = schema("DEFINE TABLE order; DEFINE FIELD total ON order TYPE string;");
+
+		assert!(check(&previous, &next, CompatibilityMode::Backward).is_compatible());
+		assert!(!check(&previous, &next, CompatibilityMode::Forward).is_compatible());
+		assert_eq!(
+			check(&previous, &unrelated, CompatibilityMode::Backward).breaking()[0].to_string(),
+			"order.total: type changed from int to string"
+		);
+	}
+}
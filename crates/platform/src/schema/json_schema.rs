@@ -0,0 +1,171 @@
+use std::collections::BTreeMap;
+
+use serde_json::{Map, Value};
+
+use super::{FieldDefinition, FieldType, SchemaError, TableDefinition};
+
+/// Parses a JSON Schema object into a table named after its `title`.
+pub fn parse(definition: &str) -> Result<Vec<TableDefinition>, SchemaError> {
+	let schema: Value = serde_json::from_str(definition)?;
+	let name = schema
+		.get("title")
+		.and_then(Value::as_str)
+		.ok_or_else(|| SchemaError::Invalid("a JSON Schema needs a title".to_string()))?;
+
+	let mut fields = Vec::new();
+	object_fields("", &schema, &mut fields)?;
+
+	Ok(vec![TableDefinition {
+		name: name.to_string(),
+		fields,
+		indexes: Vec::new(),
+	}])
+}
+
+fn object_fields(
+	prefix: &str,
+	schema: &Value,
+	fields: &mut Vec<FieldDefinition>,
+) -> Result<(), SchemaError> {
+	let empty = Map::new();
+	// Fields come sorted by name, whether or not serde_json keeps the order of the properties.
+	let properties: BTreeMap<&String, &Value> =
+		schema.get("properties").and_then(Value::as_object).unwrap_or(&empty).iter().collect();
+	let required: Vec<&str> = schema
+		.get("required")
+		.and_then(Value::as_array)
+		.map(|required| required.iter().filter_map(Value::as_str).collect())
+		.unwrap_or_default();
+
+	for (key, property) in properties {
+		let name = format!("{}{}", prefix, key);
+		let (kind, nullable) = field_type(property)?;
+		let optional = nullable || !required.contains(&key.as_str());
+
+		fields.push(FieldDefinition {
+			name: name.clone(),
+			kind: kind.clone(),
+			optional,
+		});
+		nested_fields(&name, property, &kind, fields)?;
+	}
+
+	Ok(())
+}
+
+fn nested_fields(
+	name: &str,
+	property: &Value,
+	kind: &FieldType,
+	fields: &mut Vec<FieldDefinition>,
+) -> Result<(), SchemaError> {
+	match kind {
+		FieldType::Object => object_fields(&format!("{}.", name), property, fields),
+		FieldType::Array(items) if **items == FieldType::Object => {
+			object_fields(&format!("{}.*.", name), &property["items"], fields)
+		}
+		_ => Ok(()),
+	}
+}
+
+/// The type of a property and whether it accepts `null`.
+fn field_type(property: &Value) -> Result<(FieldType, bool), SchemaError> {
+	let (type_name, nullable) = match property.get("type") {
+		None => return Ok((FieldType::Any, false)),
+		Some(Value::String(type_name)) => (type_name.as_str(), false),
+		Some(Value::Array(types)) => {
+			let types: Vec<&str> = types.iter().filter_map(Value::as_str).collect();
+			let nullable = types.contains(&"null");
+			match types.iter().filter(|type_name| **type_name != "null").collect::<Vec<_>>()[..] {
+				[type_name] => (*type_name, nullable),
+				_ => return Ok((FieldType::Any, nullable)),
+			}
+		}
+		Some(other) => return Err(SchemaError::UnsupportedType(other.to_string())),
+	};
+
+	let kind = match type_name {
+		"string" => match property.get("format").and_then(Value::as_str) {
+			Some("date-time") => FieldType::Datetime,
+			Some("duration") => FieldType::Duration,
+			_ => FieldType::String,
+		},
+		"integer" => FieldType::Int,
+		"number" => FieldType::Float,
+		"boolean" => FieldType::Bool,
+		"object" => FieldType::Object,
+		"array" => {
+			let items = property.get("items").map(field_type).transpose()?;
+			FieldType::Array(Box::new(items.map_or(FieldType::Any, |(items, _)| items)))
+		}
+		other => return Err(SchemaError::UnsupportedType(other.to_string())),
+	};
+
+	Ok((kind, nullable))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::schema::{Schema, SchemaFormat};
+
+	#[test]
+	fn properties_become_fields_of_the_titled_table() {
+		let definition = r#"{
+			"title": "order",
+			"type": "object",
+			"required": ["id", "address"],
+			"properties": {
+				"id": { "type": "integer" },
+				"placedAt": { "type": "string", "format": "date-time" },
+				"note": { "type": ["string", "null"] },
+				"address": {
+					"type": "object",
+					"required": ["city"],
+					"properties": { "city": { "type": "string" } }
+				},
+				"lines": {
+					"type": "array",
+					"items": { "type": "object", "properties": { "sku": { "type": "string" } } }
+				}
+			}
+		}"#;
+
+		let tables = parse(definition).unwrap();
+		let fields: Vec<(&str, String, bool)> = tables[0]
+			.fields
+			.iter()
+			.map(|field| (field.name.as_str(), field.kind.to_string(), field.optional))
+			.collect();
+
+		assert_eq!(tables[0].name, "order");
+		assert_eq!(
+			fields,
+			vec![
+				("address", "object".to_string(), false),
+				("address.city", "string".to_string(), false),
+				("id", "int".to_string(), false),
+				("lines", "array<object>".to_string(), true),
+				("lines.*.sku", "string".to_string(), true),
+				("note", "string".to_string(), true),
+				("placedAt", "datetime".to_string(), true),
+			]
+		);
+	}
+
+	#[test]
+	fn schema_without_title_is_rejected() {
+		assert!(matches!(parse(r#"{ "type": "object" }"#), Err(SchemaError::Invalid(_))));
+	}
+
+	#[test]
+	fn title_with_a_statement_is_rejected() {
+		let definition =
+			r#"{ "title": "order SCHEMALESS; REMOVE DATABASE shop", "type": "object" }"#;
+
+		assert!(matches!(
+			Schema::parse(SchemaFormat::JsonSchema, definition),
+			Err(SchemaError::Invalid(_))
+		));
+	}
+}
@@ -0,0 +1,176 @@
+pub mod avro;
+pub mod compatibility;
+pub mod json_schema;
+pub mod surrealql;
+
+use std::fmt;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// The languages a data schema can be written in.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, JsonSchema)]
+pub enum SchemaFormat {
+	JsonSchema,
+	Avro,
+	SurrealQL,
+}
+
+/// A schema in its normalized form, independent of the format it was written in.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct Schema {
+	pub tables: Vec<TableDefinition>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TableDefinition {
+	pub name: String,
+	pub fields: Vec<FieldDefinition>,
+	pub indexes: Vec<IndexDefinition>,
+}
+
+/// A field of a table. Fields of nested objects are separate definitions with a dotted
+/// name, e.g. `address.city`, and `*` stands for the items of an array.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct FieldDefinition {
+	pub name: String,
+	pub kind: FieldType,
+	pub optional: bool,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct IndexDefinition {
+	pub name: String,
+	/// Field names, in the same form as [`FieldDefinition::name`].
+	pub columns: Vec<String>,
+	pub unique: bool,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum FieldType {
+	Any,
+	Bool,
+	Int,
+	Float,
+	Decimal,
+	String,
+	Datetime,
+	Duration,
+	Bytes,
+	Object,
+	Array(Box<FieldType>),
+	Record(String),
+}
+
+impl fmt::Display for FieldType {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			FieldType::Any => write!(f, "any"),
+			FieldType::Bool => write!(f, "bool"),
+			FieldType::Int => write!(f, "int"),
+			FieldType::Float => write!(f, "float"),
+			FieldType::Decimal => write!(f, "decimal"),
+			FieldType::String => write!(f, "string"),
+			FieldType::Datetime => write!(f, "datetime"),
+			FieldType::Duration => write!(f, "duration"),
+			FieldType::Bytes => write!(f, "bytes"),
+			FieldType::Object => write!(f, "object"),
+			FieldType::Array(items) => write!(f, "array<{}>", items),
+			FieldType::Record(table) => write!(f, "record<{}>", table),
+		}
+	}
+}
+
+impl FieldType {
+	/// The table referenced by a record, or by the items of an array of records.
+	fn record_table(&self) -> Option<&str> {
+		match self {
+			FieldType::Record(table) => Some(table),
+			FieldType::Array(items) => items.record_table(),
+			_ => None,
+		}
+	}
+}
+
+impl TableDefinition {
+	pub fn field(&self, name: &str) -> Option<&FieldDefinition> {
+		self.fields.iter().find(|field| field.name == name)
+	}
+}
+
+impl Schema {
+	/// Parses a schema written in `format`. Table, field and index names end up in SurrealQL
+	/// statements, so each of them, and each part of a dotted field name, must be an identifier.
+	pub fn parse(format: SchemaFormat, definition: &str) -> Result<Self, SchemaError> {
+		let tables = match format {
+			SchemaFormat::JsonSchema => json_schema::parse(definition)?,
+			SchemaFormat::Avro => avro::parse(definition)?,
+			SchemaFormat::SurrealQL => surrealql::parse(definition)?,
+		};
+
+		if tables.is_empty() {
+			return Err(SchemaError::Invalid("the schema defines no table".to_string()));
+		}
+		tables.iter().try_for_each(check_names)?;
+
+		Ok(Self {
+			tables,
+		})
+	}
+
+	pub fn table(&self, name: &str) -> Option<&TableDefinition> {
+		self.tables.iter().find(|table| table.name == name)
+	}
+}
+
+/// Whether `name` matches `^[A-Za-z_][A-Za-z0-9_]*$`.
+pub fn is_identifier(name: &str) -> bool {
+	let mut chars = name.chars();
+	chars.next().is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+		&& chars.all(|char| char.is_ascii_alphanumeric() || char == '_')
+}
+
+/// Whether `name` is a field name: identifiers separated by `.`, with `*` for array items.
+fn is_field_name(name: &str) -> bool {
+	name.split('.').all(|part| part == "*" || is_identifier(part))
+}
+
+fn check_names(table: &TableDefinition) -> Result<(), SchemaError> {
+	let invalid = |kind: &str, name: &str| {
+		Err(SchemaError::Invalid(format!("{} name {:?} is not an identifier", kind, name)))
+	};
+
+	if !is_identifier(&table.name) {
+		return invalid("table", &table.name);
+	}
+	for field in &table.fields {
+		if !is_field_name(&field.name) {
+			return invalid("field", &field.name);
+		}
+		if let Some(record) = field.kind.record_table() {
+			if !record.is_empty() && !is_identifier(record) {
+				return invalid("table", record);
+			}
+		}
+	}
+	for index in &table.indexes {
+		if !is_identifier(&index.name) {
+			return invalid("index", &index.name);
+		}
+		if let Some(column) = index.columns.iter().find(|column| !is_field_name(column)) {
+			return invalid("field", column);
+		}
+	}
+
+	Ok(())
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SchemaError {
+	#[error("Schema is not valid JSON: {0}")]
+	Json(#[from] serde_json::Error),
+	#[error("Invalid schema: {0}")]
+	Invalid(String),
+	#[error("Unsupported type {0:?}")]
+	UnsupportedType(String),
+}
@@ -0,0 +1,285 @@
+use super::{FieldDefinition, FieldType, IndexDefinition, Schema, SchemaError, TableDefinition};
+
+/// Parses `DEFINE TABLE`, `DEFINE FIELD` and `DEFINE INDEX` statements and rejects any other
+/// statement. Other clauses, e.g. `ASSERT` or `PERMISSIONS`, are accepted but not part of the
+/// normalized schema.
+pub fn parse(definition: &str) -> Result<Vec<TableDefinition>, SchemaError> {
+	let mut tables: Vec<TableDefinition> = Vec::new();
+
+	for statement in definition.split(';').map(str::trim).filter(|statement| !statement.is_empty())
+	{
+		let tokens: Vec<&str> = statement.split_whitespace().collect();
+		let keywords: Vec<String> =
+			tokens.iter().take(2).map(|token| token.to_uppercase()).collect();
+
+		match keywords.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+			["DEFINE", "TABLE"] => {
+				let name = tokens.get(2).ok_or_else(|| invalid(statement))?;
+				if !tables.iter().any(|table| table.name == *name) {
+					tables.push(TableDefinition {
+						name: name.to_string(),
+						fields: Vec::new(),
+						indexes: Vec::new(),
+					});
+				}
+			}
+			["DEFINE", "FIELD"] => {
+				let (table, field) = field(statement, &tokens)?;
+				defined_table(&mut tables, table, "field", &field.name)?.fields.push(field);
+			}
+			["DEFINE", "INDEX"] => {
+				let (table, index) = index(statement, &tokens)?;
+				defined_table(&mut tables, table, "index", &index.name)?.indexes.push(index);
+			}
+			_ => {
+				return Err(SchemaError::Invalid(format!(
+					"only DEFINE TABLE, DEFINE FIELD and DEFINE INDEX statements are allowed, got {:?}",
+					statement
+				)))
+			}
+		}
+	}
+
+	Ok(tables)
+}
+
+fn defined_table<'a>(
+	tables: &'a mut [TableDefinition],
+	table: &str,
+	kind: &str,
+	name: &str,
+) -> Result<&'a mut TableDefinition, SchemaError> {
+	tables.iter_mut().find(|defined| defined.name == table).ok_or_else(|| {
+		SchemaError::Invalid(format!("{} {} on undefined table {}", kind, name, table))
+	})
+}
+
+/// Splits `DEFINE <kind> <name> ON [TABLE] <table> <rest>` into the name, the table and the
+/// tokens after the table.
+fn target<'a, 't>(
+	statement: &str,
+	tokens: &'t [&'a str],
+) -> Result<(&'a str, &'a str, &'t [&'a str]), SchemaError> {
+	let name = tokens.get(2).ok_or_else(|| invalid(statement))?;
+	if !tokens.get(3).is_some_and(|token| token.eq_ignore_ascii_case("ON")) {
+		return Err(invalid(statement));
+	}
+
+	let mut rest = &tokens[4..];
+	if rest.first().is_some_and(|token| token.eq_ignore_ascii_case("TABLE")) {
+		rest = &rest[1..];
+	}
+	let table = rest.first().ok_or_else(|| invalid(statement))?;
+
+	Ok((name, table, &rest[1..]))
+}
+
+/// Parses `DEFINE FIELD <name> ON [TABLE] <table> [TYPE <type>] ...`.
+fn field<'a>(
+	statement: &str,
+	tokens: &[&'a str],
+) -> Result<(&'a str, FieldDefinition), SchemaError> {
+	let (name, table, rest) = target(statement, tokens)?;
+
+	let type_position = rest.iter().position(|token| token.eq_ignore_ascii_case("TYPE"));
+	let (kind, optional) = match type_position.and_then(|position| rest.get(position + 1)) {
+		Some(type_name) => parse_type(type_name)?,
+		None => (FieldType::Any, true),
+	};
+
+	Ok((
+		table,
+		FieldDefinition {
+			name: name.to_string(),
+			kind,
+			optional,
+		},
+	))
+}
+
+/// Parses a SurrealQL type and whether it is wrapped in `option<..>`.
+pub fn parse_type(type_name: &str) -> Result<(FieldType, bool), SchemaError> {
+	let lower = type_name.to_lowercase();
+
+	if let Some(inner) = generic(&lower, "option") {
+		return Ok((parse_type(inner)?.0, true));
+	}
+	if let Some(inner) = generic(&lower, "array") {
+		return Ok((FieldType::Array(Box::new(parse_type(inner)?.0)), false));
+	}
+	if let Some(table) = generic(&lower, "record") {
+		return Ok((FieldType::Record(table.to_string()), false));
+	}
+
+	let kind = match lower.as_str() {
+		"any" => FieldType::Any,
+		"bool" => FieldType::Bool,
+		"int" => FieldType::Int,
+		"float" | "number" => FieldType::Float,
+		"decimal" => FieldType::Decimal,
+		"string" => FieldType::String,
+		"datetime" => FieldType::Datetime,
+		"duration" => FieldType::Duration,
+		"bytes" => FieldType::Bytes,
+		"object" => FieldType::Object,
+		"array" => FieldType::Array(Box::new(FieldType::Any)),
+		"record" => FieldType::Record(String::new()),
+		_ => return Err(SchemaError::UnsupportedType(type_name.to_string())),
+	};
+
+	Ok((kind, false))
+}
+
+/// Parses `DEFINE INDEX <name> ON [TABLE] <table> COLUMNS|FIELDS <field>, ... [UNIQUE]`.
+fn index<'a>(
+	statement: &str,
+	tokens: &[&'a str],
+) -> Result<(&'a str, IndexDefinition), SchemaError> {
+	let (name, table, rest) = target(statement, tokens)?;
+	let Some((keyword, rest)) = rest.split_first() else {
+		return Err(invalid(statement));
+	};
+	if !keyword.eq_ignore_ascii_case("COLUMNS") && !keyword.eq_ignore_ascii_case("FIELDS") {
+		return Err(invalid(statement));
+	}
+
+	// The list ends at the first token that is not joined to the next one by a comma.
+	let mut list = String::new();
+	let mut clauses = rest.iter().peekable();
+	while let Some(token) = clauses.next() {
+		list.push_str(token);
+		if !list.ends_with(',') && !clauses.peek().is_some_and(|next| next.starts_with(',')) {
+			break;
+		}
+	}
+	let columns: Vec<String> = list
+		.split(',')
+		.map(str::trim)
+		.filter(|column| !column.is_empty())
+		.map(str::to_string)
+		.collect();
+	if columns.is_empty() {
+		return Err(invalid(statement));
+	}
+
+	Ok((
+		table,
+		IndexDefinition {
+			name: name.to_string(),
+			columns,
+			unique: clauses.any(|token| token.eq_ignore_ascii_case("UNIQUE")),
+		},
+	))
+}
+
+fn generic<'a>(type_name: &'a str, wrapper: &str) -> Option<&'a str> {
+	type_name.strip_prefix(wrapper)?.strip_prefix('<')?.strip_suffix('>')
+}
+
+fn invalid(statement: &str) -> SchemaError {
+	SchemaError::Invalid(format!("cannot parse {:?}", statement))
+}
+
+/// Renders the `DEFINE TABLE`, `DEFINE FIELD` and `DEFINE INDEX` statements for a schema.
+/// Only these statements reach the database, so the names must have passed [`Schema::parse`].
+pub fn define_statements(schema: &Schema) -> Vec<String> {
+	let mut statements = Vec::new();
+
+	for table in &schema.tables {
+		statements.push(format!("DEFINE TABLE {} SCHEMAFULL;", table.name));
+		for field in &table.fields {
+			let kind = if field.optional {
+				format!("option<{}>", field.kind)
+			} else {
+				field.kind.to_string()
+			};
+			statements.push(format!(
+				"DEFINE FIELD {} ON TABLE {} TYPE {};",
+				field.name, table.name, kind
+			));
+		}
+		for index in &table.indexes {
+			statements.push(format!(
+				"DEFINE INDEX {} ON TABLE {} COLUMNS {}{};",
+				index.name,
+				table.name,
+				index.columns.join(", "),
+				if index.unique {
+					" UNIQUE"
+				} else {
+					""
+				}
+			));
+		}
+	}
+
+	statements
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::schema::SchemaFormat;
+
+	#[test]
+	fn define_statements_round_trip() {
+		let definition = "
+			DEFINE TABLE order SCHEMAFULL;
+			DEFINE FIELD id ON TABLE order TYPE int;
+			DEFINE FIELD note ON order TYPE option<string> ASSERT $value != NONE;
+			DEFINE FIELD lines ON TABLE order TYPE array<record<line>>;
+			DEFINE INDEX order_id ON TABLE order COLUMNS id UNIQUE;
+		";
+
+		let schema = Schema::parse(SchemaFormat::SurrealQL, definition).unwrap();
+
+		assert_eq!(
+			define_statements(&schema),
+			vec![
+				"DEFINE TABLE order SCHEMAFULL;",
+				"DEFINE FIELD id ON TABLE order TYPE int;",
+				"DEFINE FIELD note ON TABLE order TYPE option<string>;",
+				"DEFINE FIELD lines ON TABLE order TYPE array<record<line>>;",
+				"DEFINE INDEX order_id ON TABLE order COLUMNS id UNIQUE;",
+			]
+		);
+	}
+
+	#[test]
+	fn field_on_undefined_table_is_rejected() {
+		let definition = "DEFINE FIELD id ON TABLE order TYPE int;";
+
+		assert!(matches!(parse(definition), Err(SchemaError::Invalid(_))));
+	}
+
+	#[test]
+	fn other_statements_are_rejected() {
+		let definition = "DEFINE TABLE order; REMOVE DATABASE shop; DELETE order;";
+
+		assert!(matches!(
+			parse(definition),
+			Err(SchemaError::Invalid(message)) if message.contains("REMOVE DATABASE shop")
+		));
+	}
+
+	#[test]
+	fn names_that_are_not_identifiers_are_rejected() {
+		let definitions = [
+			"DEFINE TABLE `order`;",
+			"DEFINE TABLE order; DEFINE FIELD id-1 ON order TYPE int;",
+			"DEFINE TABLE order; DEFINE FIELD line ON order TYPE record<line|item>;",
+			"DEFINE TABLE order; DEFINE INDEX by_id ON order COLUMNS id,(SELECT);",
+		];
+
+		for definition in definitions {
+			assert!(
+				matches!(
+					Schema::parse(SchemaFormat::SurrealQL, definition),
+					Err(SchemaError::Invalid(_))
+				),
+				"{} is accepted",
+				definition
+			);
+		}
+	}
+}
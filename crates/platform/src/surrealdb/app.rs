@@ -1,20 +1,32 @@
 use k8s_openapi::api::{
 	apps::v1::StatefulSet,
 	core::v1::{PersistentVolumeClaim, Service, ServiceAccount},
+	networking::v1::NetworkPolicy,
+	policy::v1::PodDisruptionBudget,
 };
 
-use kube::{
-	api::{DeleteParams, ListParams, PostParams},
-	Api, Client, Error, ResourceExt,
-};
+use kube::{api::ListParams, Api, Client, Error, Resource, ResourceExt};
+use serde::de::DeserializeOwned;
+use std::fmt::Debug;
 
 use super::{
-	config::SurrealDBConfig, service::SurrealDBService, service_account::SurrealDBServiceAccount,
-	statefulset::SurrealDBStatefulSet,
+	config::SurrealDBConfig, network_policy::SurrealDBNetworkPolicy,
+	pod_disruption_budget::SurrealDBPodDisruptionBudget, service::SurrealDBService,
+	service_account::SurrealDBServiceAccount, statefulset::SurrealDBStatefulSet,
+};
+
+use crate::core::{
+	diff::ManifestDiff,
+	plan::{Operation, Plan},
 };
 
+pub const FIELD_MANAGER: &str = "dappmesh";
+
 pub struct SurrealDBApp {
 	client: Client,
+	plan: Plan,
+	pub network_policy: SurrealDBNetworkPolicy,
+	pub pod_disruption_budget: SurrealDBPodDisruptionBudget,
 	pub service: SurrealDBService,
 	pub service_account: SurrealDBServiceAccount,
 	pub statefulset: SurrealDBStatefulSet,
@@ -22,59 +34,114 @@ pub struct SurrealDBApp {
 
 impl SurrealDBApp {
 	pub fn new(name: String, namespace: String, client: Client) -> Self {
-		let config = SurrealDBConfig::new(format!("{}-db", name), namespace);
+		Self::with_config(SurrealDBConfig::new(Self::db_name(&name), namespace), client)
+	}
 
+	pub fn with_config(config: SurrealDBConfig, client: Client) -> Self {
 		Self {
 			client,
+			plan: Plan::default(),
+			network_policy: SurrealDBNetworkPolicy::new(config.clone()),
+			pod_disruption_budget: SurrealDBPodDisruptionBudget::new(config.clone()),
 			service: SurrealDBService::new(config.clone()),
 			service_account: SurrealDBServiceAccount::new(config.clone()),
 			statefulset: SurrealDBStatefulSet::new(config.clone()),
 		}
 	}
 
-	pub async fn create(&self) -> Result<(), Error> {
-		self.create_service_account().await?;
-		self.create_statefulset().await?;
-		self.create_service().await?;
-		Ok(())
+	/// Validates every change with the API server without persisting it.
+	pub fn dry_run(mut self, dry_run: bool) -> Self {
+		self.plan = Plan::new(dry_run);
+		self
 	}
 
-	async fn create_service(&self) -> Result<(), Error> {
-		let api: Api<Service> = Api::namespaced(self.client.clone(), &self.service.namespace);
-		let params = ListParams::default().labels(&self.service.get_labels());
+	pub fn plan(&self) -> &Plan {
+		&self.plan
+	}
+
+	pub fn db_name(name: &str) -> String {
+		format!("{}-db", name)
+	}
 
-		if api.list(&params).await?.items.is_empty() {
-			api.create(&PostParams::default(), &self.service.manifest()).await?;
+	pub async fn create(&self) -> Result<(), Error> {
+		self.apply_workload().await?;
+		if self.pod_disruption_budget.enabled() {
+			self.apply_pod_disruption_budget().await?;
 		}
+		self.apply_network_policy().await?;
+		Ok(())
+	}
 
+	/// Brings the resources derived from mutable configuration in line with the desired state.
+	pub async fn update(&self) -> Result<(), Error> {
+		self.apply_workload().await?;
+		self.sync_pod_disruption_budget().await?;
+		self.apply_network_policy().await?;
 		Ok(())
 	}
 
-	async fn create_service_account(&self) -> Result<(), Error> {
-		let api: Api<ServiceAccount> =
+	/// Applies the ServiceAccount, StatefulSet and Service wherever they differ from their
+	/// manifests.
+	async fn apply_workload(&self) -> Result<(), Error> {
+		let service_accounts: Api<ServiceAccount> =
 			Api::namespaced(self.client.clone(), &self.service_account.namespace);
-		let params = ListParams::default().labels(&self.service_account.get_labels());
+		self.plan.apply(&service_accounts, &self.service_account.manifest()).await?;
 
-		if api.list(&params).await?.items.is_empty() {
-			api.create(&PostParams::default(), &self.service_account.manifest()).await?;
-		}
+		let statefulsets: Api<StatefulSet> =
+			Api::namespaced(self.client.clone(), &self.statefulset.namespace);
+		self.plan.apply(&statefulsets, &self.statefulset.manifest()).await?;
 
-		Ok(())
+		let services: Api<Service> = Api::namespaced(self.client.clone(), &self.service.namespace);
+		self.plan.apply(&services, &self.service.manifest()).await
 	}
 
-	pub async fn create_statefulset(&self) -> Result<(), Error> {
-		let api: Api<StatefulSet> =
-			Api::namespaced(self.client.clone(), &self.statefulset.namespace);
-		let lp = ListParams::default().labels(&self.statefulset.get_labels());
+	/// Keeps the budget while more than one replica runs and removes it after scaling down.
+	async fn sync_pod_disruption_budget(&self) -> Result<(), Error> {
+		if self.pod_disruption_budget.enabled() {
+			self.apply_pod_disruption_budget().await
+		} else {
+			self.delete_pod_disruption_budget().await
+		}
+	}
 
-		if api.list(&lp).await?.items.is_empty() {
-			api.create(&PostParams::default(), &self.statefulset.manifest()).await?;
+	async fn apply_pod_disruption_budget(&self) -> Result<(), Error> {
+		let api: Api<PodDisruptionBudget> =
+			Api::namespaced(self.client.clone(), &self.pod_disruption_budget.namespace);
+
+		self.plan.apply(&api, &self.pod_disruption_budget.manifest()).await
+	}
+
+	async fn apply_network_policy(&self) -> Result<(), Error> {
+		if !self.network_policy.enabled() {
+			return Ok(());
 		}
 
+		let api: Api<NetworkPolicy> =
+			Api::namespaced(self.client.clone(), &self.network_policy.namespace);
+
+		self.plan.apply(&api, &self.network_policy.manifest()).await
+	}
+
+	async fn delete_object<K>(&self, api: &Api<K>, object: &K) -> Result<(), Error>
+	where
+		K: Resource<DynamicType = ()> + Clone + DeserializeOwned + Debug,
+	{
+		api.delete(&object.name_any(), &self.plan.delete_params()).await?;
+		self.plan.record(
+			Operation::Delete,
+			&K::kind(&()),
+			&object.name_any(),
+			ManifestDiff::default(),
+		);
+
 		Ok(())
 	}
 
 	pub async fn delete(&self) -> Result<(), Error> {
+		self.delete_network_policy().await?;
+		if self.pod_disruption_budget.enabled() {
+			self.delete_pod_disruption_budget().await?;
+		}
 		self.delete_service().await?;
 		self.delete_statefulset().await?;
 		self.delete_pvc().await?;
@@ -82,13 +149,41 @@ impl SurrealDBApp {
 		Ok(())
 	}
 
+	pub async fn delete_network_policy(&self) -> Result<(), Error> {
+		if !self.network_policy.enabled() {
+			return Ok(());
+		}
+
+		let api: Api<NetworkPolicy> =
+			Api::namespaced(self.client.clone(), &self.network_policy.namespace);
+		let params = ListParams::default().labels(&self.network_policy.get_labels());
+
+		for policy in api.list(&params).await? {
+			self.delete_object(&api, &policy).await?;
+		}
+
+		Ok(())
+	}
+
 	pub async fn delete_pvc(&self) -> Result<(), Error> {
 		let api: Api<PersistentVolumeClaim> =
 			Api::namespaced(self.client.clone(), &self.statefulset.namespace);
 		let lp = ListParams::default().labels(&self.statefulset.get_labels());
 
 		for pvc in api.list(&lp).await? {
-			api.delete(&pvc.name_any(), &DeleteParams::default()).await?;
+			self.delete_object(&api, &pvc).await?;
+		}
+
+		Ok(())
+	}
+
+	pub async fn delete_pod_disruption_budget(&self) -> Result<(), Error> {
+		let api: Api<PodDisruptionBudget> =
+			Api::namespaced(self.client.clone(), &self.pod_disruption_budget.namespace);
+		let params = ListParams::default().labels(&self.pod_disruption_budget.get_labels());
+
+		for budget in api.list(&params).await? {
+			self.delete_object(&api, &budget).await?;
 		}
 
 		Ok(())
@@ -99,7 +194,7 @@ impl SurrealDBApp {
 		let params = ListParams::default().labels(&self.service.get_labels());
 
 		for service in api.list(&params).await? {
-			api.delete(&service.name_any(), &DeleteParams::default()).await?;
+			self.delete_object(&api, &service).await?;
 		}
 		Ok(())
 	}
@@ -111,7 +206,7 @@ impl SurrealDBApp {
 		let params = ListParams::default().labels(&self.service_account.get_labels());
 
 		for sa in api.list(&params).await? {
-			api.delete(&sa.name_any(), &DeleteParams::default()).await?;
+			self.delete_object(&api, &sa).await?;
 		}
 
 		Ok(())
@@ -124,7 +219,7 @@ impl SurrealDBApp {
 		let lp = ListParams::default().labels(&self.statefulset.get_labels());
 
 		for ss in api.list(&lp).await? {
-			api.delete(&ss.name_any(), &DeleteParams::default()).await?;
+			self.delete_object(&api, &ss).await?;
 		}
 
 		Ok(())
@@ -134,6 +229,7 @@ impl SurrealDBApp {
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use crate::{core::network_policy::pod_peer, surrealdb::spec::SurrealDBSpec};
 	use hyper::{Request, Response, StatusCode};
 	use k8s_openapi::api::{apps::v1::StatefulSet, core::v1::PersistentVolumeClaim};
 	use kube::{
@@ -141,7 +237,8 @@ mod tests {
 		client::Body,
 		Error,
 	};
-	use serde_json::to_vec;
+	use serde::Serialize;
+	use serde_json::{json, to_vec};
 
 	const TEST_NAME: &str = "test-name";
 	const TEST_NAME_PREFIXED: &str = "test-name-db";
@@ -163,32 +260,87 @@ mod tests {
 		Response::builder().status(StatusCode::OK).body(Body::from(data)).unwrap()
 	}
 
+	fn not_found_response() -> Response<Body> {
+		let status = json!({
+			"kind": "Status",
+			"apiVersion": "v1",
+			"status": "Failure",
+			"message": "not found",
+			"reason": "NotFound",
+			"code": 404
+		});
+
+		Response::builder()
+			.status(StatusCode::NOT_FOUND)
+			.body(Body::from(to_vec(&status).unwrap()))
+			.unwrap()
+	}
+
+	fn dry_run_param(dry_run: bool) -> &'static str {
+		if dry_run {
+			"&dryRun=All"
+		} else {
+			""
+		}
+	}
+
+	/// The state of a live object, which is applied unless it is current.
+	pub enum Live<K> {
+		Missing,
+		Outdated(K),
+		Current(K),
+	}
+
+	type Workload = (Live<ServiceAccount>, Live<StatefulSet>, Live<Service>);
+
 	pub enum Scenario {
-		CreateResources(bool),
+		CreateResources,
+		CreateResourcesWithNetworkPolicy,
 		DeleteResources(bool),
+		UpdateResources(Box<(Workload, NetworkPolicy)>),
+		UpdateReplicatedResources(Box<Workload>, bool),
+	}
+
+	fn path(api: &str, resource: &str) -> String {
+		format!("{}/namespaces/{}/{}/{}", api, TEST_NAMESPACE, resource, TEST_NAME_PREFIXED)
 	}
 
 	impl MockedKubeApiServer {
 		pub fn run(self, scenario: Scenario) -> tokio::task::JoinHandle<MockedKubeApiServer> {
 			tokio::spawn(async move {
 				match scenario {
-					Scenario::CreateResources(creating_resources) => self
-						.handle_list_service_accounts(creating_resources)
+					Scenario::CreateResources => self
+						.handle_workload((Live::Missing, Live::Missing, Live::Missing), false)
+						.await
+						.unwrap(),
+					Scenario::CreateResourcesWithNetworkPolicy => self
+						.handle_workload((Live::Missing, Live::Missing, Live::Missing), false)
 						.await
 						.unwrap()
-						.handle_create_service_account()
+						.handle_get_network_policy(None)
 						.await
 						.unwrap()
-						.handle_list_statefulset(creating_resources)
+						.handle_apply_network_policy()
+						.await
+						.unwrap(),
+					Scenario::UpdateResources(live) => self
+						.handle_workload(live.0, false)
 						.await
 						.unwrap()
-						.handle_create_statefulset()
+						.handle_list_pod_disruption_budget()
 						.await
 						.unwrap()
-						.handle_list_service(creating_resources)
+						.handle_get_network_policy(Some(live.1))
+						.await
+						.unwrap(),
+					Scenario::UpdateReplicatedResources(live, dry_run) => self
+						.handle_workload(*live, dry_run)
+						.await
+						.unwrap()
+						.handle_get_pod_disruption_budget()
 						.await
 						.unwrap()
-						.handle_create_service()
+						.handle_apply_pod_disruption_budget(dry_run)
 						.await
 						.unwrap(),
 					Scenario::DeleteResources(creating_resources) => self
@@ -219,6 +371,71 @@ mod tests {
 				}
 			})
 		}
+		/// Expects the ServiceAccount, StatefulSet and Service to be fetched, and applied unless
+		/// they are current.
+		pub async fn handle_workload(self, live: Workload, dry_run: bool) -> Result<Self, Error> {
+			let (service_account, statefulset, service) = live;
+			self.handle_object(&path("/api/v1", "serviceaccounts"), service_account, dry_run)
+				.await?
+				.handle_object(&path("/apis/apps/v1", "statefulsets"), statefulset, dry_run)
+				.await?
+				.handle_object(&path("/api/v1", "services"), service, dry_run)
+				.await
+		}
+
+		pub async fn handle_object<K: Default + Serialize>(
+			self,
+			path: &str,
+			live: Live<K>,
+			dry_run: bool,
+		) -> Result<Self, Error> {
+			match live {
+				Live::Missing => {
+					self.handle_get::<K>(path, None).await?.handle_apply::<K>(path, dry_run).await
+				}
+				Live::Outdated(live) => {
+					self.handle_get(path, Some(live)).await?.handle_apply::<K>(path, dry_run).await
+				}
+				Live::Current(live) => self.handle_get(path, Some(live)).await,
+			}
+		}
+
+		pub async fn handle_get<K: Serialize>(
+			mut self,
+			path: &str,
+			live: Option<K>,
+		) -> Result<Self, Error> {
+			let (request, send) = self.0.next_request().await.expect("Service not called");
+			assert_eq!(request.method(), &hyper::Method::GET);
+			assert_eq!(request.uri().to_string(), path);
+
+			let mock_response = match live {
+				Some(live) => mock_response(to_vec(&live).unwrap()),
+				None => not_found_response(),
+			};
+			send.send_response(mock_response);
+
+			Ok(self)
+		}
+
+		pub async fn handle_apply<K: Default + Serialize>(
+			mut self,
+			path: &str,
+			dry_run: bool,
+		) -> Result<Self, Error> {
+			let (request, send) = self.0.next_request().await.expect("Service not called");
+			assert_eq!(request.method(), &hyper::Method::PATCH);
+			assert_eq!(
+				request.uri().to_string(),
+				format!("{}?{}&force=true&fieldManager=dappmesh", path, dry_run_param(dry_run))
+			);
+
+			let response = to_vec(&K::default()).unwrap();
+			send.send_response(mock_response(response));
+
+			Ok(self)
+		}
+
 		pub async fn handle_list_service_accounts(
 			mut self,
 			creating_resources: bool,
@@ -253,21 +470,6 @@ mod tests {
 			Ok(self)
 		}
 
-		pub async fn handle_create_service_account(mut self) -> Result<Self, Error> {
-			let (request, send) = self.0.next_request().await.expect("Service not called");
-			assert_eq!(request.method(), &hyper::Method::POST);
-			assert_eq!(
-				request.uri().to_string(),
-				format!("/api/v1/namespaces/{}/serviceaccounts?", TEST_NAMESPACE)
-			);
-
-			let response = to_vec(&ServiceAccount::default()).unwrap();
-			let mock_response = mock_response(response);
-			send.send_response(mock_response);
-
-			Ok(self)
-		}
-
 		pub async fn handle_list_statefulset(
 			mut self,
 			creating_resources: bool,
@@ -302,21 +504,6 @@ mod tests {
 			Ok(self)
 		}
 
-		pub async fn handle_create_statefulset(mut self) -> Result<Self, Error> {
-			let (request, send) = self.0.next_request().await.expect("Service not called");
-			assert_eq!(request.method(), &hyper::Method::POST);
-			assert_eq!(
-				request.uri().to_string(),
-				format!("/apis/apps/v1/namespaces/{}/statefulsets?", TEST_NAMESPACE)
-			);
-
-			let response = to_vec(&StatefulSet::default()).unwrap();
-			let mock_response = mock_response(response);
-			send.send_response(mock_response);
-
-			Ok(self)
-		}
-
 		pub async fn handle_list_service(
 			mut self,
 			creating_resources: bool,
@@ -351,21 +538,6 @@ mod tests {
 			Ok(self)
 		}
 
-		pub async fn handle_create_service(mut self) -> Result<Self, Error> {
-			let (request, send) = self.0.next_request().await.expect("Service not called");
-			assert_eq!(request.method(), &hyper::Method::POST);
-			assert_eq!(
-				request.uri().to_string(),
-				format!("/api/v1/namespaces/{}/services?", TEST_NAMESPACE)
-			);
-
-			let response = to_vec(&Service::default()).unwrap();
-			let mock_response = mock_response(response);
-			send.send_response(mock_response);
-
-			Ok(self)
-		}
-
 		pub async fn handle_delete_service(mut self) -> Result<Self, Error> {
 			let (request, send) = self.0.next_request().await.expect("Service not called");
 			assert_eq!(request.method(), &hyper::Method::DELETE);
@@ -442,6 +614,112 @@ mod tests {
 			Ok(self)
 		}
 
+		pub async fn handle_list_pod_disruption_budget(mut self) -> Result<Self, Error> {
+			let (request, send) = self.0.next_request().await.expect("Service not called");
+			assert_eq!(request.method(), &hyper::Method::GET);
+			assert_eq!(
+				request.uri().to_string(),
+				format!(
+					"/apis/policy/v1/namespaces/{}/poddisruptionbudgets?&labelSelector=part-of%3D{}",
+					TEST_NAMESPACE, TEST_NAME_PREFIXED
+				)
+			);
+
+			let list: ObjectList<PodDisruptionBudget> = ObjectList {
+				types: TypeMeta::default(),
+				metadata: ListMeta::default(),
+				items: vec![],
+			};
+
+			let response = to_vec(&list).unwrap();
+			let mock_response = mock_response(response);
+			send.send_response(mock_response);
+
+			Ok(self)
+		}
+
+		pub async fn handle_get_pod_disruption_budget(mut self) -> Result<Self, Error> {
+			let (request, send) = self.0.next_request().await.expect("Service not called");
+			assert_eq!(request.method(), &hyper::Method::GET);
+			assert_eq!(
+				request.uri().to_string(),
+				format!(
+					"/apis/policy/v1/namespaces/{}/poddisruptionbudgets/{}",
+					TEST_NAMESPACE, TEST_NAME_PREFIXED
+				)
+			);
+
+			let response = to_vec(&PodDisruptionBudget::default()).unwrap();
+			let mock_response = mock_response(response);
+			send.send_response(mock_response);
+
+			Ok(self)
+		}
+
+		pub async fn handle_apply_pod_disruption_budget(
+			mut self,
+			dry_run: bool,
+		) -> Result<Self, Error> {
+			let (request, send) = self.0.next_request().await.expect("Service not called");
+			assert_eq!(request.method(), &hyper::Method::PATCH);
+			assert_eq!(
+				request.uri().to_string(),
+				format!(
+					"/apis/policy/v1/namespaces/{}/poddisruptionbudgets/{}?{}&force=true&fieldManager=dappmesh",
+					TEST_NAMESPACE,
+					TEST_NAME_PREFIXED,
+					dry_run_param(dry_run)
+				)
+			);
+
+			let response = to_vec(&PodDisruptionBudget::default()).unwrap();
+			let mock_response = mock_response(response);
+			send.send_response(mock_response);
+
+			Ok(self)
+		}
+
+		pub async fn handle_get_network_policy(
+			mut self,
+			live: Option<NetworkPolicy>,
+		) -> Result<Self, Error> {
+			let (request, send) = self.0.next_request().await.expect("Service not called");
+			assert_eq!(request.method(), &hyper::Method::GET);
+			assert_eq!(
+				request.uri().to_string(),
+				format!(
+					"/apis/networking.k8s.io/v1/namespaces/{}/networkpolicies/{}",
+					TEST_NAMESPACE, TEST_NAME_PREFIXED
+				)
+			);
+
+			let mock_response = match live {
+				Some(live) => mock_response(to_vec(&live).unwrap()),
+				None => not_found_response(),
+			};
+			send.send_response(mock_response);
+
+			Ok(self)
+		}
+
+		pub async fn handle_apply_network_policy(mut self) -> Result<Self, Error> {
+			let (request, send) = self.0.next_request().await.expect("Service not called");
+			assert_eq!(request.method(), &hyper::Method::PATCH);
+			assert_eq!(
+				request.uri().to_string(),
+				format!(
+					"/apis/networking.k8s.io/v1/namespaces/{}/networkpolicies/{}?&force=true&fieldManager=dappmesh",
+					TEST_NAMESPACE, TEST_NAME_PREFIXED
+				)
+			);
+
+			let response = to_vec(&NetworkPolicy::default()).unwrap();
+			let mock_response = mock_response(response);
+			send.send_response(mock_response);
+
+			Ok(self)
+		}
+
 		pub async fn handle_delete_service_account(mut self) -> Result<Self, Error> {
 			let (request, send) = self.0.next_request().await.expect("Service not called");
 			assert_eq!(request.method(), &hyper::Method::DELETE);
@@ -458,6 +736,15 @@ mod tests {
 		}
 	}
 
+	/// The ServiceAccount and Service of `app` as current, next to the live `statefulset`.
+	fn workload(app: &SurrealDBApp, statefulset: Live<StatefulSet>) -> Workload {
+		(
+			Live::Current(app.service_account.manifest()),
+			statefulset,
+			Live::Current(app.service.manifest()),
+		)
+	}
+
 	pub async fn timeout_after_1s(handle: tokio::task::JoinHandle<MockedKubeApiServer>) {
 		tokio::time::timeout(std::time::Duration::from_secs(1), handle)
 			.await
@@ -471,13 +758,156 @@ mod tests {
 		let surrdb_app =
 			SurrealDBApp::new(TEST_NAME.to_string(), TEST_NAMESPACE.to_string(), client);
 
-		let mockserver = fakeserver.run(Scenario::CreateResources(true));
+		let mockserver = fakeserver.run(Scenario::CreateResources);
+		let result = surrdb_app.create().await;
+		timeout_after_1s(mockserver).await;
+
+		assert!(matches!(result, Ok(())));
+	}
+
+	#[tokio::test]
+	async fn create_resources_with_network_policy_and_return_ok() {
+		let (client, fakeserver) = mock_client();
+		let config =
+			SurrealDBConfig::new(TEST_NAME_PREFIXED.to_string(), TEST_NAMESPACE.to_string())
+				.with_network_policy(vec![]);
+		let surrdb_app = SurrealDBApp::with_config(config, client);
+
+		let mockserver = fakeserver.run(Scenario::CreateResourcesWithNetworkPolicy);
 		let result = surrdb_app.create().await;
 		timeout_after_1s(mockserver).await;
 
 		assert!(matches!(result, Ok(())));
 	}
 
+	#[tokio::test]
+	async fn update_skips_unchanged_resources_and_return_ok() {
+		let (client, fakeserver) = mock_client();
+		let config =
+			SurrealDBConfig::new(TEST_NAME_PREFIXED.to_string(), TEST_NAMESPACE.to_string())
+				.with_network_policy(vec![]);
+		let surrdb_app = SurrealDBApp::with_config(config, client);
+		let statefulset = Live::Current(surrdb_app.statefulset.manifest());
+		let network_policy = surrdb_app.network_policy.manifest();
+
+		let mockserver = fakeserver.run(Scenario::UpdateResources(Box::new((
+			workload(&surrdb_app, statefulset),
+			network_policy,
+		))));
+		let result = surrdb_app.update().await;
+		timeout_after_1s(mockserver).await;
+
+		assert!(matches!(result, Ok(())));
+	}
+
+	#[tokio::test]
+	async fn update_applies_template_changes_to_the_live_statefulset() {
+		let (client, fakeserver) = mock_client();
+		let config =
+			SurrealDBConfig::new(TEST_NAME_PREFIXED.to_string(), TEST_NAMESPACE.to_string())
+				.with_network_policy(vec![]);
+		let surrdb_app = SurrealDBApp::with_config(config, client);
+		let mut live = surrdb_app.statefulset.manifest();
+		let template = live.spec.as_mut().unwrap().template.spec.as_mut().unwrap();
+		template.containers[0].image = Some("surrealdb/surrealdb:v1.0.0".to_string());
+		let network_policy = surrdb_app.network_policy.manifest();
+
+		let mockserver = fakeserver.run(Scenario::UpdateResources(Box::new((
+			workload(&surrdb_app, Live::Outdated(live)),
+			network_policy,
+		))));
+		let result = surrdb_app.update().await;
+		timeout_after_1s(mockserver).await;
+
+		let changes = surrdb_app.plan().changes();
+		assert!(matches!(result, Ok(())));
+		assert_eq!(changes.len(), 1);
+		assert_eq!(changes[0].kind, "StatefulSet");
+		assert_eq!(changes[0].diff.changes[0].path, "spec.template.spec.containers[0].image");
+	}
+
+	#[tokio::test]
+	async fn update_scales_and_applies_disruption_budget_and_return_ok() {
+		let (client, fakeserver) = mock_client();
+		let spec = SurrealDBSpec {
+			replicas: Some(3),
+			..SurrealDBSpec::default()
+		};
+		let config =
+			SurrealDBConfig::new(TEST_NAME_PREFIXED.to_string(), TEST_NAMESPACE.to_string())
+				.with_spec(Some(&spec));
+		let surrdb_app = SurrealDBApp::with_config(config, client);
+		let statefulset = Live::Outdated(StatefulSet::default());
+
+		let mockserver = fakeserver.run(Scenario::UpdateReplicatedResources(
+			Box::new(workload(&surrdb_app, statefulset)),
+			false,
+		));
+		let result = surrdb_app.update().await;
+		timeout_after_1s(mockserver).await;
+
+		assert!(matches!(result, Ok(())));
+	}
+
+	#[tokio::test]
+	async fn dry_run_update_submits_dry_run_requests_and_records_plan() {
+		let (client, fakeserver) = mock_client();
+		let spec = SurrealDBSpec {
+			replicas: Some(3),
+			..SurrealDBSpec::default()
+		};
+		let config =
+			SurrealDBConfig::new(TEST_NAME_PREFIXED.to_string(), TEST_NAMESPACE.to_string())
+				.with_spec(Some(&spec));
+		let surrdb_app = SurrealDBApp::with_config(config, client).dry_run(true);
+		let statefulset = Live::Outdated(StatefulSet::default());
+
+		let mockserver = fakeserver.run(Scenario::UpdateReplicatedResources(
+			Box::new(workload(&surrdb_app, statefulset)),
+			true,
+		));
+		let result = surrdb_app.update().await;
+		timeout_after_1s(mockserver).await;
+
+		let changes = surrdb_app.plan().changes();
+		assert!(matches!(result, Ok(())));
+		assert_eq!(changes.len(), 2);
+		assert!(changes.iter().all(|change| change.operation == Operation::Patch));
+		assert_eq!(changes[0].kind, "StatefulSet");
+		assert_eq!(changes[1].kind, "PodDisruptionBudget");
+	}
+
+	#[test]
+	fn disruption_budget_is_only_enabled_for_multiple_replicas() {
+		let config =
+			SurrealDBConfig::new(TEST_NAME_PREFIXED.to_string(), TEST_NAMESPACE.to_string());
+		let spec = SurrealDBSpec {
+			replicas: Some(2),
+			..SurrealDBSpec::default()
+		};
+
+		assert!(!SurrealDBPodDisruptionBudget::new(config.clone()).enabled());
+		assert!(SurrealDBPodDisruptionBudget::new(config.with_spec(Some(&spec))).enabled());
+	}
+
+	#[test]
+	fn network_policy_only_admits_own_pods_and_configured_peers() {
+		let consumer = pod_peer(vec![("dappmesh.io/product", "consumer")], Some("other"));
+		let config =
+			SurrealDBConfig::new(TEST_NAME_PREFIXED.to_string(), TEST_NAMESPACE.to_string())
+				.with_network_policy(vec![consumer.clone()]);
+
+		let spec = SurrealDBNetworkPolicy::new(config).manifest().spec.unwrap();
+		let ingress = spec.ingress.unwrap();
+
+		assert_eq!(spec.policy_types, Some(vec!["Ingress".to_string()]));
+		assert_eq!(ingress.len(), 1);
+		assert_eq!(
+			ingress[0].from,
+			Some(vec![pod_peer(vec![("part-of", TEST_NAME_PREFIXED)], None), consumer])
+		);
+	}
+
 	#[tokio::test]
 	async fn delete_resources_in_order_and_return_ok_in() {
 		let (client, fakeserver) = mock_client();
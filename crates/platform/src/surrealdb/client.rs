@@ -0,0 +1,196 @@
+use std::time::Duration;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use http_body_util::{BodyExt, Full};
+use hyper::{body::Bytes, header, Method, Request};
+use hyper_util::{
+	client::legacy::{connect::HttpConnector, Client},
+	rt::TokioExecutor,
+};
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::config::SurrealDBConfig;
+
+/// How long a request may take before it is given up, so that an unresponsive instance does not
+/// stall the reconciliation waiting on it.
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Runs SurrealQL against a SurrealDB instance through its HTTP `/sql` endpoint.
+#[derive(Clone)]
+pub struct SurrealDBClient {
+	url: String,
+	namespace: String,
+	database: String,
+	authorization: String,
+	http: Client<HttpConnector, Full<Bytes>>,
+	timeout: Duration,
+}
+
+#[derive(Deserialize)]
+struct StatementResult {
+	status: String,
+	#[serde(default)]
+	result: Value,
+}
+
+impl SurrealDBClient {
+	pub fn new(url: &str, namespace: &str, database: &str, user: &str, password: &str) -> Self {
+		Self {
+			url: url.trim_end_matches('/').to_string(),
+			namespace: namespace.to_string(),
+			database: database.to_string(),
+			authorization: format!("Basic {}", STANDARD.encode(format!("{}:{}", user, password))),
+			http: Client::builder(TokioExecutor::new()).build_http(),
+			timeout: REQUEST_TIMEOUT,
+		}
+	}
+
+	pub fn timeout(mut self, timeout: Duration) -> Self {
+		self.timeout = timeout;
+		self
+	}
+
+	/// Connects to the instance described by `config` through its in-cluster service and
+	/// uses the Kubernetes namespace as SurrealDB namespace.
+	pub fn from_config(config: &SurrealDBConfig, database: &str) -> Self {
+		let url =
+			format!("http://{}.{}.svc:{}", config.name, config.namespace, config.service.port);
+		let container = &config.statefulset.container;
+
+		Self::new(&url, &config.namespace, database, &container.user, &container.password)
+	}
+
+	pub fn url(&self) -> &str {
+		&self.url
+	}
+
+	/// Runs the statements in one request and returns the result of each statement.
+	pub async fn query(&self, statements: &str) -> Result<Vec<Value>, SurrealDBError> {
+		let request = Request::builder()
+			.method(Method::POST)
+			.uri(format!("{}/sql", self.url))
+			.header(header::ACCEPT, "application/json")
+			.header(header::AUTHORIZATION, &self.authorization)
+			.header("NS", &self.namespace)
+			.header("DB", &self.database)
+			.body(Full::new(Bytes::from(statements.to_string())))
+			.map_err(|error| SurrealDBError::Http(error.to_string()))?;
+
+		let exchange = async {
+			let response = self.http.request(request).await.map_err(|error| error.to_string())?;
+			let status = response.status();
+			let body = response.into_body().collect().await.map_err(|error| error.to_string())?;
+			Ok((status, body.to_bytes()))
+		};
+		let (status, body) = match tokio::time::timeout(self.timeout, exchange).await {
+			Ok(result) => result.map_err(SurrealDBError::Http)?,
+			Err(_) => {
+				let message = format!("no response from {} within {:?}", self.url, self.timeout);
+				return Err(SurrealDBError::Http(message));
+			}
+		};
+
+		if !status.is_success() {
+			return Err(SurrealDBError::Http(format!(
+				"{}: {}",
+				status,
+				String::from_utf8_lossy(&body)
+			)));
+		}
+
+		let results: Vec<StatementResult> = serde_json::from_slice(&body)?;
+		results
+			.into_iter()
+			.map(|result| match result.status.as_str() {
+				"OK" => Ok(result.result),
+				_ => Err(SurrealDBError::Query(result.result.to_string())),
+			})
+			.collect()
+	}
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SurrealDBError {
+	#[error("SurrealDB request failed: {0}")]
+	Http(String),
+	#[error("SurrealDB rejected a statement: {0}")]
+	Query(String),
+	#[error("Unexpected SurrealDB response: {0}")]
+	Response(#[from] serde_json::Error),
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use tokio::{
+		io::{AsyncReadExt, AsyncWriteExt},
+		net::TcpListener,
+		task::JoinHandle,
+	};
+
+	/// Answers a single request with `body` and returns the raw request.
+	async fn serve_once(body: &'static str) -> (String, JoinHandle<String>) {
+		let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+		let url = format!("http://{}", listener.local_addr().unwrap());
+
+		let handle = tokio::spawn(async move {
+			let (mut stream, _) = listener.accept().await.unwrap();
+			let mut request = vec![0; 4096];
+			let length = stream.read(&mut request).await.unwrap();
+			let response = format!(
+				"HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{}",
+				body.len(),
+				body
+			);
+			stream.write_all(response.as_bytes()).await.unwrap();
+			String::from_utf8_lossy(&request[..length]).to_string()
+		});
+
+		(url, handle)
+	}
+
+	#[tokio::test]
+	async fn query_posts_statements_to_the_sql_endpoint() {
+		let (url, server) = serve_once(r#"[{"time":"1ms","status":"OK","result":[]}]"#).await;
+		let client = SurrealDBClient::new(&url, "shop", "orders-db", "root", "root");
+
+		let results = client.query("DEFINE TABLE order SCHEMAFULL;").await.unwrap();
+		let request = server.await.unwrap().to_lowercase();
+
+		assert_eq!(results, vec![Value::Array(vec![])]);
+		assert!(request.starts_with("post /sql http/1.1"));
+		assert!(request.contains("ns: shop"));
+		assert!(request.contains("db: orders-db"));
+		assert!(request.contains("authorization: basic cm9vddpyb290"));
+		assert!(request.ends_with("define table order schemafull;"));
+	}
+
+	#[tokio::test]
+	async fn query_gives_up_on_unresponsive_instances() {
+		let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+		let url = format!("http://{}", listener.local_addr().unwrap());
+		let client = SurrealDBClient::new(&url, "shop", "orders-db", "root", "root")
+			.timeout(Duration::from_millis(50));
+
+		let result = client.query("INFO FOR DB;").await;
+
+		assert!(
+			matches!(result, Err(SurrealDBError::Http(message)) if message.contains("no response"))
+		);
+	}
+
+	#[tokio::test]
+	async fn query_fails_when_a_statement_fails() {
+		let (url, server) =
+			serve_once(r#"[{"time":"1ms","status":"ERR","result":"Parse error"}]"#).await;
+		let client = SurrealDBClient::new(&url, "shop", "orders-db", "root", "root");
+
+		let result = client.query("DEFINE TABLE;").await;
+		server.await.unwrap();
+
+		assert!(
+			matches!(result, Err(SurrealDBError::Query(message)) if message.contains("Parse error"))
+		);
+	}
+}
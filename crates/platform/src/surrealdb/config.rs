@@ -1,4 +1,13 @@
-use k8s_openapi::apimachinery::pkg::api::resource::Quantity;
+use k8s_openapi::{
+	api::{core::v1::EnvVar, networking::v1::NetworkPolicyPeer},
+	apimachinery::pkg::{api::resource::Quantity, util::intstr::IntOrString},
+};
+
+use super::spec::SurrealDBSpec;
+use crate::core::{
+	affinity::{HOSTNAME_TOPOLOGY_KEY, ZONE_TOPOLOGY_KEY},
+	security::{SecurityContextConfig, SecurityContextOverrides},
+};
 
 #[derive(Clone)]
 pub struct SurrealDBConfig {
@@ -6,6 +15,8 @@ pub struct SurrealDBConfig {
 	pub namespace: String,
 	pub service: SurrealDBServiceConfig,
 	pub statefulset: SurrealDBStatefulSetConfig,
+	pub network_policy: Option<SurrealDBNetworkPolicyConfig>,
+	pub metrics: Option<MetricsConfig>,
 }
 
 #[derive(Clone)]
@@ -14,11 +25,25 @@ pub struct SurrealDBServiceConfig {
 	pub port: i32,
 }
 
+#[derive(Clone)]
+pub struct SurrealDBNetworkPolicyConfig {
+	pub peers: Vec<NetworkPolicyPeer>,
+}
+
 #[derive(Clone)]
 pub struct SurrealDBStatefulSetConfig {
 	pub replicas: i32,
 	pub container: ContainerConfig,
 	pub pvc: PVCConfig,
+	pub security: SecurityContextConfig,
+	pub disruption_budget: DisruptionBudgetConfig,
+	pub anti_affinity_topology_keys: Vec<String>,
+}
+
+#[derive(Clone)]
+pub struct DisruptionBudgetConfig {
+	pub min_available: Option<IntOrString>,
+	pub max_unavailable: Option<IntOrString>,
 }
 
 #[derive(Clone)]
@@ -33,10 +58,21 @@ pub struct ContainerConfig {
 	pub protocol: String,
 }
 
+/// SurrealDB only exports its metrics over OTLP, so an OpenTelemetry Collector next to it
+/// receives them and serves them to Prometheus.
+#[derive(Clone)]
+pub struct MetricsConfig {
+	pub image: String,
+	pub otlp_port: i32,
+	pub port: i32,
+	pub port_name: String,
+}
+
 #[derive(Clone)]
 pub struct PVCConfig {
 	pub access_modes: Vec<String>,
 	pub resource_quantity: Quantity,
+	pub storage_class_name: Option<String>,
 }
 
 impl SurrealDBConfig {
@@ -46,7 +82,54 @@ impl SurrealDBConfig {
 			namespace,
 			service: SurrealDBServiceConfig::default(),
 			statefulset: SurrealDBStatefulSetConfig::default(),
+			network_policy: None,
+			metrics: None,
+		}
+	}
+
+	pub fn with_metrics(mut self, enabled: bool) -> Self {
+		self.metrics = enabled.then(MetricsConfig::default);
+		self
+	}
+
+	pub fn with_network_policy(mut self, peers: Vec<NetworkPolicyPeer>) -> Self {
+		self.network_policy = Some(SurrealDBNetworkPolicyConfig {
+			peers,
+		});
+		self
+	}
+
+	pub fn with_spec(mut self, spec: Option<&SurrealDBSpec>) -> Self {
+		let Some(spec) = spec else {
+			return self;
+		};
+
+		if let Some(replicas) = spec.replicas {
+			self.statefulset.replicas = replicas;
+		}
+		if let Some(budget) = &spec.disruption_budget {
+			self.statefulset.disruption_budget = DisruptionBudgetConfig {
+				min_available: budget.min_available.clone(),
+				max_unavailable: budget.max_unavailable.clone(),
+			};
+		}
+		if let Some(anti_affinity) = &spec.anti_affinity {
+			self.statefulset.anti_affinity_topology_keys = anti_affinity.topology_keys.clone();
+		}
+		if let Some(storage) = &spec.storage {
+			if let Some(size) = &storage.size {
+				self.statefulset.pvc.resource_quantity = Quantity(size.clone());
+			}
+			self.statefulset.pvc.storage_class_name = storage.class_name.clone();
 		}
+		self
+	}
+
+	pub fn with_security_overrides(mut self, overrides: Option<&SecurityContextOverrides>) -> Self {
+		if let Some(overrides) = overrides {
+			self.statefulset.security = self.statefulset.security.with_overrides(overrides);
+		}
+		self
 	}
 }
 
@@ -65,6 +148,21 @@ impl SurrealDBStatefulSetConfig {
 			replicas: 1,
 			container: ContainerConfig::default(),
 			pvc: PVCConfig::default(),
+			security: SecurityContextConfig::default(),
+			disruption_budget: DisruptionBudgetConfig::default(),
+			anti_affinity_topology_keys: vec![
+				HOSTNAME_TOPOLOGY_KEY.to_string(),
+				ZONE_TOPOLOGY_KEY.to_string(),
+			],
+		}
+	}
+}
+
+impl DisruptionBudgetConfig {
+	fn default() -> Self {
+		Self {
+			min_available: None,
+			max_unavailable: Some(IntOrString::Int(1)),
 		}
 	}
 }
@@ -82,13 +180,48 @@ impl ContainerConfig {
 			protocol: "TCP".to_string(),
 		}
 	}
+
+	pub fn user_env(&self) -> EnvVar {
+		EnvVar {
+			name: "SURREAL_USER".to_owned(),
+			value: Some(self.user.clone()),
+			value_from: None,
+		}
+	}
+
+	/// Keeps the password out of the arguments of the container, which anyone allowed to read
+	/// the workload can see.
+	pub fn password_env(&self) -> EnvVar {
+		EnvVar {
+			name: "SURREAL_PASS".to_owned(),
+			value: Some(self.password.clone()),
+			value_from: None,
+		}
+	}
+}
+
+impl MetricsConfig {
+	pub const PORT: i32 = 9464;
+	pub const PORT_NAME: &'static str = "metrics";
+
+	fn default() -> Self {
+		Self {
+			image: "otel/opentelemetry-collector-contrib:0.98.0".to_string(),
+			otlp_port: 4317,
+			port: Self::PORT,
+			port_name: Self::PORT_NAME.to_string(),
+		}
+	}
 }
 
 impl PVCConfig {
+	pub const DEFAULT_SIZE: &'static str = "1Gi";
+
 	fn default() -> Self {
 		Self {
 			access_modes: vec!["ReadWriteOnce".to_string()],
-			resource_quantity: Quantity("1Gi".to_string()),
+			resource_quantity: Quantity(Self::DEFAULT_SIZE.to_string()),
+			storage_class_name: None,
 		}
 	}
 }
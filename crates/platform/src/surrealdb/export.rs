@@ -0,0 +1,298 @@
+use k8s_openapi::api::{
+	batch::v1::Job,
+	core::v1::{
+		Container, EmptyDirVolumeSource, EnvFromSource, EnvVar, PersistentVolumeClaimVolumeSource,
+		PodSpec, SecretEnvSource, Volume, VolumeMount,
+	},
+};
+
+use super::config::{ContainerConfig, SurrealDBConfig};
+use crate::core::{job::JobBuilder, security::SecurityContextConfig};
+
+pub const EXPORT_PATH: &str = "/export";
+pub const UPLOAD_IMAGE: &str = "amazon/aws-cli:2.15.30";
+
+/// Where an export is written to.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExportDestination {
+	/// A directory of a PersistentVolumeClaim in the namespace of the database.
+	Volume {
+		claim_name: String,
+		path: String,
+	},
+	/// An S3 compatible bucket URL, with the credentials in the environment of a Secret.
+	Bucket {
+		url: String,
+		secret_name: String,
+	},
+}
+
+/// A Job exporting one database of a SurrealDB instance as SurrealQL with `surreal export`.
+pub struct SurrealDBExport {
+	pub name: String,
+	pub namespace: String,
+	pub url: String,
+	pub database: String,
+	pub container: ContainerConfig,
+	pub security: SecurityContextConfig,
+	pub destination: ExportDestination,
+	pub pod_labels: Vec<(String, String)>,
+}
+
+impl SurrealDBExport {
+	pub fn new(
+		config: SurrealDBConfig,
+		database: &str,
+		name: &str,
+		destination: ExportDestination,
+	) -> Self {
+		Self {
+			name: name.to_string(),
+			url: format!("http://{}.{}.svc:{}", config.name, config.namespace, config.service.port),
+			namespace: config.namespace,
+			database: database.to_string(),
+			container: config.statefulset.container,
+			security: config.statefulset.security,
+			destination,
+			pod_labels: Vec::new(),
+		}
+	}
+
+	/// Labels the export pods, e.g. to be admitted by the database network policy.
+	pub fn pod_labels(mut self, labels: Vec<(&str, &str)>) -> Self {
+		self.pod_labels =
+			labels.into_iter().map(|(key, value)| (key.to_string(), value.to_string())).collect();
+		self
+	}
+
+	pub fn file_name(&self) -> String {
+		format!("{}.surql", self.name)
+	}
+
+	/// Where the export ends up once the Job completed.
+	pub fn location(&self) -> String {
+		match &self.destination {
+			ExportDestination::Volume {
+				claim_name,
+				path,
+			} => {
+				format!("pvc://{}/{}", claim_name, join(path, &self.file_name()))
+			}
+			ExportDestination::Bucket {
+				url,
+				..
+			} => join(url, &self.file_name()),
+		}
+	}
+
+	fn export_container(&self, mount: VolumeMount) -> Container {
+		let file = join(EXPORT_PATH, &self.file_name());
+
+		Container {
+			name: "export".to_string(),
+			image: Some(self.container.image.clone()),
+			image_pull_policy: Some("IfNotPresent".to_string()),
+			args: Some(
+				[
+					"export",
+					"--conn",
+					&self.url,
+					"--ns",
+					&self.namespace,
+					"--db",
+					&self.database,
+					&file,
+				]
+				.map(str::to_string)
+				.to_vec(),
+			),
+			env: Some(vec![self.container.user_env(), self.container.password_env()]),
+			volume_mounts: Some(vec![mount]),
+			..Container::default()
+		}
+	}
+
+	fn upload_container(
+		&self,
+		url: &str,
+		secret_name: &str,
+		mounts: Vec<VolumeMount>,
+	) -> Container {
+		Container {
+			name: "upload".to_string(),
+			image: Some(UPLOAD_IMAGE.to_string()),
+			image_pull_policy: Some("IfNotPresent".to_string()),
+			args: Some(vec![
+				"s3".to_string(),
+				"cp".to_string(),
+				join(EXPORT_PATH, &self.file_name()),
+				join(url, &self.file_name()),
+			]),
+			env: Some(vec![EnvVar {
+				name: "HOME".to_string(),
+				value: Some("/tmp".to_string()),
+				value_from: None,
+			}]),
+			env_from: Some(vec![EnvFromSource {
+				secret_ref: Some(SecretEnvSource {
+					name: Some(secret_name.to_string()),
+					optional: Some(false),
+				}),
+				..EnvFromSource::default()
+			}]),
+			volume_mounts: Some(mounts),
+			..Container::default()
+		}
+	}
+
+	fn pod_spec(&self) -> PodSpec {
+		match &self.destination {
+			ExportDestination::Volume {
+				claim_name,
+				path,
+			} => PodSpec {
+				containers: vec![self.export_container(mount("export", EXPORT_PATH, path))],
+				volumes: Some(vec![Volume {
+					name: "export".to_string(),
+					persistent_volume_claim: Some(PersistentVolumeClaimVolumeSource {
+						claim_name: claim_name.clone(),
+						read_only: Some(false),
+					}),
+					..Volume::default()
+				}]),
+				..PodSpec::default()
+			},
+			// The export is staged on an emptyDir and uploaded once it is complete.
+			ExportDestination::Bucket {
+				url,
+				secret_name,
+			} => PodSpec {
+				init_containers: Some(vec![self.export_container(mount(
+					"export",
+					EXPORT_PATH,
+					"",
+				))]),
+				containers: vec![self.upload_container(
+					url,
+					secret_name,
+					vec![mount("export", EXPORT_PATH, ""), mount("tmp", "/tmp", "")],
+				)],
+				volumes: Some(vec![empty_dir("export"), empty_dir("tmp")]),
+				..PodSpec::default()
+			},
+		}
+	}
+
+	pub fn manifest(&self) -> Job {
+		let pod_labels =
+			self.pod_labels.iter().map(|(key, value)| (key.as_str(), value.as_str())).collect();
+
+		JobBuilder::default()
+			.metadata(&self.name, &self.name, &self.namespace)
+			.spec(pod_labels, self.pod_spec())
+			.security_context(&self.security)
+			.manifest()
+	}
+
+	pub fn get_labels(&self) -> String {
+		format!("part-of={}", self.name)
+	}
+}
+
+fn join(base: &str, file: &str) -> String {
+	match base.trim_end_matches('/') {
+		"" => file.to_string(),
+		base => format!("{}/{}", base, file),
+	}
+}
+
+fn mount(name: &str, mount_path: &str, sub_path: &str) -> VolumeMount {
+	VolumeMount {
+		name: name.to_string(),
+		mount_path: mount_path.to_string(),
+		sub_path: Some(sub_path.trim_matches('/').to_string()).filter(|path| !path.is_empty()),
+		..VolumeMount::default()
+	}
+}
+
+fn empty_dir(name: &str) -> Volume {
+	Volume {
+		name: name.to_string(),
+		empty_dir: Some(EmptyDirVolumeSource::default()),
+		..Volume::default()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::core::security::restricted_profile_violations;
+
+	fn export(destination: ExportDestination) -> SurrealDBExport {
+		let config = SurrealDBConfig::new("orders-db".to_string(), "shop".to_string());
+
+		SurrealDBExport::new(config, "orders", "orders-ingest-1a2b3c4d", destination)
+			.pod_labels(vec![("dappmesh.io/product", "orders")])
+	}
+
+	#[test]
+	fn volume_export_writes_to_the_claim() {
+		let export = export(ExportDestination::Volume {
+			claim_name: "snapshots".to_string(),
+			path: "orders/".to_string(),
+		});
+
+		let job = export.manifest();
+		let template = job.spec.unwrap().template;
+		let pod = template.spec.unwrap();
+		let args = pod.containers[0].args.clone().unwrap();
+
+		assert_eq!(export.location(), "pvc://snapshots/orders/orders-ingest-1a2b3c4d.surql");
+		assert_eq!(args[2], "http://orders-db.shop.svc:8080");
+		assert_eq!(args.last().unwrap(), "/export/orders-ingest-1a2b3c4d.surql");
+		assert_eq!(
+			pod.containers[0].volume_mounts.as_ref().unwrap()[0].sub_path.as_deref(),
+			Some("orders")
+		);
+		assert_eq!(pod.restart_policy.as_deref(), Some("Never"));
+		assert_eq!(template.metadata.unwrap().labels.unwrap()["dappmesh.io/product"], "orders");
+		assert!(restricted_profile_violations(&pod).is_empty());
+	}
+
+	#[test]
+	fn bucket_export_uploads_after_exporting() {
+		let export = export(ExportDestination::Bucket {
+			url: "s3://snapshots/orders".to_string(),
+			secret_name: "snapshot-credentials".to_string(),
+		});
+
+		let pod = export.manifest().spec.unwrap().template.spec.unwrap();
+		let upload = &pod.containers[0];
+
+		assert_eq!(export.location(), "s3://snapshots/orders/orders-ingest-1a2b3c4d.surql");
+		assert_eq!(pod.init_containers.as_ref().unwrap()[0].name, "export");
+		assert_eq!(
+			upload.args.clone().unwrap()[2..],
+			[
+				"/export/orders-ingest-1a2b3c4d.surql",
+				"s3://snapshots/orders/orders-ingest-1a2b3c4d.surql"
+			]
+		);
+		assert!(restricted_profile_violations(&pod).is_empty());
+	}
+
+	#[test]
+	fn the_password_is_passed_through_the_environment() {
+		let destination = ExportDestination::Volume {
+			claim_name: "snapshots".to_string(),
+			path: String::new(),
+		};
+		let export = export(destination);
+		let container =
+			export.manifest().spec.unwrap().template.spec.unwrap().containers[0].clone();
+
+		assert!(!container.args.unwrap().iter().any(|arg| arg == "--pass" || arg == "root"));
+		let env = container.env.unwrap();
+		assert_eq!((env[1].name.as_str(), env[1].value.as_deref()), ("SURREAL_PASS", Some("root")));
+	}
+}
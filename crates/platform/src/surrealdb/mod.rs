@@ -1,5 +1,10 @@
 pub mod app;
+pub mod client;
 pub mod config;
+pub mod export;
+pub mod network_policy;
+pub mod pod_disruption_budget;
 pub mod service;
 pub mod service_account;
+pub mod spec;
 pub mod statefulset;
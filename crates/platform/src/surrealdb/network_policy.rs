@@ -0,0 +1,42 @@
+use k8s_openapi::api::networking::v1::NetworkPolicy;
+
+use super::config::{SurrealDBConfig, SurrealDBNetworkPolicyConfig};
+use crate::core::network_policy::{pod_peer, NetworkPolicyBuilder};
+
+pub struct SurrealDBNetworkPolicy {
+	pub name: String,
+	pub namespace: String,
+	pub port: i32,
+	pub config: Option<SurrealDBNetworkPolicyConfig>,
+}
+
+impl SurrealDBNetworkPolicy {
+	pub fn new(config: SurrealDBConfig) -> Self {
+		Self {
+			name: config.name,
+			namespace: config.namespace,
+			port: config.service.port,
+			config: config.network_policy,
+		}
+	}
+
+	pub fn enabled(&self) -> bool {
+		self.config.is_some()
+	}
+
+	pub fn manifest(&self) -> NetworkPolicy {
+		let mut peers = vec![pod_peer(vec![("part-of", &self.name)], None)];
+		if let Some(config) = &self.config {
+			peers.extend(config.peers.clone());
+		}
+
+		NetworkPolicyBuilder::default()
+			.metadata(&self.name, &self.name, &self.namespace)
+			.ingress_spec(vec![("part-of", &self.name)], peers, self.port)
+			.manifest()
+	}
+
+	pub fn get_labels(&self) -> String {
+		format!("part-of={}", self.name)
+	}
+}
@@ -0,0 +1,42 @@
+use k8s_openapi::api::policy::v1::PodDisruptionBudget;
+
+use super::config::{DisruptionBudgetConfig, SurrealDBConfig};
+use crate::core::pod_disruption_budget::PodDisruptionBudgetBuilder;
+
+pub struct SurrealDBPodDisruptionBudget {
+	pub name: String,
+	pub namespace: String,
+	pub replicas: i32,
+	pub config: DisruptionBudgetConfig,
+}
+
+impl SurrealDBPodDisruptionBudget {
+	pub fn new(config: SurrealDBConfig) -> Self {
+		Self {
+			name: config.name,
+			namespace: config.namespace,
+			replicas: config.statefulset.replicas,
+			config: config.statefulset.disruption_budget,
+		}
+	}
+
+	/// A budget on a single replica would block every node drain.
+	pub fn enabled(&self) -> bool {
+		self.replicas > 1
+	}
+
+	pub fn manifest(&self) -> PodDisruptionBudget {
+		PodDisruptionBudgetBuilder::default()
+			.metadata(&self.name, &self.name, &self.namespace)
+			.spec(
+				vec![("part-of", &self.name)],
+				self.config.min_available.clone(),
+				self.config.max_unavailable.clone(),
+			)
+			.manifest()
+	}
+
+	pub fn get_labels(&self) -> String {
+		format!("part-of={}", self.name)
+	}
+}
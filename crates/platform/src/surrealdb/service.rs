@@ -1,11 +1,12 @@
 use k8s_openapi::api::core::v1::Service;
 
-use super::config::{SurrealDBConfig, SurrealDBServiceConfig};
+use super::config::{MetricsConfig, SurrealDBConfig, SurrealDBServiceConfig};
 use crate::core::service::ServiceBuilder;
 pub struct SurrealDBService {
 	pub name: String,
 	pub namespace: String,
 	config: SurrealDBServiceConfig,
+	metrics: Option<MetricsConfig>,
 }
 
 impl SurrealDBService {
@@ -14,13 +15,21 @@ impl SurrealDBService {
 			name: config.name,
 			namespace: config.namespace,
 			config: config.service,
+			metrics: config.metrics,
 		}
 	}
 
 	pub fn manifest(&self) -> Service {
-		ServiceBuilder::default()
-			.metadata(&self.name, &self.namespace)
-			.service_spec(&self.config.protocol, self.config.port)
+		let builder = ServiceBuilder::default().metadata(&self.name, &self.namespace);
+		let Some(metrics) = &self.metrics else {
+			return builder.service_spec(&self.config.protocol, self.config.port).manifest();
+		};
+
+		builder
+			.named_ports(vec![
+				("http", &self.config.protocol, self.config.port),
+				(&metrics.port_name, "TCP", metrics.port),
+			])
 			.manifest()
 	}
 
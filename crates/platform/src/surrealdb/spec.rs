@@ -0,0 +1,111 @@
+use k8s_openapi::apimachinery::pkg::util::intstr::IntOrString;
+use schemars::{
+	gen::SchemaGenerator,
+	schema::{Schema, SchemaObject},
+	JsonSchema,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::config::PVCConfig;
+use crate::core::validation::{optional_schema, Rule, Validated, DNS_SUBDOMAIN, QUANTITY};
+
+/// Database settings that custom resources may set for their SurrealDB instance.
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SurrealDBSpec {
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schemars(range(min = 1), default = "default_replicas")]
+	pub replicas: Option<i32>,
+	/// Only created while more than one replica is requested.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schemars(schema_with = "optional_schema::<DisruptionBudgetSpec>")]
+	pub disruption_budget: Option<DisruptionBudgetSpec>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub anti_affinity: Option<AntiAffinitySpec>,
+	/// Fixed once the database is created, as the volumes of a StatefulSet cannot change.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schemars(schema_with = "optional_schema::<StorageSpec>")]
+	pub storage: Option<StorageSpec>,
+}
+
+impl Validated for SurrealDBSpec {
+	const RULES: &'static [Rule] = &[
+		Rule::new(
+			"!has(self.disruptionBudget) || !has(self.disruptionBudget.minAvailable) \
+		 || type(self.disruptionBudget.minAvailable) != int \
+		 || self.disruptionBudget.minAvailable <= (has(self.replicas) ? self.replicas : 1)",
+			"disruptionBudget.minAvailable cannot exceed replicas",
+		),
+		Rule::new(
+			"has(self.storage) == has(oldSelf.storage)",
+			"storage cannot be added or removed",
+		),
+	];
+}
+
+fn default_replicas() -> Option<i32> {
+	Some(1)
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DisruptionBudgetSpec {
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schemars(schema_with = "int_or_string")]
+	pub min_available: Option<IntOrString>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schemars(schema_with = "int_or_string")]
+	pub max_unavailable: Option<IntOrString>,
+}
+
+impl Validated for DisruptionBudgetSpec {
+	const RULES: &'static [Rule] = &[Rule::new(
+		"!(has(self.minAvailable) && has(self.maxUnavailable))",
+		"minAvailable and maxUnavailable are mutually exclusive",
+	)];
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AntiAffinitySpec {
+	/// Topology keys to spread replicas across, most important first. Empty disables it.
+	pub topology_keys: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageSpec {
+	/// Requested size of each replica's volume.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schemars(regex = "QUANTITY", length(max = 63), default = "default_size")]
+	pub size: Option<String>,
+	/// Defaults to the default StorageClass of the cluster.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schemars(regex = "DNS_SUBDOMAIN", length(max = 253))]
+	pub class_name: Option<String>,
+}
+
+impl Validated for StorageSpec {
+	const RULES: &'static [Rule] = &[
+		Rule::new(
+			"has(self.className) == has(oldSelf.className) \
+			 && (!has(self.className) || self.className == oldSelf.className)",
+			"className is immutable",
+		),
+		Rule::new(
+			"has(self.size) == has(oldSelf.size) && (!has(self.size) || self.size == oldSelf.size)",
+			"size is immutable",
+		),
+	];
+}
+
+fn default_size() -> Option<String> {
+	Some(PVCConfig::DEFAULT_SIZE.to_string())
+}
+
+pub fn int_or_string(_: &mut SchemaGenerator) -> Schema {
+	let mut schema = SchemaObject::default();
+	schema.extensions.insert("x-kubernetes-int-or-string".to_string(), Value::Bool(true));
+	Schema::Object(schema)
+}
@@ -2,18 +2,19 @@ use k8s_openapi::api::core::v1::VolumeMount;
 use k8s_openapi::{
 	api::{
 		apps::v1::StatefulSet,
-		core::v1::{Container, ContainerPort, EnvVar, HTTPGetAction, Probe},
+		core::v1::{Container, ContainerPort, EnvVar, HTTPGetAction, Probe, TCPSocketAction},
 	},
 	apimachinery::pkg::util::intstr::IntOrString,
 };
 
-use super::config::{SurrealDBConfig, SurrealDBStatefulSetConfig};
-use crate::core::statefulset::StatefulSetBuilder;
+use super::config::{MetricsConfig, SurrealDBConfig, SurrealDBStatefulSetConfig};
+use crate::core::{affinity::soft_pod_anti_affinity, statefulset::StatefulSetBuilder};
 
 pub struct SurrealDBStatefulSet {
 	pub name: String,
 	pub namespace: String,
 	pub config: SurrealDBStatefulSetConfig,
+	pub metrics: Option<MetricsConfig>,
 }
 
 impl SurrealDBStatefulSet {
@@ -22,6 +23,7 @@ impl SurrealDBStatefulSet {
 			name: config.name,
 			namespace: config.namespace,
 			config: config.statefulset,
+			metrics: config.metrics,
 		}
 	}
 
@@ -51,13 +53,45 @@ impl SurrealDBStatefulSet {
 		};
 
 		containers.push(container);
+		containers.extend(self.metrics.as_ref().map(Self::collector));
 		containers
 	}
 
+	/// Receives the metrics SurrealDB exports over OTLP and serves them to Prometheus.
+	fn collector(metrics: &MetricsConfig) -> Container {
+		let pipeline = "service::pipelines::metrics: {receivers: [otlp], exporters: [prometheus]}";
+		Container {
+			name: metrics.port_name.clone(),
+			image: Some(metrics.image.clone()),
+			image_pull_policy: Some("IfNotPresent".to_string()),
+			args: Some(vec![
+				format!(
+					"--config=yaml:receivers::otlp::protocols::grpc::endpoint: 127.0.0.1:{}",
+					metrics.otlp_port
+				),
+				format!("--config=yaml:exporters::prometheus::endpoint: 0.0.0.0:{}", metrics.port),
+				format!("--config=yaml:{}", pipeline),
+			]),
+			ports: Some(vec![ContainerPort {
+				container_port: metrics.port,
+				name: Some(metrics.port_name.clone()),
+				..ContainerPort::default()
+			}]),
+			readiness_probe: Some(Probe {
+				tcp_socket: Some(TCPSocketAction {
+					port: IntOrString::Int(metrics.port),
+					..TCPSocketAction::default()
+				}),
+				..Probe::default()
+			}),
+			..Container::default()
+		}
+	}
+
 	fn container_env(&self) -> Vec<EnvVar> {
 		let config = &self.config.container;
 
-		vec![
+		let mut env = vec![
 			EnvVar {
 				name: "SURREAL_PATH".to_owned(),
 				value: Some(format!("file:{}", config.path.clone())),
@@ -78,17 +112,24 @@ impl SurrealDBStatefulSet {
 				value: Some(format!("0.0.0.0:{}", config.port)),
 				value_from: None,
 			},
-			EnvVar {
-				name: "SURREAL_USER".to_owned(),
-				value: Some(config.user.clone()),
-				value_from: None,
-			},
-			EnvVar {
-				name: "SURREAL_PASS".to_owned(),
-				value: Some(config.password.clone()),
-				value_from: None,
-			},
-		]
+			config.user_env(),
+			config.password_env(),
+		];
+		if let Some(metrics) = &self.metrics {
+			env.extend([
+				EnvVar {
+					name: "SURREAL_TELEMETRY_PROVIDER".to_owned(),
+					value: Some("otlp".to_owned()),
+					value_from: None,
+				},
+				EnvVar {
+					name: "OTEL_EXPORTER_OTLP_ENDPOINT".to_owned(),
+					value: Some(format!("http://127.0.0.1:{}", metrics.otlp_port)),
+					value_from: None,
+				},
+			]);
+		}
+		env
 	}
 
 	fn http_probe(&self, port: i32) -> Probe {
@@ -103,7 +144,7 @@ impl SurrealDBStatefulSet {
 	}
 
 	pub fn manifest(&self) -> StatefulSet {
-		StatefulSetBuilder::default()
+		let builder = StatefulSetBuilder::default()
 			.metadata(&self.name, &self.name, &self.namespace)
 			.spec(
 				self.config.replicas,
@@ -111,10 +152,121 @@ impl SurrealDBStatefulSet {
 				self.config.pvc.access_modes.clone(),
 				self.config.pvc.resource_quantity.clone(),
 			)
-			.manifest()
+			.security_context(&self.config.security)
+			.storage_class_name(self.config.pvc.storage_class_name.clone());
+
+		let affinity = soft_pod_anti_affinity(
+			&builder.metadata.labels.clone().unwrap_or_default(),
+			&self.config.anti_affinity_topology_keys,
+		);
+
+		builder.affinity(affinity).manifest()
 	}
 
 	pub fn get_labels(&self) -> String {
 		format!("part-of={}", self.name)
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		core::security::{restricted_profile_violations, SecurityContextOverrides},
+		surrealdb::spec::{AntiAffinitySpec, StorageSpec, SurrealDBSpec},
+	};
+
+	fn pod_spec(config: SurrealDBConfig) -> k8s_openapi::api::core::v1::PodSpec {
+		let manifest = SurrealDBStatefulSet::new(config).manifest();
+		manifest.spec.unwrap().template.spec.unwrap()
+	}
+
+	#[test]
+	fn manifest_passes_restricted_pod_security_standard() {
+		let config = SurrealDBConfig::new("test-db".to_string(), "test".to_string());
+
+		let violations = restricted_profile_violations(&pod_spec(config));
+
+		assert!(violations.is_empty(), "{:?}", violations);
+	}
+
+	#[test]
+	fn metrics_are_exported_to_a_collector_serving_them() {
+		let config = SurrealDBConfig::new("test-db".to_string(), "test".to_string());
+
+		let without = pod_spec(config.clone());
+		let pod = pod_spec(config.with_metrics(true));
+		let env = pod.containers[0].env.clone().unwrap();
+		let collector = &pod.containers[1];
+
+		assert_eq!(without.containers.len(), 1);
+		assert!(env.iter().any(|env| env.name == "SURREAL_TELEMETRY_PROVIDER"));
+		assert_eq!(collector.ports.as_ref().unwrap()[0].container_port, MetricsConfig::PORT);
+		assert!(restricted_profile_violations(&pod).is_empty());
+	}
+
+	#[test]
+	fn manifest_prefers_spreading_replicas_across_nodes_and_zones() {
+		let config = SurrealDBConfig::new("test-db".to_string(), "test".to_string());
+
+		let affinity = pod_spec(config).affinity.unwrap().pod_anti_affinity.unwrap();
+		let terms = affinity.preferred_during_scheduling_ignored_during_execution.unwrap();
+
+		assert!(affinity.required_during_scheduling_ignored_during_execution.is_none());
+		assert_eq!(terms[0].pod_affinity_term.topology_key, "kubernetes.io/hostname");
+		assert_eq!(terms[1].pod_affinity_term.topology_key, "topology.kubernetes.io/zone");
+		assert!(terms[0].weight > terms[1].weight);
+	}
+
+	#[test]
+	fn manifest_omits_anti_affinity_without_topology_keys() {
+		let spec = SurrealDBSpec {
+			anti_affinity: Some(AntiAffinitySpec {
+				topology_keys: vec![],
+			}),
+			..SurrealDBSpec::default()
+		};
+		let config =
+			SurrealDBConfig::new("test-db".to_string(), "test".to_string()).with_spec(Some(&spec));
+
+		assert!(pod_spec(config).affinity.is_none());
+	}
+
+	#[test]
+	fn manifest_requests_the_configured_storage() {
+		let spec = SurrealDBSpec {
+			storage: Some(StorageSpec {
+				size: Some("10Gi".to_string()),
+				class_name: Some("fast-ssd".to_string()),
+			}),
+			..SurrealDBSpec::default()
+		};
+		let config =
+			SurrealDBConfig::new("test-db".to_string(), "test".to_string()).with_spec(Some(&spec));
+
+		let manifest = SurrealDBStatefulSet::new(config).manifest();
+		let claim = manifest.spec.unwrap().volume_claim_templates.unwrap()[0].spec.clone().unwrap();
+
+		assert_eq!(claim.storage_class_name.as_deref(), Some("fast-ssd"));
+		assert_eq!(claim.resources.unwrap().requests.unwrap()["storage"].0, "10Gi");
+	}
+
+	#[test]
+	fn manifest_applies_security_overrides() {
+		let overrides = SecurityContextOverrides {
+			run_as_user: Some(1000),
+			fs_group: Some(2000),
+			..SecurityContextOverrides::default()
+		};
+		let config = SurrealDBConfig::new("test-db".to_string(), "test".to_string())
+			.with_security_overrides(Some(&overrides));
+
+		let pod = pod_spec(config);
+		let pod_context = pod.security_context.clone().unwrap();
+		let container_context = pod.containers[0].security_context.clone().unwrap();
+
+		assert_eq!(pod_context.fs_group, Some(2000));
+		assert_eq!(container_context.run_as_user, Some(1000));
+		assert!(restricted_profile_violations(&pod).is_empty());
+	}
+}
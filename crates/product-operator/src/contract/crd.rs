@@ -0,0 +1,158 @@
+use kube::CustomResource;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use dapp_platform::core::{
+	operator::OperatorResource,
+	status::Condition,
+	validation::{optional_schema, Rule, Validated, DNS_LABEL, DNS_SUBDOMAIN},
+};
+
+use crate::schema::crd::SchemaReference;
+
+pub const DEFAULT_DOCUMENT_KEY: &str = "datacontract.yaml";
+
+/// Sections set here take precedence over the same sections of the referenced document.
+#[derive(CustomResource, Serialize, Deserialize, Debug, PartialEq, Eq, Clone, JsonSchema)]
+#[kube(
+	doc = "DappMesh data contract between a product and its consumers.",
+	group = "dappmesh.io",
+	version = "v1alpha1",
+	kind = "DataContract",
+	namespaced,
+	singular = "datacontract",
+	plural = "datacontracts",
+	shortname = "contract",
+	status = "DataContractStatus",
+	derive = "PartialEq"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct DataContractSpec {
+	/// Name of the providing DappProduct in the same namespace.
+	#[schemars(regex = "DNS_SUBDOMAIN", length(max = 253))]
+	pub product: String,
+	/// A `datacontract.yaml` following the Data Contract Specification.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schemars(schema_with = "optional_schema::<ContractDocumentSource>")]
+	pub document: Option<ContractDocumentSource>,
+	/// The DataSchema describing the provided data.
+	pub schema_ref: SchemaReference,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub terms: Option<ContractTerms>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub service_levels: Option<ServiceLevels>,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub quality: Vec<QualityRule>,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub consumers: Vec<ContractConsumer>,
+}
+
+/// Either the document itself or the ConfigMap holding it.
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractDocumentSource {
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub inline: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub config_map_ref: Option<ConfigMapKeyReference>,
+}
+
+impl Validated for ContractDocumentSource {
+	const RULES: &'static [Rule] = &[Rule::new(
+		"has(self.inline) != has(self.configMapRef)",
+		"exactly one of inline or configMapRef has to be set",
+	)];
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+pub struct ConfigMapKeyReference {
+	pub name: String,
+	/// Defaults to `datacontract.yaml`.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub key: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractTerms {
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub usage: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub limitations: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub billing: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub notice_period: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+pub struct ServiceLevels {
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub availability: Option<AvailabilityObjective>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub freshness: Option<FreshnessObjective>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+pub struct AvailabilityObjective {
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub description: Option<String>,
+	/// For example `99.9%`.
+	pub percentage: String,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FreshnessObjective {
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub description: Option<String>,
+	/// Maximum age of the newest record, for example `25h`.
+	pub threshold: String,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub timestamp_field: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+pub struct QualityRule {
+	pub name: String,
+	/// The rule language, for example `SodaCL` or `SurrealQL`.
+	#[serde(rename = "type")]
+	pub type_: String,
+	pub specification: String,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+pub struct ContractConsumer {
+	/// Name of the consuming DappProduct.
+	pub name: String,
+	/// Defaults to the namespace of the contract.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schemars(regex = "DNS_LABEL", length(max = 63))]
+	pub namespace: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, JsonSchema)]
+pub enum ContractState {
+	Valid,
+	Invalid,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DataContractStatus {
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub state: Option<ContractState>,
+	/// The `id` of the contract document.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub contract_id: Option<String>,
+	/// The `info.version` of the contract document.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub contract_version: Option<String>,
+	/// Why the contract is invalid.
+	#[serde(default)]
+	pub errors: Vec<String>,
+	#[serde(default)]
+	pub conditions: Vec<Condition>,
+}
+
+impl OperatorResource for DataContract {}
@@ -0,0 +1,259 @@
+use serde::Deserialize;
+
+use dapp_platform::core::quantity::seconds;
+
+use super::crd::{ContractConsumer, ContractTerms, DataContractSpec, QualityRule, ServiceLevels};
+
+/// The sections of a `datacontract.yaml` the operator understands. Other sections, e.g.
+/// `models` or `servers`, are accepted and ignored.
+#[derive(Deserialize, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractDocument {
+	pub data_contract_specification: Option<String>,
+	pub id: Option<String>,
+	#[serde(default)]
+	pub info: ContractInfo,
+	pub terms: Option<ContractTerms>,
+	#[serde(rename = "servicelevels")]
+	pub service_levels: Option<ServiceLevels>,
+	pub quality: Option<DocumentQuality>,
+}
+
+#[derive(Deserialize, Debug, Default, PartialEq)]
+pub struct ContractInfo {
+	pub title: Option<String>,
+	pub version: Option<String>,
+	pub owner: Option<String>,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+pub struct DocumentQuality {
+	#[serde(rename = "type")]
+	pub type_: String,
+	pub specification: serde_yaml::Value,
+}
+
+impl ContractDocument {
+	pub fn parse(document: &str) -> Result<Self, serde_yaml::Error> {
+		serde_yaml::from_str(document)
+	}
+
+	/// The fields the Data Contract Specification requires.
+	pub fn validate(&self) -> Vec<String> {
+		let required = [
+			("dataContractSpecification", &self.data_contract_specification),
+			("id", &self.id),
+			("info.title", &self.info.title),
+			("info.version", &self.info.version),
+		];
+
+		required
+			.iter()
+			.filter(|(_, value)| value.as_ref().map_or(true, |value| value.trim().is_empty()))
+			.map(|(field, _)| format!("document: {} is required", field))
+			.collect()
+	}
+
+	fn quality_rule(&self) -> Option<QualityRule> {
+		let quality = self.quality.as_ref()?;
+		let specification = match &quality.specification {
+			serde_yaml::Value::String(specification) => specification.clone(),
+			specification => serde_yaml::to_string(specification).unwrap_or_default(),
+		};
+
+		Some(QualityRule {
+			name: "document".to_string(),
+			type_: quality.type_.clone(),
+			specification,
+		})
+	}
+}
+
+/// A contract with the sections of the resource merged over those of its document.
+#[derive(Debug, Default, PartialEq)]
+pub struct Contract {
+	pub id: Option<String>,
+	pub version: Option<String>,
+	pub terms: Option<ContractTerms>,
+	pub service_levels: Option<ServiceLevels>,
+	pub quality: Vec<QualityRule>,
+	pub consumers: Vec<ContractConsumer>,
+}
+
+impl Contract {
+	pub fn merge(spec: &DataContractSpec, document: Option<&ContractDocument>) -> Self {
+		let quality = if spec.quality.is_empty() {
+			document.and_then(ContractDocument::quality_rule).into_iter().collect()
+		} else {
+			spec.quality.clone()
+		};
+
+		Self {
+			id: document.and_then(|document| document.id.clone()),
+			version: document.and_then(|document| document.info.version.clone()),
+			terms: spec
+				.terms
+				.clone()
+				.or_else(|| document.and_then(|document| document.terms.clone())),
+			service_levels: spec
+				.service_levels
+				.clone()
+				.or_else(|| document.and_then(|document| document.service_levels.clone())),
+			quality,
+			consumers: spec.consumers.clone(),
+		}
+	}
+
+	pub fn validate(&self) -> Vec<String> {
+		let mut errors = Vec::new();
+		let service_levels = self.service_levels.clone().unwrap_or_default();
+
+		if let Some(availability) = &service_levels.availability {
+			if !is_percentage(&availability.percentage) {
+				errors.push(format!(
+					"serviceLevels.availability.percentage {:?} is not a percentage like 99.9%",
+					availability.percentage
+				));
+			}
+		}
+
+		if let Some(freshness) = &service_levels.freshness {
+			if seconds(&freshness.threshold).is_none() {
+				errors.push(format!(
+					"serviceLevels.freshness.threshold {:?} is not a duration like 25h",
+					freshness.threshold
+				));
+			}
+		}
+
+		for (index, rule) in self.quality.iter().enumerate() {
+			if rule.name.is_empty() || rule.type_.is_empty() || rule.specification.trim().is_empty()
+			{
+				errors.push(format!("quality[{}] needs a name, a type and a specification", index));
+			}
+			if self.quality[..index].iter().any(|other| other.name == rule.name) {
+				errors.push(format!("quality rule {:?} is defined twice", rule.name));
+			}
+		}
+
+		for (index, consumer) in self.consumers.iter().enumerate() {
+			if consumer.name.is_empty() {
+				errors.push(format!("consumers[{}] needs a name", index));
+			}
+			if self.consumers[..index].contains(consumer) {
+				errors.push(format!("consumer {:?} is listed twice", consumer.name));
+			}
+		}
+
+		errors
+	}
+}
+
+fn is_percentage(value: &str) -> bool {
+	value
+		.strip_suffix('%')
+		.and_then(|number| number.parse::<f64>().ok())
+		.is_some_and(|number| (0.0..=100.0).contains(&number))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{contract::crd::AvailabilityObjective, schema::crd::SchemaReference};
+
+	const DOCUMENT: &str = r#"
+dataContractSpecification: 0.9.3
+id: urn:datacontract:checkout:orders
+info:
+  title: Orders
+  version: 1.0.0
+terms:
+  usage: Analytics only.
+servicelevels:
+  availability:
+    percentage: 99.9%
+  freshness:
+    threshold: 25h
+    timestampField: orders.placed_at
+quality:
+  type: SodaCL
+  specification:
+    checks for orders:
+      - row_count > 0
+models:
+  orders:
+    type: table
+"#;
+
+	fn spec() -> DataContractSpec {
+		DataContractSpec {
+			product: "orders".to_string(),
+			document: None,
+			schema_ref: SchemaReference {
+				name: "orders".to_string(),
+				version: None,
+			},
+			terms: None,
+			service_levels: None,
+			quality: vec![],
+			consumers: vec![],
+		}
+	}
+
+	#[test]
+	fn document_sections_fill_in_unset_spec_sections() {
+		let document = ContractDocument::parse(DOCUMENT).unwrap();
+		let mut spec = spec();
+		spec.service_levels = Some(ServiceLevels {
+			availability: Some(AvailabilityObjective {
+				description: None,
+				percentage: "99%".to_string(),
+			}),
+			freshness: None,
+		});
+
+		let contract = Contract::merge(&spec, Some(&document));
+
+		assert!(document.validate().is_empty());
+		assert!(contract.validate().is_empty());
+		assert_eq!(contract.id.as_deref(), Some("urn:datacontract:checkout:orders"));
+		assert_eq!(contract.version.as_deref(), Some("1.0.0"));
+		assert_eq!(contract.terms.unwrap().usage.as_deref(), Some("Analytics only."));
+		assert_eq!(contract.service_levels, spec.service_levels);
+		assert_eq!(contract.quality[0].type_, "SodaCL");
+	}
+
+	#[test]
+	fn document_without_required_fields_is_invalid() {
+		let document = ContractDocument::parse("id: orders\ninfo:\n  title: Orders\n").unwrap();
+
+		assert_eq!(
+			document.validate(),
+			vec![
+				"document: dataContractSpecification is required",
+				"document: info.version is required"
+			]
+		);
+	}
+
+	#[test]
+	fn malformed_objectives_and_duplicate_consumers_are_reported() {
+		let document = ContractDocument::parse(
+			&DOCUMENT.replace("99.9%", "high").replace("threshold: 25h", "threshold: a day"),
+		)
+		.unwrap();
+		let mut spec = spec();
+		let consumer = ContractConsumer {
+			name: "forecast".to_string(),
+			namespace: None,
+		};
+		spec.consumers = vec![consumer.clone(), consumer];
+
+		let errors = Contract::merge(&spec, Some(&document)).validate();
+
+		assert_eq!(errors.len(), 3);
+		assert!(errors[0].contains("availability.percentage"));
+		assert!(errors[1].contains("freshness.threshold"));
+		assert!(errors[2].contains("listed twice"));
+	}
+}
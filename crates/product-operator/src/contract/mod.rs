@@ -0,0 +1,3 @@
+pub mod crd;
+pub mod document;
+pub mod operator;
@@ -0,0 +1,232 @@
+use k8s_openapi::api::core::v1::ConfigMap;
+use kube::{Api, Client};
+
+use dapp_platform::core::{
+	operator::{OperatorController, OperatorError},
+	plan::Plan,
+	status::{patch_status, set_condition, Condition, READY},
+};
+
+use super::{
+	crd::{
+		ContractDocumentSource, ContractState, DataContract, DataContractSpec, DataContractStatus,
+		DEFAULT_DOCUMENT_KEY,
+	},
+	document::{Contract, ContractDocument},
+};
+use crate::{crd::DappProduct, schema::crd::DataSchema};
+
+pub struct DataContractController {
+	name: String,
+	namespace: String,
+	spec: DataContractSpec,
+	status: DataContractStatus,
+	client: Client,
+	plan: Plan,
+}
+
+impl DataContractController {
+	pub const FINALIZER: &'static str = "datacontracts.dappmesh.io/finalizer";
+
+	pub fn new(name: String, namespace: String, resource: &DataContract, client: Client) -> Self {
+		Self {
+			name,
+			namespace,
+			spec: resource.spec.clone(),
+			status: resource.status.clone().unwrap_or_default(),
+			client,
+			plan: Plan::default(),
+		}
+	}
+
+	/// The contract document, or why it cannot be read.
+	async fn document(
+		&self,
+		source: &ContractDocumentSource,
+	) -> Result<Result<String, String>, OperatorError> {
+		let reference = match (&source.inline, &source.config_map_ref) {
+			(Some(inline), None) => return Ok(Ok(inline.clone())),
+			(None, Some(reference)) => reference,
+			_ => {
+				return Ok(Err("document needs exactly one of inline or configMapRef".to_string()))
+			}
+		};
+
+		let key = reference.key.as_deref().unwrap_or(DEFAULT_DOCUMENT_KEY);
+		let config_maps: Api<ConfigMap> = Api::namespaced(self.client.clone(), &self.namespace);
+		let document = config_maps
+			.get_opt(&reference.name)
+			.await?
+			.and_then(|config_map| config_map.data?.get(key).cloned())
+			.ok_or_else(|| format!("ConfigMap {} has no key {}", reference.name, key));
+
+		Ok(document)
+	}
+
+	/// Merges and validates the contract, including the resources it refers to.
+	async fn evaluate(&self) -> Result<(Contract, Vec<String>), OperatorError> {
+		let mut errors = Vec::new();
+
+		let document = match &self.spec.document {
+			Some(source) => match self.document(source).await? {
+				Ok(document) => match ContractDocument::parse(&document) {
+					Ok(document) => Some(document),
+					Err(error) => {
+						errors.push(format!("document is not valid YAML: {}", error));
+						None
+					}
+				},
+				Err(error) => {
+					errors.push(error);
+					None
+				}
+			},
+			None => None,
+		};
+		errors.extend(document.iter().flat_map(ContractDocument::validate));
+
+		let contract = Contract::merge(&self.spec, document.as_ref());
+		errors.extend(contract.validate());
+
+		let products: Api<DappProduct> = Api::namespaced(self.client.clone(), &self.namespace);
+		if products.get_opt(&self.spec.product).await?.is_none() {
+			errors.push(format!("DappProduct {} does not exist", self.spec.product));
+		}
+
+		for consumer in &contract.consumers {
+			let namespace = consumer.namespace.as_deref().unwrap_or(&self.namespace);
+			let products: Api<DappProduct> = Api::namespaced(self.client.clone(), namespace);
+			if products.get_opt(&consumer.name).await?.is_none() {
+				errors.push(format!("consumer {}/{} does not exist", namespace, consumer.name));
+			}
+		}
+
+		let schemas: Api<DataSchema> = Api::namespaced(self.client.clone(), &self.namespace);
+		let schema_ref = &self.spec.schema_ref;
+		match schemas.get_opt(&schema_ref.name).await? {
+			None => errors.push(format!("DataSchema {} does not exist", schema_ref.name)),
+			Some(schema) => {
+				if let Some(version) = &schema_ref.version {
+					if schema.spec.version(version).is_none() {
+						errors.push(format!(
+							"DataSchema {} has no version {}",
+							schema_ref.name, version
+						));
+					}
+				}
+			}
+		}
+
+		Ok((contract, errors))
+	}
+
+	/// Records the outcome of a validation in `status`, keeping the transition time of an
+	/// unchanged Ready condition.
+	pub fn contract_status(
+		status: &DataContractStatus,
+		contract: &Contract,
+		errors: Vec<String>,
+	) -> DataContractStatus {
+		let mut status = status.clone();
+		let condition = if errors.is_empty() {
+			Condition::new(READY, true, "Valid", "The contract is valid.")
+		} else {
+			Condition::new(READY, false, "Invalid", &errors.join("; "))
+		};
+
+		status.state = Some(if errors.is_empty() {
+			ContractState::Valid
+		} else {
+			ContractState::Invalid
+		});
+		status.contract_id = contract.id.clone();
+		status.contract_version = contract.version.clone();
+		status.errors = errors;
+		set_condition(&mut status.conditions, condition);
+
+		status
+	}
+
+	async fn reconcile_contract(&self) -> Result<(), OperatorError> {
+		let (contract, errors) = self.evaluate().await?;
+		let status = Self::contract_status(&self.status, &contract, errors);
+
+		if status != self.status && !self.plan.is_dry_run() {
+			let api: Api<DataContract> = Api::namespaced(self.client.clone(), &self.namespace);
+			patch_status(&api, &self.name, &status).await?;
+		}
+
+		Ok(())
+	}
+}
+
+impl OperatorController<DataContract> for DataContractController {
+	async fn create_resources(&self) -> Result<(), OperatorError> {
+		self.reconcile_contract().await
+	}
+
+	async fn delete_resources(&self) -> Result<(), OperatorError> {
+		Ok(())
+	}
+
+	async fn update_resources(&self) -> Result<(), OperatorError> {
+		self.reconcile_contract().await
+	}
+
+	fn dry_run(mut self, dry_run: bool) -> Self {
+		self.plan = Plan::new(dry_run);
+		self
+	}
+
+	fn plan(&self) -> Plan {
+		self.plan.clone()
+	}
+
+	fn finalizer(&self) -> &str {
+		Self::FINALIZER
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn contract() -> Contract {
+		Contract {
+			id: Some("urn:datacontract:checkout:orders".to_string()),
+			version: Some("1.0.0".to_string()),
+			..Contract::default()
+		}
+	}
+
+	#[test]
+	fn valid_contract_is_ready() {
+		let status =
+			DataContractController::contract_status(&Default::default(), &contract(), vec![]);
+
+		assert_eq!(status.state, Some(ContractState::Valid));
+		assert_eq!(status.contract_version.as_deref(), Some("1.0.0"));
+		assert!(status.conditions[0].is_true());
+	}
+
+	#[test]
+	fn invalid_contract_reports_its_errors() {
+		let errors = vec!["DataSchema orders does not exist".to_string()];
+
+		let status =
+			DataContractController::contract_status(&Default::default(), &contract(), errors);
+
+		assert_eq!(status.state, Some(ContractState::Invalid));
+		assert_eq!(status.errors, vec!["DataSchema orders does not exist"]);
+		assert_eq!(status.conditions[0].reason, "Invalid");
+		assert!(!status.conditions[0].is_true());
+	}
+
+	#[test]
+	fn unchanged_outcome_leaves_the_status_as_is() {
+		let status =
+			DataContractController::contract_status(&Default::default(), &contract(), vec![]);
+
+		assert_eq!(DataContractController::contract_status(&status, &contract(), vec![]), status);
+	}
+}
@@ -1,8 +1,25 @@
-use kube::CustomResource;
+pub mod v1beta1;
+
+use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition;
+use kube::{core::crd::merge_crds, CustomResource, CustomResourceExt};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
-use dapp_platform::core::operator::OperatorResource;
+use dapp_platform::{
+	core::{
+		constants::{OPERATOR_NAMESPACE, PRODUCT_OPERATOR},
+		conversion::{self, with_webhook, ConversionError},
+		governance::DataGovernance,
+		hierarchy::ParentReference,
+		operator::OperatorResource,
+		ownership::Ownership,
+		security::SecurityContextOverrides,
+		status::Condition,
+		validation::{optional_schema, DNS_LABEL, DNS_SUBDOMAIN},
+	},
+	surrealdb::spec::SurrealDBSpec,
+};
 
 #[derive(
 	CustomResource, Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema,
@@ -17,16 +34,70 @@ use dapp_platform::core::operator::OperatorResource;
 	plural = "dappproducts",
 	shortname = "product",
 	shortname = "products",
+	status = "DappProductStatus",
 	derive = "PartialEq",
 	derive = "Default"
 )]
+#[serde(rename_all = "camelCase")]
 pub struct DappProductSpec {
-	#[schemars(regex(pattern = r"[A-Za-z0-9](?:[A-Za-z0-9\-]{0,61}[A-Za-z0-9])?"))]
+	/// Repeats `metadata.name` and is dropped in v1beta1.
+	#[schemars(regex = "DNS_SUBDOMAIN", length(min = 1, max = 253))]
 	name: String,
+	/// The DappDomain the resource belongs to, which has to be Ready before it is provisioned.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub domain_ref: Option<ParentReference>,
+	/// Overrides for the hardened security context of the generated workloads.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub security_context: Option<SecurityContextOverrides>,
+	/// Replication and scheduling of the SurrealDB instance backing this resource.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schemars(schema_with = "optional_schema::<SurrealDBSpec>")]
+	pub database: Option<SurrealDBSpec>,
+	/// Data products whose workloads may reach this product's storage.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub consumers: Vec<ProductConsumer>,
+	/// What the data is, checked continuously against the policies of the DappDomain.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub governance: Option<DataGovernance>,
+	/// The team accountable for the product, defaulting to the owner of the DappDomain.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub owner: Option<Ownership>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+pub struct ProductConsumer {
+	#[schemars(regex = "DNS_SUBDOMAIN", length(max = 253))]
+	pub name: String,
+	/// Defaults to the namespace of the consumed product.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schemars(regex = "DNS_LABEL", length(max = 63))]
+	pub namespace: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+pub struct DappProductStatus {
+	#[serde(default)]
+	pub conditions: Vec<Condition>,
 }
 
 impl OperatorResource for DappProduct {}
 
+/// Objects are stored as v1alpha1 until every one of them has been migrated to v1beta1.
+pub const STORAGE_VERSION: &str = "v1alpha1";
+pub const CONVERSION_PATH: &str = "/convert/dappproducts";
+
+/// The CRD serving every version of DappProduct, converted by the webhook of the product operator.
+pub fn crd() -> CustomResourceDefinition {
+	let crd = merge_crds(vec![DappProduct::crd(), v1beta1::DappProduct::crd()], STORAGE_VERSION)
+		.expect("versions of DappProduct have the same names and scope");
+	with_webhook(crd, PRODUCT_OPERATOR, OPERATOR_NAMESPACE, CONVERSION_PATH)
+}
+
+/// Converts a DappProduct object between v1alpha1 and v1beta1.
+pub fn convert(object: Value, desired_api_version: &str) -> Result<Value, ConversionError> {
+	conversion::convert::<DappProduct, v1beta1::DappProduct>(object, desired_api_version)
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -56,4 +127,31 @@ mod tests {
 			self
 		}
 	}
+
+	#[test]
+	fn v1alpha1_objects_round_trip_through_v1beta1() {
+		let mut product = DappProduct::test_instance();
+		product.spec.name = product.name_any();
+		let object = serde_json::to_value(&product).unwrap();
+
+		let beta = convert(object.clone(), "dappmesh.io/v1beta1").unwrap();
+		assert_eq!(beta["apiVersion"], "dappmesh.io/v1beta1");
+		assert!(beta["spec"].get("name").is_none());
+
+		assert_eq!(convert(beta, "dappmesh.io/v1alpha1").unwrap(), object);
+	}
+
+	#[test]
+	fn both_versions_are_served_and_v1alpha1_is_stored() {
+		let crd = crd();
+
+		let versions: Vec<_> = crd
+			.spec
+			.versions
+			.iter()
+			.map(|version| (version.name.as_str(), version.storage))
+			.collect();
+		assert_eq!(versions, vec![("v1alpha1", true), ("v1beta1", false)]);
+		assert!(crd.spec.conversion.is_some());
+	}
 }
@@ -0,0 +1,89 @@
+use kube::CustomResource;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use dapp_platform::{
+	core::{
+		governance::DataGovernance, hierarchy::ParentReference, ownership::Ownership,
+		security::SecurityContextOverrides, validation::optional_schema,
+	},
+	surrealdb::spec::SurrealDBSpec,
+};
+
+use super::{DappProductStatus, ProductConsumer};
+
+#[derive(
+	CustomResource, Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema,
+)]
+#[kube(
+	doc = "DappMesh product controller custom resource.",
+	group = "dappmesh.io",
+	version = "v1beta1",
+	kind = "DappProduct",
+	namespaced,
+	singular = "dappproduct",
+	plural = "dappproducts",
+	shortname = "product",
+	shortname = "products",
+	status = "DappProductStatus",
+	derive = "PartialEq",
+	derive = "Default"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct DappProductSpec {
+	/// The DappDomain the resource belongs to, which has to be Ready before it is provisioned.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub domain_ref: Option<ParentReference>,
+	/// Overrides for the hardened security context of the generated workloads.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub security_context: Option<SecurityContextOverrides>,
+	/// Replication and scheduling of the SurrealDB instance backing this resource.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schemars(schema_with = "optional_schema::<SurrealDBSpec>")]
+	pub database: Option<SurrealDBSpec>,
+	/// Data products whose workloads may reach this product's storage.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub consumers: Vec<ProductConsumer>,
+	/// What the data is, checked continuously against the policies of the DappDomain.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub governance: Option<DataGovernance>,
+	/// The team accountable for the product, defaulting to the owner of the DappDomain.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub owner: Option<Ownership>,
+}
+
+impl From<super::DappProduct> for DappProduct {
+	fn from(product: super::DappProduct) -> Self {
+		Self {
+			metadata: product.metadata,
+			spec: DappProductSpec {
+				domain_ref: product.spec.domain_ref,
+				security_context: product.spec.security_context,
+				database: product.spec.database,
+				consumers: product.spec.consumers,
+				governance: product.spec.governance,
+				owner: product.spec.owner,
+			},
+			status: product.status,
+		}
+	}
+}
+
+/// Restores the `name` that `v1alpha1` repeats from the metadata.
+impl From<DappProduct> for super::DappProduct {
+	fn from(product: DappProduct) -> Self {
+		Self {
+			spec: super::DappProductSpec {
+				name: product.metadata.name.clone().unwrap_or_default(),
+				domain_ref: product.spec.domain_ref,
+				security_context: product.spec.security_context,
+				database: product.spec.database,
+				consumers: product.spec.consumers,
+				governance: product.spec.governance,
+				owner: product.spec.owner,
+			},
+			metadata: product.metadata,
+			status: product.status,
+		}
+	}
+}
@@ -0,0 +1,133 @@
+use kube::CustomResource;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use dapp_platform::core::{
+	operator::OperatorResource,
+	status::Condition,
+	validation::{optional_schema, DNS_LABEL, DNS_SUBDOMAIN},
+};
+
+use crate::{schema::crd::SchemaReference, snapshot::crd::SnapshotDestination};
+
+/// Steps move data from one schema version to the next, inputs are wired to the outputs of
+/// other steps so that the steps form a DAG.
+#[derive(CustomResource, Serialize, Deserialize, Debug, PartialEq, Eq, Clone, JsonSchema)]
+#[kube(
+	doc = "DappMesh data flow moving the data of a product between schema versions.",
+	group = "dappmesh.io",
+	version = "v1alpha1",
+	kind = "DataFlow",
+	namespaced,
+	singular = "dataflow",
+	plural = "dataflows",
+	shortname = "dflow",
+	status = "DataFlowStatus",
+	derive = "PartialEq"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct DataFlowSpec {
+	/// Name of the DappProduct in the same namespace the flow belongs to.
+	#[schemars(regex = "DNS_SUBDOMAIN", length(max = 253))]
+	pub product: String,
+	#[schemars(length(min = 1))]
+	pub steps: Vec<FlowStep>,
+	/// Where to export the product tables each time a step completes, no snapshots are
+	/// taken when unset.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schemars(schema_with = "optional_schema::<SnapshotDestination>")]
+	pub snapshots: Option<SnapshotDestination>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, JsonSchema)]
+pub struct FlowStep {
+	/// Part of the names of the snapshots taken after the step.
+	#[schemars(regex = "DNS_LABEL", length(max = 63))]
+	pub name: String,
+	pub kind: StepKind,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub inputs: Vec<StepInput>,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub outputs: Vec<StepOutput>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, JsonSchema)]
+pub enum StepKind {
+	Ingestion,
+	Transformation,
+	Aggregation,
+	Serving,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, JsonSchema)]
+pub struct StepInput {
+	pub name: String,
+	pub schema: SchemaReference,
+	/// The output feeding the input as `step.output`. Only ingestion steps read from
+	/// outside the flow and may leave it unset.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub from: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, JsonSchema)]
+pub struct StepOutput {
+	pub name: String,
+	pub schema: SchemaReference,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DataFlowStatus {
+	/// Step names in an order that runs every step after the steps it reads from.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub order: Vec<String>,
+	/// The steps with their dependencies and schema versions resolved.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub steps: Vec<ResolvedStep>,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub errors: Vec<String>,
+	/// Reported by the runners executing the flow.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub completed_steps: Vec<CompletedStep>,
+	#[serde(default)]
+	pub conditions: Vec<Condition>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletedStep {
+	pub name: String,
+	pub completed_at: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolvedStep {
+	pub name: String,
+	pub kind: StepKind,
+	/// Steps whose outputs this step reads.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub depends_on: Vec<String>,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub inputs: Vec<ResolvedPort>,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub outputs: Vec<ResolvedPort>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, JsonSchema)]
+pub struct ResolvedPort {
+	pub name: String,
+	pub schema: String,
+	pub version: String,
+}
+
+impl DataFlowSpec {
+	/// Hex encoded SHA-256 of the spec, identifying the flow that produced a snapshot.
+	pub fn hash(&self) -> String {
+		let spec = serde_json::to_vec(self).expect("DataFlowSpec serializes to JSON");
+		hex::encode(Sha256::digest(spec))
+	}
+}
+
+impl OperatorResource for DataFlow {}
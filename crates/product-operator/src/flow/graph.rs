@@ -0,0 +1,329 @@
+use std::collections::BTreeMap;
+
+use super::crd::{DataFlowSpec, ResolvedPort, ResolvedStep, StepKind};
+use crate::schema::crd::{DataSchemaSpec, SchemaReference};
+
+/// A flow whose references all resolve and whose steps form a DAG.
+#[derive(Debug, PartialEq)]
+pub struct ResolvedFlow {
+	/// Step names, every step after the steps it depends on.
+	pub order: Vec<String>,
+	/// The steps in declaration order.
+	pub steps: Vec<ResolvedStep>,
+}
+
+/// Resolves the schema versions and dependencies of the steps of `spec` against the
+/// DataSchemas in `schemas`, keyed by name, and orders the steps topologically.
+pub fn resolve(
+	spec: &DataFlowSpec,
+	schemas: &BTreeMap<String, DataSchemaSpec>,
+) -> Result<ResolvedFlow, Vec<String>> {
+	let mut errors = duplicate_names(spec);
+	let mut steps = Vec::new();
+
+	for step in &spec.steps {
+		let port = |name: &str, schema: &SchemaReference, errors: &mut Vec<String>| {
+			resolve_port(&step.name, name, schema, schemas).map_err(|error| errors.push(error)).ok()
+		};
+		let inputs: Vec<_> =
+			step.inputs.iter().map(|input| port(&input.name, &input.schema, &mut errors)).collect();
+		let outputs: Vec<_> = step
+			.outputs
+			.iter()
+			.map(|output| port(&output.name, &output.schema, &mut errors))
+			.collect();
+		steps.push((inputs, outputs));
+	}
+
+	let mut resolved = Vec::new();
+	for (index, step) in spec.steps.iter().enumerate() {
+		let mut depends_on = Vec::new();
+
+		for (input, resolved_input) in step.inputs.iter().zip(&steps[index].0) {
+			let Some(from) = &input.from else {
+				if step.kind != StepKind::Ingestion {
+					errors.push(format!(
+						"step {}: input {} needs a from, only ingestion steps read from outside the flow",
+						step.name, input.name
+					));
+				}
+				continue;
+			};
+
+			let Some((upstream, output)) = find_output(spec, from) else {
+				errors.push(format!(
+					"step {}: input {} refers to unknown output {}",
+					step.name, input.name, from
+				));
+				continue;
+			};
+
+			let provided = &steps[upstream].1[output];
+			if let (Some(expected), Some(provided)) = (resolved_input, provided) {
+				if (&expected.schema, &expected.version) != (&provided.schema, &provided.version) {
+					errors.push(format!(
+						"step {}: input {} expects {}@{} but {} provides {}@{}",
+						step.name,
+						input.name,
+						expected.schema,
+						expected.version,
+						from,
+						provided.schema,
+						provided.version
+					));
+				}
+			}
+
+			let upstream = &spec.steps[upstream].name;
+			if !depends_on.contains(upstream) {
+				depends_on.push(upstream.clone());
+			}
+		}
+
+		resolved.push(ResolvedStep {
+			name: step.name.clone(),
+			kind: step.kind,
+			depends_on,
+			inputs: steps[index].0.iter().flatten().cloned().collect(),
+			outputs: steps[index].1.iter().flatten().cloned().collect(),
+		});
+	}
+
+	let order = match topological_order(&resolved) {
+		Ok(order) => order,
+		Err(cycle) => {
+			errors.push(format!("steps {} form a cycle", cycle.join(", ")));
+			Vec::new()
+		}
+	};
+
+	if errors.is_empty() {
+		Ok(ResolvedFlow {
+			order,
+			steps: resolved,
+		})
+	} else {
+		Err(errors)
+	}
+}
+
+fn duplicate_names(spec: &DataFlowSpec) -> Vec<String> {
+	let mut errors = Vec::new();
+
+	for (index, step) in spec.steps.iter().enumerate() {
+		if spec.steps[..index].iter().any(|other| other.name == step.name) {
+			errors.push(format!("step {} is defined twice", step.name));
+		}
+		let inputs = step.inputs.iter().map(|input| &input.name);
+		let outputs = step.outputs.iter().map(|output| &output.name);
+		for (kind, names) in [("input", inputs.collect::<Vec<_>>()), ("output", outputs.collect())]
+		{
+			for (index, name) in names.iter().enumerate() {
+				if names[..index].contains(name) {
+					errors.push(format!("step {}: {} {} is defined twice", step.name, kind, name));
+				}
+			}
+		}
+	}
+
+	errors
+}
+
+fn resolve_port(
+	step: &str,
+	port: &str,
+	schema: &SchemaReference,
+	schemas: &BTreeMap<String, DataSchemaSpec>,
+) -> Result<ResolvedPort, String> {
+	let spec = schemas.get(&schema.name).ok_or_else(|| {
+		format!("step {}: {} refers to DataSchema {} which does not exist", step, port, schema.name)
+	})?;
+	let version = schema.version.as_ref().unwrap_or(&spec.active_version);
+
+	if spec.version(version).is_none() {
+		return Err(format!(
+			"step {}: {} refers to version {} which DataSchema {} does not have",
+			step, port, version, schema.name
+		));
+	}
+
+	Ok(ResolvedPort {
+		name: port.to_string(),
+		schema: schema.name.clone(),
+		version: version.clone(),
+	})
+}
+
+/// The step and output index of a `step.output` reference.
+fn find_output(spec: &DataFlowSpec, reference: &str) -> Option<(usize, usize)> {
+	let (step, output) = reference.split_once('.')?;
+	let index = spec.steps.iter().position(|candidate| candidate.name == step)?;
+	let output = spec.steps[index].outputs.iter().position(|candidate| candidate.name == output)?;
+
+	Some((index, output))
+}
+
+/// Kahn's algorithm, preferring declaration order among ready steps. Fails with the steps
+/// that lie on a cycle.
+fn topological_order(steps: &[ResolvedStep]) -> Result<Vec<String>, Vec<String>> {
+	let mut order: Vec<String> = Vec::new();
+	let mut remaining: Vec<&ResolvedStep> = steps.iter().collect();
+
+	while !remaining.is_empty() {
+		let ready = remaining
+			.iter()
+			.position(|step| step.depends_on.iter().all(|dependency| order.contains(dependency)));
+
+		match ready {
+			Some(index) => order.push(remaining.remove(index).name.clone()),
+			None => {
+				let cycle = remaining
+					.iter()
+					.filter(|step| reaches(&remaining, step, &step.name))
+					.map(|step| step.name.clone())
+					.collect();
+				return Err(cycle);
+			}
+		}
+	}
+
+	Ok(order)
+}
+
+/// Whether `target` is among the transitive dependencies of `step`.
+fn reaches(steps: &[&ResolvedStep], step: &ResolvedStep, target: &str) -> bool {
+	let mut visited: Vec<&str> = Vec::new();
+	let mut pending: Vec<&str> = step.depends_on.iter().map(String::as_str).collect();
+
+	while let Some(name) = pending.pop() {
+		if name == target {
+			return true;
+		}
+		if visited.contains(&name) {
+			continue;
+		}
+		visited.push(name);
+		if let Some(next) = steps.iter().find(|candidate| candidate.name == name) {
+			pending.extend(next.depends_on.iter().map(String::as_str));
+		}
+	}
+
+	false
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		flow::crd::{FlowStep, StepInput, StepOutput},
+		schema::crd::SchemaVersion,
+	};
+	use dapp_platform::schema::{compatibility::CompatibilityMode, SchemaFormat};
+
+	fn schemas() -> BTreeMap<String, DataSchemaSpec> {
+		let versions = ["v1", "v2"].map(|version| SchemaVersion {
+			version: version.to_string(),
+			definition: "DEFINE TABLE order;".to_string(),
+		});
+		let spec = DataSchemaSpec {
+			product: "orders".to_string(),
+			format: SchemaFormat::SurrealQL,
+			compatibility: CompatibilityMode::None,
+			versions: versions.to_vec(),
+			active_version: "v2".to_string(),
+		};
+
+		BTreeMap::from([("orders".to_string(), spec)])
+	}
+
+	fn schema(version: Option<&str>) -> SchemaReference {
+		SchemaReference {
+			name: "orders".to_string(),
+			version: version.map(str::to_string),
+		}
+	}
+
+	fn step(name: &str, kind: StepKind, from: Option<&str>, version: &str) -> FlowStep {
+		FlowStep {
+			name: name.to_string(),
+			kind,
+			inputs: vec![StepInput {
+				name: "in".to_string(),
+				schema: schema(Some(version)),
+				from: from.map(str::to_string),
+			}],
+			outputs: vec![StepOutput {
+				name: "out".to_string(),
+				schema: schema(Some(version)),
+			}],
+		}
+	}
+
+	fn flow(steps: Vec<FlowStep>) -> DataFlowSpec {
+		DataFlowSpec {
+			product: "orders".to_string(),
+			steps,
+			snapshots: None,
+		}
+	}
+
+	#[test]
+	fn steps_are_ordered_after_their_dependencies() {
+		let spec = flow(vec![
+			step("serve", StepKind::Serving, Some("clean.out"), "v2"),
+			step("clean", StepKind::Transformation, Some("ingest.out"), "v2"),
+			step("ingest", StepKind::Ingestion, None, "v2"),
+		]);
+
+		let flow = resolve(&spec, &schemas()).unwrap();
+
+		assert_eq!(flow.order, vec!["ingest", "clean", "serve"]);
+		assert_eq!(flow.steps[0].depends_on, vec!["clean"]);
+		assert_eq!(flow.steps[2].inputs[0].version, "v2");
+	}
+
+	#[test]
+	fn unset_versions_resolve_to_the_active_version() {
+		let mut ingest = step("ingest", StepKind::Ingestion, None, "v2");
+		ingest.outputs[0].schema = schema(None);
+
+		let flow = resolve(&flow(vec![ingest]), &schemas()).unwrap();
+
+		assert_eq!(flow.steps[0].outputs[0].version, "v2");
+	}
+
+	#[test]
+	fn cycles_are_rejected() {
+		let spec = flow(vec![
+			step("ingest", StepKind::Ingestion, None, "v2"),
+			step("a", StepKind::Transformation, Some("b.out"), "v2"),
+			step("b", StepKind::Transformation, Some("a.out"), "v2"),
+			step("c", StepKind::Serving, Some("b.out"), "v2"),
+		]);
+
+		assert_eq!(resolve(&spec, &schemas()).unwrap_err(), vec!["steps a, b form a cycle"]);
+	}
+
+	#[test]
+	fn dangling_and_mismatched_references_are_rejected() {
+		let spec = flow(vec![
+			step("ingest", StepKind::Ingestion, None, "v1"),
+			step("clean", StepKind::Transformation, Some("ingest.out"), "v2"),
+			step("serve", StepKind::Serving, Some("clean.missing"), "v2"),
+			step("report", StepKind::Aggregation, None, "v3"),
+		]);
+
+		let errors = resolve(&spec, &schemas()).unwrap_err();
+
+		assert_eq!(
+			errors,
+			vec![
+				"step report: in refers to version v3 which DataSchema orders does not have",
+				"step report: out refers to version v3 which DataSchema orders does not have",
+				"step clean: input in expects orders@v2 but ingest.out provides orders@v1",
+				"step serve: input in refers to unknown output clean.missing",
+				"step report: input in needs a from, only ingestion steps read from outside the flow",
+			]
+		);
+	}
+}
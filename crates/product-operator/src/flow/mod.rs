@@ -0,0 +1,3 @@
+pub mod crd;
+pub mod graph;
+pub mod operator;
@@ -0,0 +1,401 @@
+use std::collections::BTreeMap;
+
+use kube::{
+	api::{ListParams, ObjectMeta},
+	Api, Client, ResourceExt,
+};
+use tracing::info;
+
+use dapp_platform::{
+	catalog::{lineage::Derivation, record_id, Catalog},
+	core::{
+		labels::{Labels, PRODUCT_LABEL},
+		metadata::{truncated_name, MAX_LABEL_NAME_LENGTH},
+		operator::{OperatorController, OperatorError},
+		plan::Plan,
+		status::{patch_status, set_condition, Condition, READY},
+	},
+};
+
+use super::{
+	crd::{DataFlow, DataFlowSpec, DataFlowStatus},
+	graph::{resolve, ResolvedFlow},
+};
+use crate::{
+	crd::DappProduct,
+	schema::crd::{DataSchema, SchemaReference},
+	snapshot::crd::{DataSnapshot, DataSnapshotSpec, FLOW_LABEL},
+};
+
+pub struct DataFlowController {
+	name: String,
+	namespace: String,
+	spec: DataFlowSpec,
+	status: DataFlowStatus,
+	client: Client,
+	catalog: Option<Catalog>,
+	plan: Plan,
+}
+
+impl DataFlowController {
+	pub const FINALIZER: &'static str = "dataflows.dappmesh.io/finalizer";
+
+	pub fn new(name: String, namespace: String, resource: &DataFlow, client: Client) -> Self {
+		Self {
+			name,
+			namespace,
+			spec: resource.spec.clone(),
+			status: resource.status.clone().unwrap_or_default(),
+			client,
+			catalog: None,
+			plan: Plan::default(),
+		}
+	}
+
+	/// Records the derivations in `catalog`.
+	pub fn with_catalog(mut self, catalog: Option<Catalog>) -> Self {
+		self.catalog = catalog;
+		self
+	}
+
+	/// The catalog to record the derivations in, none in dry-run mode.
+	fn catalog(&self) -> Option<&Catalog> {
+		self.catalog.as_ref().filter(|_| !self.plan.is_dry_run())
+	}
+
+	/// Resolves the flow against the product and the DataSchemas of the namespace.
+	async fn evaluate(&self) -> Result<Result<ResolvedFlow, Vec<String>>, OperatorError> {
+		let products: Api<DappProduct> = Api::namespaced(self.client.clone(), &self.namespace);
+		let product = products.get_opt(&self.spec.product).await?;
+
+		let schemas: Api<DataSchema> = Api::namespaced(self.client.clone(), &self.namespace);
+		let schemas: BTreeMap<_, _> = schemas
+			.list(&ListParams::default())
+			.await?
+			.into_iter()
+			.map(|schema| (schema.name_any(), schema.spec))
+			.collect();
+
+		let mut result = resolve(&self.spec, &schemas);
+		if product.is_none() {
+			let error = format!("DappProduct {} does not exist", self.spec.product);
+			match &mut result {
+				Ok(_) => result = Err(vec![error]),
+				Err(errors) => errors.insert(0, error),
+			}
+		}
+
+		Ok(result)
+	}
+
+	/// Records the resolved graph, or why the flow cannot be resolved, in `status`.
+	pub fn flow_status(
+		status: &DataFlowStatus,
+		result: Result<ResolvedFlow, Vec<String>>,
+	) -> DataFlowStatus {
+		let mut status = status.clone();
+
+		let condition = match result {
+			Ok(flow) => {
+				status.order = flow.order;
+				status.steps = flow.steps;
+				status.errors = Vec::new();
+				Condition::new(READY, true, "Resolved", "The steps form a valid DAG.")
+			}
+			Err(errors) => {
+				status.order = Vec::new();
+				status.steps = Vec::new();
+				let condition = Condition::new(READY, false, "Invalid", &errors.join("; "));
+				status.errors = errors;
+				condition
+			}
+		};
+		set_condition(&mut status.conditions, condition);
+
+		status
+	}
+
+	/// One snapshot per completed step that produces data, named after the step and the
+	/// flow spec hash so that a changed flow snapshots its steps again. The name is shortened
+	/// to fit the export Job, keeping the hash.
+	pub fn snapshots(
+		name: &str,
+		namespace: &str,
+		spec: &DataFlowSpec,
+		status: &DataFlowStatus,
+		flow: &ResolvedFlow,
+	) -> Vec<DataSnapshot> {
+		let Some(destination) = &spec.snapshots else {
+			return Vec::new();
+		};
+		let hash = spec.hash();
+
+		status
+			.completed_steps
+			.iter()
+			.filter_map(|completed| {
+				let index = flow.order.iter().position(|step| *step == completed.name)?;
+				let step = flow.steps.iter().find(|step| step.name == completed.name)?;
+				let output = step.outputs.first()?;
+				let labels =
+					Labels::labels(vec![(PRODUCT_LABEL, &spec.product), (FLOW_LABEL, name)]);
+
+				let mut snapshot = DataSnapshot::new(
+					&truncated_name(
+						&format!("{}-{}", name, step.name),
+						&hash[..8],
+						MAX_LABEL_NAME_LENGTH,
+					),
+					DataSnapshotSpec {
+						product: spec.product.clone(),
+						flow: name.to_string(),
+						step: step.name.clone(),
+						step_index: index as i32,
+						schema: SchemaReference {
+							name: output.schema.clone(),
+							version: Some(output.version.clone()),
+						},
+						flow_hash: hash.clone(),
+						destination: destination.clone(),
+					},
+				);
+				snapshot.metadata = ObjectMeta {
+					namespace: Some(namespace.to_string()),
+					labels: Some(labels),
+					..snapshot.metadata
+				};
+
+				Some(snapshot)
+			})
+			.collect()
+	}
+
+	/// Every schema version a step writes, derived from every schema version the step reads.
+	pub fn derivations(
+		name: &str,
+		namespace: &str,
+		spec: &DataFlowSpec,
+		flow: &ResolvedFlow,
+	) -> Vec<Derivation> {
+		let mut derivations = Vec::new();
+		for step in &flow.steps {
+			for input in &step.inputs {
+				for output in &step.outputs {
+					if input.schema == output.schema && input.version == output.version {
+						continue;
+					}
+					derivations.push(Derivation {
+						flow: record_id(&[namespace, name]),
+						step: step.name.clone(),
+						product: record_id(&[namespace, &spec.product]),
+						schema: record_id(&[namespace, &input.schema]),
+						version: input.version.clone(),
+						derived: record_id(&[namespace, &output.schema]),
+						derived_version: output.version.clone(),
+					});
+				}
+			}
+		}
+		derivations
+	}
+
+	async fn take_snapshots(&self, flow: &ResolvedFlow) -> Result<(), OperatorError> {
+		let snapshots: Api<DataSnapshot> = Api::namespaced(self.client.clone(), &self.namespace);
+
+		for snapshot in Self::snapshots(&self.name, &self.namespace, &self.spec, &self.status, flow)
+		{
+			if snapshots.get_opt(&snapshot.name_any()).await?.is_none() {
+				info!("Taking snapshot {} of step {}", snapshot.name_any(), snapshot.spec.step);
+				self.plan.create(&snapshots, &snapshot).await?;
+			}
+		}
+
+		Ok(())
+	}
+
+	async fn reconcile_flow(&self) -> Result<(), OperatorError> {
+		let result = self.evaluate().await?;
+		if let Ok(flow) = &result {
+			self.take_snapshots(flow).await?;
+		}
+		if let Some(catalog) = self.catalog() {
+			let derivations = match &result {
+				Ok(flow) => Self::derivations(&self.name, &self.namespace, &self.spec, flow),
+				Err(_) => Vec::new(),
+			};
+			let flow = record_id(&[&self.namespace, &self.name]);
+			catalog.relate_derivations(&flow, &derivations).await?;
+		}
+		let status = Self::flow_status(&self.status, result);
+
+		if status != self.status && !self.plan.is_dry_run() {
+			let api: Api<DataFlow> = Api::namespaced(self.client.clone(), &self.namespace);
+			patch_status(&api, &self.name, &status).await?;
+		}
+
+		Ok(())
+	}
+}
+
+impl OperatorController<DataFlow> for DataFlowController {
+	async fn create_resources(&self) -> Result<(), OperatorError> {
+		self.reconcile_flow().await
+	}
+
+	async fn delete_resources(&self) -> Result<(), OperatorError> {
+		if let Some(catalog) = self.catalog() {
+			catalog.relate_derivations(&record_id(&[&self.namespace, &self.name]), &[]).await?;
+		}
+		Ok(())
+	}
+
+	async fn update_resources(&self) -> Result<(), OperatorError> {
+		self.reconcile_flow().await
+	}
+
+	fn dry_run(mut self, dry_run: bool) -> Self {
+		self.plan = Plan::new(dry_run);
+		self
+	}
+
+	fn plan(&self) -> Plan {
+		self.plan.clone()
+	}
+
+	fn finalizer(&self) -> &str {
+		Self::FINALIZER
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		flow::crd::{CompletedStep, ResolvedPort, ResolvedStep, StepKind},
+		snapshot::crd::{SnapshotDestination, VolumeDestination},
+	};
+
+	fn step(name: &str, outputs: Vec<ResolvedPort>) -> ResolvedStep {
+		ResolvedStep {
+			name: name.to_string(),
+			kind: StepKind::Ingestion,
+			depends_on: vec![],
+			inputs: vec![],
+			outputs,
+		}
+	}
+
+	fn port(schema: &str, version: &str) -> ResolvedPort {
+		ResolvedPort {
+			name: schema.to_string(),
+			schema: schema.to_string(),
+			version: version.to_string(),
+		}
+	}
+
+	#[test]
+	fn invalid_flow_clears_the_resolved_graph() {
+		let flow = ResolvedFlow {
+			order: vec!["ingest".to_string()],
+			steps: vec![step("ingest", vec![])],
+		};
+		let resolved = DataFlowController::flow_status(&Default::default(), Ok(flow));
+
+		let errors = vec!["steps a, b form a cycle".to_string()];
+		let invalid = DataFlowController::flow_status(&resolved, Err(errors.clone()));
+
+		assert_eq!(resolved.order, vec!["ingest"]);
+		assert!(resolved.conditions[0].is_true());
+		assert!(invalid.steps.is_empty());
+		assert_eq!(invalid.errors, errors);
+		assert_eq!(invalid.conditions[0].reason, "Invalid");
+	}
+
+	#[test]
+	fn completed_steps_with_outputs_are_snapshotted() {
+		let output = ResolvedPort {
+			name: "out".to_string(),
+			schema: "orders".to_string(),
+			version: "v2".to_string(),
+		};
+		let flow = ResolvedFlow {
+			order: vec!["ingest".to_string(), "serve".to_string()],
+			steps: vec![step("ingest", vec![output]), step("serve", vec![])],
+		};
+		let mut spec = DataFlowSpec {
+			product: "orders".to_string(),
+			steps: vec![],
+			snapshots: None,
+		};
+		let status = DataFlowStatus {
+			completed_steps: ["ingest", "serve"]
+				.map(|name| CompletedStep {
+					name: name.to_string(),
+					completed_at: "2024-05-01T00:00:00Z".to_string(),
+				})
+				.to_vec(),
+			..DataFlowStatus::default()
+		};
+
+		let disabled = DataFlowController::snapshots("daily", "shop", &spec, &status, &flow);
+		spec.snapshots = Some(SnapshotDestination {
+			volume: Some(VolumeDestination {
+				claim_name: "snapshots".to_string(),
+				path: String::new(),
+			}),
+			bucket: None,
+		});
+		let snapshots = DataFlowController::snapshots("daily", "shop", &spec, &status, &flow);
+
+		assert!(disabled.is_empty());
+		assert_eq!(snapshots.len(), 1);
+		assert_eq!(snapshots[0].name_any(), format!("daily-ingest-{}", &spec.hash()[..8]));
+		assert_eq!(snapshots[0].spec.step_index, 0);
+		assert_eq!(snapshots[0].spec.schema.version.as_deref(), Some("v2"));
+		assert_eq!(snapshots[0].labels()[FLOW_LABEL], "daily");
+
+		let long = "daily".repeat(40);
+		let snapshots = DataFlowController::snapshots(&long, "shop", &spec, &status, &flow);
+		assert_eq!(snapshots[0].name_any().len(), MAX_LABEL_NAME_LENGTH);
+		assert!(snapshots[0].name_any().ends_with(&spec.hash()[..8]));
+	}
+
+	#[test]
+	fn steps_derive_their_outputs_from_their_inputs() {
+		let mut enrich = step("enrich", vec![port("orders", "v2"), port("raw", "v1")]);
+		enrich.inputs = vec![port("raw", "v1"), port("customers", "v3")];
+		let flow = ResolvedFlow {
+			order: vec!["ingest".to_string(), "enrich".to_string()],
+			steps: vec![step("ingest", vec![port("raw", "v1")]), enrich],
+		};
+		let spec = DataFlowSpec {
+			product: "orders".to_string(),
+			steps: vec![],
+			snapshots: None,
+		};
+
+		let derivations = DataFlowController::derivations("daily", "shop", &spec, &flow);
+
+		let edges: Vec<(&str, &str, &str, &str)> = derivations
+			.iter()
+			.map(|derivation| {
+				(
+					derivation.schema.as_str(),
+					derivation.version.as_str(),
+					derivation.derived.as_str(),
+					derivation.derived_version.as_str(),
+				)
+			})
+			.collect();
+		assert_eq!(
+			edges,
+			vec![
+				("shop/raw", "v1", "shop/orders", "v2"),
+				("shop/customers", "v3", "shop/orders", "v2"),
+				("shop/customers", "v3", "shop/raw", "v1"),
+			]
+		);
+		assert_eq!(derivations[0].flow, "shop/daily");
+		assert_eq!(derivations[0].product, "shop/orders");
+	}
+}
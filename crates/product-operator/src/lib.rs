@@ -1,5 +1,10 @@
+pub mod contract;
 pub mod crd;
+pub mod flow;
 pub mod operator;
+pub mod port;
+pub mod schema;
+pub mod snapshot;
 
 #[cfg(test)]
 mod tests;
@@ -1,12 +1,40 @@
 use kube::{runtime::controller::Action, ResourceExt};
 use std::sync::Arc;
+use tracing::error;
 
-use dapp_platform::core::operator::{Operator, OperatorContext, OperatorController, OperatorError};
-use dapp_product_operator::{crd::DappProduct, operator::ProductOperatorController};
+use dapp_platform::{
+	catalog::Catalog,
+	core::{
+		operator::{Operator, OperatorContext, OperatorController, OperatorError},
+		webhook::{WebhookServer, DEFAULT_WEBHOOK_PORT},
+	},
+};
+use dapp_product_operator::{
+	contract::{crd::DataContract, operator::DataContractController},
+	crd::{convert, DappProduct, CONVERSION_PATH},
+	flow::{crd::DataFlow, operator::DataFlowController},
+	operator::ProductOperatorController,
+	port::{
+		crd::{InputPort, OutputPort},
+		input::InputPortController,
+		output::OutputPortController,
+	},
+	schema::{crd::DataSchema, operator::DataSchemaController},
+	snapshot::{crd::DataSnapshot, operator::DataSnapshotController},
+};
 
 #[tokio::main]
 async fn main() {
-	Operator::run(reconcile).await;
+	tokio::join!(
+		Operator::run(reconcile),
+		Operator::run(reconcile_schema),
+		Operator::run(reconcile_contract),
+		Operator::run(reconcile_flow),
+		Operator::run(reconcile_snapshot),
+		Operator::run(reconcile_input_port),
+		Operator::run(reconcile_output_port),
+		serve_webhooks()
+	);
 }
 
 async fn reconcile(
@@ -14,10 +42,126 @@ async fn reconcile(
 	context: Arc<OperatorContext>,
 ) -> Result<Action, OperatorError> {
 	if let Some(namespace) = resource.namespace() {
-		let controller =
-			ProductOperatorController::new(resource.name_any(), namespace, context.client.clone());
+		let controller = ProductOperatorController::new(
+			resource.name_any(),
+			namespace,
+			&resource.spec,
+			context.client.clone(),
+		);
 		controller.reconcile(resource, context).await
 	} else {
 		Err(OperatorError::UserInputError("Expected resource to be namespaced.".to_string()))
 	}
 }
+
+async fn reconcile_schema(
+	resource: Arc<DataSchema>,
+	context: Arc<OperatorContext>,
+) -> Result<Action, OperatorError> {
+	if let Some(namespace) = resource.namespace() {
+		let controller = DataSchemaController::new(
+			resource.name_any(),
+			namespace,
+			&resource,
+			context.client.clone(),
+		);
+		controller.reconcile(resource, context).await
+	} else {
+		Err(OperatorError::UserInputError("Expected resource to be namespaced.".to_string()))
+	}
+}
+
+async fn reconcile_contract(
+	resource: Arc<DataContract>,
+	context: Arc<OperatorContext>,
+) -> Result<Action, OperatorError> {
+	if let Some(namespace) = resource.namespace() {
+		let controller = DataContractController::new(
+			resource.name_any(),
+			namespace,
+			&resource,
+			context.client.clone(),
+		);
+		controller.reconcile(resource, context).await
+	} else {
+		Err(OperatorError::UserInputError("Expected resource to be namespaced.".to_string()))
+	}
+}
+
+async fn reconcile_flow(
+	resource: Arc<DataFlow>,
+	context: Arc<OperatorContext>,
+) -> Result<Action, OperatorError> {
+	if let Some(namespace) = resource.namespace() {
+		let controller = DataFlowController::new(
+			resource.name_any(),
+			namespace,
+			&resource,
+			context.client.clone(),
+		)
+		.with_catalog(Catalog::from_env()?);
+		controller.reconcile(resource, context).await
+	} else {
+		Err(OperatorError::UserInputError("Expected resource to be namespaced.".to_string()))
+	}
+}
+
+async fn reconcile_snapshot(
+	resource: Arc<DataSnapshot>,
+	context: Arc<OperatorContext>,
+) -> Result<Action, OperatorError> {
+	if let Some(namespace) = resource.namespace() {
+		let controller = DataSnapshotController::new(
+			resource.name_any(),
+			namespace,
+			&resource,
+			context.client.clone(),
+		);
+		controller.reconcile(resource, context).await
+	} else {
+		Err(OperatorError::UserInputError("Expected resource to be namespaced.".to_string()))
+	}
+}
+
+async fn reconcile_input_port(
+	resource: Arc<InputPort>,
+	context: Arc<OperatorContext>,
+) -> Result<Action, OperatorError> {
+	if let Some(namespace) = resource.namespace() {
+		let controller = InputPortController::new(
+			resource.name_any(),
+			namespace,
+			&resource,
+			context.client.clone(),
+		)
+		.with_catalog(Catalog::from_env()?);
+		controller.reconcile(resource, context).await
+	} else {
+		Err(OperatorError::UserInputError("Expected resource to be namespaced.".to_string()))
+	}
+}
+
+async fn reconcile_output_port(
+	resource: Arc<OutputPort>,
+	context: Arc<OperatorContext>,
+) -> Result<Action, OperatorError> {
+	if let Some(namespace) = resource.namespace() {
+		let controller = OutputPortController::new(
+			resource.name_any(),
+			namespace,
+			&resource,
+			context.client.clone(),
+		);
+		controller.reconcile(resource, context).await
+	} else {
+		Err(OperatorError::UserInputError("Expected resource to be namespaced.".to_string()))
+	}
+}
+
+/// Serves the conversion webhook of the CRD, leaving the operator running if it cannot start.
+async fn serve_webhooks() {
+	let webhooks = WebhookServer::new(DEFAULT_WEBHOOK_PORT).conversion(CONVERSION_PATH, convert);
+	if let Err(error) = webhooks.run().await {
+		error!("Webhook server stopped: {}", error);
+	}
+}
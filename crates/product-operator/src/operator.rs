@@ -1,30 +1,74 @@
+use k8s_openapi::api::networking::v1::NetworkPolicyPeer;
 use kube::Client;
 
 use dapp_platform::{
-	core::operator::{OperatorController, OperatorError},
-	surrealdb::app::SurrealDBApp,
+	core::{
+		constants::{DOMAIN_OPERATOR, OPERATOR_NAMESPACE, PRODUCT_OPERATOR},
+		hierarchy::{Hierarchy, Level},
+		labels::PRODUCT_LABEL,
+		network_policy::pod_peer,
+		operator::{OperatorController, OperatorError},
+		plan::Plan,
+	},
+	surrealdb::{app::SurrealDBApp, config::SurrealDBConfig},
 };
 
-use crate::crd::DappProduct;
+use crate::crd::{DappProduct, DappProductSpec};
 
 pub struct ProductOperatorController {
 	pub surrealdb_app: SurrealDBApp,
+	pub hierarchy: Hierarchy,
 }
 
 impl ProductOperatorController {
 	pub const FINALIZER: &'static str = "dappproducts.dappmesh.io/finalizer";
 
-	pub fn new(name: String, namespace: String, client: Client) -> Self {
+	pub fn new(name: String, namespace: String, spec: &DappProductSpec, client: Client) -> Self {
+		let peers = Self::storage_peers(&name, spec);
+		let hierarchy = Hierarchy::new(
+			client.clone(),
+			Level::Product,
+			&name,
+			&namespace,
+			spec.domain_ref.clone(),
+		);
+		let config = Self::database_config(&name, &namespace, spec).with_network_policy(peers);
+
 		Self {
-			surrealdb_app: SurrealDBApp::new(name, namespace, client),
+			surrealdb_app: SurrealDBApp::with_config(config, client),
+			hierarchy,
 		}
 	}
+
+	/// The storage of the DappProduct `name`, also used by the controllers of its resources to
+	/// reach it.
+	pub fn database_config(name: &str, namespace: &str, spec: &DappProductSpec) -> SurrealDBConfig {
+		SurrealDBConfig::new(SurrealDBApp::db_name(name), namespace.to_string())
+			.with_security_overrides(spec.security_context.as_ref())
+			.with_spec(spec.database.as_ref())
+	}
+
+	/// The product's own workloads, the operators managing it and the declared consumers.
+	fn storage_peers(name: &str, spec: &DappProductSpec) -> Vec<NetworkPolicyPeer> {
+		let mut peers = vec![
+			pod_peer(vec![(PRODUCT_LABEL, name)], None),
+			pod_peer(vec![("app", DOMAIN_OPERATOR)], Some(OPERATOR_NAMESPACE)),
+			pod_peer(vec![("app", PRODUCT_OPERATOR)], Some(OPERATOR_NAMESPACE)),
+		];
+
+		peers.extend(spec.consumers.iter().map(|consumer| {
+			pod_peer(vec![(PRODUCT_LABEL, &consumer.name)], consumer.namespace.as_deref())
+		}));
+
+		peers
+	}
 }
 
 impl OperatorController<DappProduct> for ProductOperatorController {
 	async fn create_resources(&self) -> Result<(), OperatorError> {
+		self.hierarchy.ensure_parent_ready().await?;
 		self.surrealdb_app.create().await?;
-		Ok(())
+		self.hierarchy.publish_ready().await
 	}
 
 	async fn delete_resources(&self) -> Result<(), OperatorError> {
@@ -32,6 +76,21 @@ impl OperatorController<DappProduct> for ProductOperatorController {
 		Ok(())
 	}
 
+	async fn update_resources(&self) -> Result<(), OperatorError> {
+		self.surrealdb_app.update().await?;
+		self.hierarchy.publish_ready().await
+	}
+
+	fn dry_run(mut self, dry_run: bool) -> Self {
+		self.surrealdb_app = self.surrealdb_app.dry_run(dry_run);
+		self.hierarchy = self.hierarchy.dry_run(dry_run);
+		self
+	}
+
+	fn plan(&self) -> Plan {
+		self.surrealdb_app.plan().clone()
+	}
+
 	fn finalizer(&self) -> &str {
 		Self::FINALIZER
 	}
@@ -40,7 +99,9 @@ impl OperatorController<DappProduct> for ProductOperatorController {
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use crate::crd::ProductConsumer;
 	use dapp_platform::core::operator::OperatorAction;
+	use dapp_platform::{core::security::SecurityContextOverrides, surrealdb::spec::SurrealDBSpec};
 	use std::sync::Arc;
 
 	#[tokio::test]
@@ -70,4 +131,45 @@ mod tests {
 
 		matches!(action, OperatorAction::NoOp);
 	}
+
+	#[test]
+	fn database_config_follows_the_product_overrides() {
+		let mut spec = DappProductSpec::default();
+		spec.security_context = Some(SecurityContextOverrides {
+			run_as_user: Some(2000),
+			..SecurityContextOverrides::default()
+		});
+		spec.database = Some(SurrealDBSpec {
+			replicas: Some(3),
+			..SurrealDBSpec::default()
+		});
+
+		let config = ProductOperatorController::database_config("orders", "shop", &spec);
+
+		assert_eq!(config.name, SurrealDBApp::db_name("orders"));
+		assert_eq!(config.statefulset.security.run_as_user, 2000);
+		assert_eq!(config.statefulset.replicas, 3);
+	}
+
+	#[test]
+	fn storage_peers_include_declared_consumers() {
+		let mut spec = DappProductSpec::default();
+		spec.consumers = vec![
+			ProductConsumer {
+				name: "forecast".to_string(),
+				namespace: None,
+			},
+			ProductConsumer {
+				name: "campaign".to_string(),
+				namespace: Some("marketing".to_string()),
+			},
+		];
+
+		let peers = ProductOperatorController::storage_peers("orders", &spec);
+
+		assert_eq!(peers.len(), 5);
+		assert_eq!(peers[0], pod_peer(vec![(PRODUCT_LABEL, "orders")], None));
+		assert_eq!(peers[3], pod_peer(vec![(PRODUCT_LABEL, "forecast")], None));
+		assert_eq!(peers[4], pod_peer(vec![(PRODUCT_LABEL, "campaign")], Some("marketing")));
+	}
 }
@@ -0,0 +1,196 @@
+use kube::CustomResource;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use dapp_platform::core::{
+	operator::OperatorResource,
+	status::Condition,
+	validation::{schema, Rule, Validated, DNS_LABEL, DNS_SUBDOMAIN},
+};
+
+use crate::crd::ProductConsumer;
+
+pub const DEFAULT_INGRESS_CONTROLLER_NAMESPACE: &str = "ingress-nginx";
+
+/// Where a product reads its data from.
+#[derive(CustomResource, Serialize, Deserialize, Debug, PartialEq, Eq, Clone, JsonSchema)]
+#[kube(
+	doc = "DappMesh input port of a product.",
+	group = "dappmesh.io",
+	version = "v1alpha1",
+	kind = "InputPort",
+	namespaced,
+	singular = "inputport",
+	plural = "inputports",
+	shortname = "iport",
+	status = "PortStatus",
+	derive = "PartialEq"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct InputPortSpec {
+	/// Name of the consuming DappProduct in the same namespace.
+	#[schemars(regex = "DNS_SUBDOMAIN", length(max = 253))]
+	pub product: String,
+	#[schemars(schema_with = "schema::<InputSource>")]
+	pub source: InputSource,
+	pub protocol: PortProtocol,
+	/// The DataContract governing the data read through the port.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub contract: Option<ContractReference>,
+}
+
+/// Exactly one of `product` or `external`.
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+pub struct InputSource {
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub product: Option<UpstreamPort>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub external: Option<ExternalSource>,
+}
+
+impl Validated for InputSource {
+	const RULES: &'static [Rule] = &[Rule::new(
+		"has(self.product) != has(self.external)",
+		"exactly one of product or external has to be set",
+	)];
+}
+
+/// An OutputPort of another product.
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+pub struct UpstreamPort {
+	/// Name of the OutputPort.
+	pub port: String,
+	/// Defaults to the namespace of the input port.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schemars(regex = "DNS_LABEL", length(max = 63))]
+	pub namespace: Option<String>,
+}
+
+/// A source outside the mesh, read through a connector.
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+pub struct ExternalSource {
+	/// Connector specific location, e.g. a JDBC URL or a Kafka topic.
+	pub uri: String,
+}
+
+/// How the data of a product is served to its consumers.
+#[derive(CustomResource, Serialize, Deserialize, Debug, PartialEq, Eq, Clone, JsonSchema)]
+#[kube(
+	doc = "DappMesh output port of a product.",
+	group = "dappmesh.io",
+	version = "v1alpha1",
+	kind = "OutputPort",
+	namespaced,
+	singular = "outputport",
+	plural = "outputports",
+	shortname = "oport",
+	status = "PortStatus",
+	derive = "PartialEq"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputPortSpec {
+	/// Name of the serving DappProduct in the same namespace.
+	#[schemars(regex = "DNS_SUBDOMAIN", length(max = 253))]
+	pub product: String,
+	/// One of `GraphQL`, `Rest` or `Grpc`.
+	pub protocol: PortProtocol,
+	/// Container port of the pods serving the endpoint. The port routes to the pods labelled
+	/// `dappmesh.io/serving: <product>`, which the product's serving workloads have to carry.
+	#[schemars(range(min = 1, max = 65535))]
+	pub port: i32,
+	/// The DataContract in the same namespace governing the served data.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub contract: Option<String>,
+	/// Products allowed to reach the endpoint from within the cluster, all when empty.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub consumers: Vec<ProductConsumer>,
+	/// Exposes the endpoint outside the cluster.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub ingress: Option<PortIngress>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PortIngress {
+	pub host: String,
+	/// Defaults to `/`.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schemars(regex(pattern = "^/"))]
+	pub path: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub class_name: Option<String>,
+	/// Secret holding the TLS certificate of `host`.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub tls_secret_name: Option<String>,
+	/// Namespace of the ingress controller pods, admitted next to the consumers. Defaults
+	/// to `ingress-nginx`.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub controller_namespace: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, JsonSchema)]
+pub enum PortProtocol {
+	GraphQL,
+	Rest,
+	Grpc,
+	Kafka,
+	Sql,
+	ObjectStorage,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+pub struct ContractReference {
+	pub name: String,
+	/// Defaults to the namespace of the upstream product, or of the port itself.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schemars(regex = "DNS_LABEL", length(max = 63))]
+	pub namespace: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PortStatus {
+	/// Where the port's data is served or read from.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub url: Option<String>,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub errors: Vec<String>,
+	#[serde(default)]
+	pub conditions: Vec<Condition>,
+}
+
+impl PortProtocol {
+	/// Whether output ports can serve the protocol.
+	pub fn is_servable(&self) -> bool {
+		matches!(self, Self::GraphQL | Self::Rest | Self::Grpc)
+	}
+
+	fn scheme(&self) -> &str {
+		match self {
+			Self::Grpc => "grpc",
+			_ => "http",
+		}
+	}
+}
+
+impl OutputPortSpec {
+	/// Name of the Service, Ingress and NetworkPolicy of the port.
+	pub fn service_name(&self, port: &str) -> String {
+		format!("{}-{}", self.product, port)
+	}
+
+	/// The in-cluster URL of the endpoint.
+	pub fn url(&self, port: &str, namespace: &str) -> String {
+		format!(
+			"{}://{}.{}.svc:{}",
+			self.protocol.scheme(),
+			self.service_name(port),
+			namespace,
+			self.port
+		)
+	}
+}
+
+impl OperatorResource for InputPort {}
+
+impl OperatorResource for OutputPort {}
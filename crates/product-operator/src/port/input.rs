@@ -0,0 +1,260 @@
+use kube::{Api, Client};
+
+use dapp_platform::{
+	catalog::{lineage::Feed, record_id, Catalog},
+	core::{
+		operator::{OperatorController, OperatorError},
+		plan::Plan,
+		status::patch_status,
+	},
+};
+
+use super::{
+	crd::{InputPort, InputPortSpec, OutputPort, OutputPortSpec, PortStatus},
+	port_status,
+};
+use crate::{
+	contract::crd::DataContract,
+	crd::DappProduct,
+	schema::crd::{DataSchema, SchemaReference},
+};
+
+pub struct InputPortController {
+	name: String,
+	namespace: String,
+	spec: InputPortSpec,
+	status: PortStatus,
+	client: Client,
+	catalog: Option<Catalog>,
+	plan: Plan,
+}
+
+impl InputPortController {
+	pub const FINALIZER: &'static str = "inputports.dappmesh.io/finalizer";
+
+	pub fn new(name: String, namespace: String, resource: &InputPort, client: Client) -> Self {
+		Self {
+			name,
+			namespace,
+			spec: resource.spec.clone(),
+			status: resource.status.clone().unwrap_or_default(),
+			client,
+			catalog: None,
+			plan: Plan::default(),
+		}
+	}
+
+	/// Records the feed in `catalog`.
+	pub fn with_catalog(mut self, catalog: Option<Catalog>) -> Self {
+		self.catalog = catalog;
+		self
+	}
+
+	/// The catalog to record the feed in, none in dry-run mode.
+	fn catalog(&self) -> Option<&Catalog> {
+		self.catalog.as_ref().filter(|_| !self.plan.is_dry_run())
+	}
+
+	/// Whether `upstream`, an output port in `upstream_namespace`, admits `product`.
+	pub fn admits(
+		upstream: &OutputPortSpec,
+		upstream_namespace: &str,
+		product: &str,
+		namespace: &str,
+	) -> bool {
+		upstream.consumers.is_empty()
+			|| upstream.consumers.iter().any(|consumer| {
+				consumer.name == product
+					&& consumer.namespace.as_deref().unwrap_or(upstream_namespace) == namespace
+			})
+	}
+
+	/// The namespace of the upstream port, which also holds its contract.
+	fn upstream_namespace(&self) -> &str {
+		let upstream = self.spec.source.product.as_ref();
+		upstream.and_then(|upstream| upstream.namespace.as_deref()).unwrap_or(&self.namespace)
+	}
+
+	/// Resolves the source of the port to a URL and, for an upstream product, the feed of its
+	/// data, collecting what prevents the port from working.
+	async fn resolve(&self) -> Result<(Option<String>, Option<Feed>, Vec<String>), OperatorError> {
+		let mut errors = Vec::new();
+		let mut feed = None;
+		let mut upstream_contract = None;
+
+		let products: Api<DappProduct> = Api::namespaced(self.client.clone(), &self.namespace);
+		if products.get_opt(&self.spec.product).await?.is_none() {
+			errors.push(format!("DappProduct {} does not exist", self.spec.product));
+		}
+
+		let url = match (&self.spec.source.product, &self.spec.source.external) {
+			(Some(upstream), None) => {
+				let namespace = self.upstream_namespace();
+				let ports: Api<OutputPort> = Api::namespaced(self.client.clone(), namespace);
+
+				match ports.get_opt(&upstream.port).await? {
+					None => {
+						errors.push(format!(
+							"OutputPort {}/{} does not exist",
+							namespace, upstream.port
+						));
+						None
+					}
+					Some(port) => {
+						if port.spec.protocol != self.spec.protocol {
+							errors.push(format!(
+								"OutputPort {}/{} serves {:?}, not {:?}",
+								namespace, upstream.port, port.spec.protocol, self.spec.protocol
+							));
+						}
+						if !Self::admits(&port.spec, namespace, &self.spec.product, &self.namespace)
+						{
+							errors.push(format!(
+								"OutputPort {}/{} does not list {}/{} as a consumer",
+								namespace, upstream.port, self.namespace, self.spec.product
+							));
+						}
+						feed = Some(Feed {
+							port: record_id(&[&self.namespace, &self.name]),
+							product: record_id(&[&self.namespace, &self.spec.product]),
+							source: record_id(&[namespace, &upstream.port]),
+							upstream: record_id(&[namespace, &port.spec.product]),
+							contract: None,
+							schema: None,
+							schema_version: None,
+						});
+						upstream_contract = port.spec.contract.clone();
+						Some(port.spec.url(&upstream.port, namespace))
+					}
+				}
+			}
+			(None, Some(external)) => Some(external.uri.clone()),
+			_ => {
+				errors.push("source needs exactly one of product or external".to_string());
+				None
+			}
+		};
+
+		// The data is governed by the contract of the port, or else by that of the upstream port.
+		let contract = match &self.spec.contract {
+			Some(contract) => Some((
+				contract.namespace.as_deref().unwrap_or(self.upstream_namespace()),
+				contract.name.clone(),
+			)),
+			None => upstream_contract.map(|name| (self.upstream_namespace(), name)),
+		};
+		if let Some((namespace, name)) = contract {
+			let contracts: Api<DataContract> = Api::namespaced(self.client.clone(), namespace);
+			match contracts.get_opt(&name).await? {
+				None if self.spec.contract.is_some() => {
+					errors.push(format!("DataContract {}/{} does not exist", namespace, name));
+				}
+				None => {}
+				Some(contract) => {
+					if let Some(feed) = &mut feed {
+						let schema = &contract.spec.schema_ref;
+						feed.contract = Some(record_id(&[namespace, &name]));
+						feed.schema = Some(record_id(&[namespace, &schema.name]));
+						feed.schema_version = self.schema_version(namespace, schema).await?;
+					}
+				}
+			}
+		}
+
+		Ok((url, feed, errors))
+	}
+
+	/// The version `schema` refers to, which defaults to the active version of the schema.
+	async fn schema_version(
+		&self,
+		namespace: &str,
+		schema: &SchemaReference,
+	) -> Result<Option<String>, OperatorError> {
+		if let Some(version) = &schema.version {
+			return Ok(Some(version.clone()));
+		}
+		let schemas: Api<DataSchema> = Api::namespaced(self.client.clone(), namespace);
+		Ok(schemas.get_opt(&schema.name).await?.map(|schema| schema.spec.active_version))
+	}
+
+	async fn reconcile_port(&self) -> Result<(), OperatorError> {
+		let (url, feed, errors) = self.resolve().await?;
+		// Only ports that work feed the product.
+		let feed = feed.filter(|_| errors.is_empty());
+		if let Some(catalog) = self.catalog() {
+			catalog.relate_feed(&record_id(&[&self.namespace, &self.name]), feed.as_ref()).await?;
+		}
+
+		let status = port_status(&self.status, url.unwrap_or_default(), errors);
+		if status != self.status && !self.plan.is_dry_run() {
+			let api: Api<InputPort> = Api::namespaced(self.client.clone(), &self.namespace);
+			patch_status(&api, &self.name, &status).await?;
+		}
+
+		Ok(())
+	}
+}
+
+impl OperatorController<InputPort> for InputPortController {
+	async fn create_resources(&self) -> Result<(), OperatorError> {
+		self.reconcile_port().await
+	}
+
+	async fn delete_resources(&self) -> Result<(), OperatorError> {
+		if let Some(catalog) = self.catalog() {
+			catalog.relate_feed(&record_id(&[&self.namespace, &self.name]), None).await?;
+		}
+		Ok(())
+	}
+
+	async fn update_resources(&self) -> Result<(), OperatorError> {
+		self.reconcile_port().await
+	}
+
+	fn dry_run(mut self, dry_run: bool) -> Self {
+		self.plan = Plan::new(dry_run);
+		self
+	}
+
+	fn plan(&self) -> Plan {
+		self.plan.clone()
+	}
+
+	fn finalizer(&self) -> &str {
+		Self::FINALIZER
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{crd::ProductConsumer, port::crd::PortProtocol};
+
+	fn upstream(consumers: Vec<ProductConsumer>) -> OutputPortSpec {
+		OutputPortSpec {
+			product: "orders".to_string(),
+			protocol: PortProtocol::Rest,
+			port: 8080,
+			contract: None,
+			consumers,
+			ingress: None,
+		}
+	}
+
+	#[test]
+	fn upstream_without_consumers_admits_everyone() {
+		assert!(InputPortController::admits(&upstream(vec![]), "shop", "forecast", "planning"));
+	}
+
+	#[test]
+	fn consumers_default_to_the_namespace_of_the_upstream_port() {
+		let upstream = upstream(vec![ProductConsumer {
+			name: "forecast".to_string(),
+			namespace: None,
+		}]);
+
+		assert!(InputPortController::admits(&upstream, "shop", "forecast", "shop"));
+		assert!(!InputPortController::admits(&upstream, "shop", "forecast", "planning"));
+		assert!(!InputPortController::admits(&upstream, "shop", "billing", "shop"));
+	}
+}
@@ -0,0 +1,23 @@
+pub mod crd;
+pub mod input;
+pub mod output;
+
+use dapp_platform::core::status::{set_condition, Condition, READY};
+
+use self::crd::PortStatus;
+
+/// Records the outcome of a validation in `status`, the URL is only kept for valid ports.
+pub fn port_status(status: &PortStatus, url: String, errors: Vec<String>) -> PortStatus {
+	let mut status = status.clone();
+	let condition = if errors.is_empty() {
+		Condition::new(READY, true, "Provisioned", &format!("Port is available at {}.", url))
+	} else {
+		Condition::new(READY, false, "Invalid", &errors.join("; "))
+	};
+
+	status.url = Some(url).filter(|_| errors.is_empty());
+	status.errors = errors;
+	set_condition(&mut status.conditions, condition);
+
+	status
+}
@@ -0,0 +1,318 @@
+use k8s_openapi::{
+	api::{
+		core::v1::Service,
+		networking::v1::{Ingress, NetworkPolicy, NetworkPolicyPeer},
+	},
+	apimachinery::pkg::apis::meta::v1::LabelSelector,
+};
+use kube::{Api, Client};
+
+use dapp_platform::core::{
+	ingress::IngressBuilder,
+	labels::{PRODUCT_LABEL, SERVING_LABEL},
+	network_policy::{namespace_peer, pod_peer, NetworkPolicyBuilder},
+	operator::{OperatorController, OperatorError},
+	plan::Plan,
+	service::ServiceBuilder,
+	status::patch_status,
+};
+
+use super::{
+	crd::{OutputPort, OutputPortSpec, PortStatus, DEFAULT_INGRESS_CONTROLLER_NAMESPACE},
+	port_status,
+};
+use crate::{contract::crd::DataContract, crd::DappProduct};
+
+pub struct OutputPortController {
+	name: String,
+	namespace: String,
+	spec: OutputPortSpec,
+	status: PortStatus,
+	client: Client,
+	plan: Plan,
+}
+
+impl OutputPortController {
+	pub const FINALIZER: &'static str = "outputports.dappmesh.io/finalizer";
+
+	pub fn new(name: String, namespace: String, resource: &OutputPort, client: Client) -> Self {
+		Self {
+			name,
+			namespace,
+			spec: resource.spec.clone(),
+			status: resource.status.clone().unwrap_or_default(),
+			client,
+			plan: Plan::default(),
+		}
+	}
+
+	/// Routes to the pods labelled as serving the product, see [`SERVING_LABEL`].
+	pub fn service(name: &str, namespace: &str, spec: &OutputPortSpec) -> Service {
+		ServiceBuilder::default()
+			.metadata(&spec.service_name(name), namespace)
+			.service_spec("TCP", spec.port)
+			.selector(vec![(SERVING_LABEL, &spec.product)])
+			.manifest()
+	}
+
+	/// Admits the declared consumers and the ingress controller to the port, or every pod
+	/// of the cluster when no consumers are declared. Each port gets a policy of its own as
+	/// policies selecting the same pods add up.
+	pub fn network_policy(name: &str, namespace: &str, spec: &OutputPortSpec) -> NetworkPolicy {
+		let mut peers: Vec<NetworkPolicyPeer> = spec
+			.consumers
+			.iter()
+			.map(|consumer| {
+				pod_peer(vec![(PRODUCT_LABEL, &consumer.name)], consumer.namespace.as_deref())
+			})
+			.collect();
+
+		if peers.is_empty() {
+			peers.push(NetworkPolicyPeer {
+				namespace_selector: Some(LabelSelector::default()),
+				..NetworkPolicyPeer::default()
+			});
+		} else if let Some(ingress) = &spec.ingress {
+			let controller = ingress
+				.controller_namespace
+				.as_deref()
+				.unwrap_or(DEFAULT_INGRESS_CONTROLLER_NAMESPACE);
+			peers.push(namespace_peer(controller));
+		}
+
+		NetworkPolicyBuilder::default()
+			.metadata(&spec.service_name(name), &spec.product, namespace)
+			.ingress_spec(vec![(SERVING_LABEL, &spec.product)], peers, spec.port)
+			.manifest()
+	}
+
+	pub fn ingress(name: &str, namespace: &str, spec: &OutputPortSpec) -> Option<Ingress> {
+		let ingress = spec.ingress.as_ref()?;
+		let service = spec.service_name(name);
+
+		Some(
+			IngressBuilder::default()
+				.metadata(&service, &spec.product, namespace)
+				.rule_spec(
+					&ingress.host,
+					ingress.path.as_deref().unwrap_or("/"),
+					&service,
+					spec.port,
+					ingress.class_name.as_deref(),
+					ingress.tls_secret_name.as_deref(),
+				)
+				.manifest(),
+		)
+	}
+
+	async fn validate(&self) -> Result<Vec<String>, OperatorError> {
+		let mut errors = Vec::new();
+
+		if !self.spec.protocol.is_servable() {
+			errors.push(format!(
+				"output ports serve GraphQL, Rest or Grpc, not {:?}",
+				self.spec.protocol
+			));
+		}
+
+		let products: Api<DappProduct> = Api::namespaced(self.client.clone(), &self.namespace);
+		if products.get_opt(&self.spec.product).await?.is_none() {
+			errors.push(format!("DappProduct {} does not exist", self.spec.product));
+		}
+
+		if let Some(contract) = &self.spec.contract {
+			let contracts: Api<DataContract> =
+				Api::namespaced(self.client.clone(), &self.namespace);
+			if contracts.get_opt(contract).await?.is_none() {
+				errors.push(format!("DataContract {} does not exist", contract));
+			}
+		}
+
+		Ok(errors)
+	}
+
+	async fn provision(&self) -> Result<(), OperatorError> {
+		let (name, namespace) = (self.name.as_str(), self.namespace.as_str());
+
+		let services: Api<Service> = Api::namespaced(self.client.clone(), namespace);
+		self.plan.apply(&services, &Self::service(name, namespace, &self.spec)).await?;
+
+		let policies: Api<NetworkPolicy> = Api::namespaced(self.client.clone(), namespace);
+		self.plan.apply(&policies, &Self::network_policy(name, namespace, &self.spec)).await?;
+
+		let ingresses: Api<Ingress> = Api::namespaced(self.client.clone(), namespace);
+		match Self::ingress(name, namespace, &self.spec) {
+			Some(ingress) => self.plan.apply(&ingresses, &ingress).await?,
+			None => self.plan.delete(&ingresses, &self.spec.service_name(name)).await?,
+		}
+
+		Ok(())
+	}
+
+	async fn reconcile_port(&self) -> Result<(), OperatorError> {
+		let errors = self.validate().await?;
+
+		if errors.is_empty() {
+			self.provision().await?;
+		}
+
+		let url = self.spec.url(&self.name, &self.namespace);
+		let status = port_status(&self.status, url, errors);
+		if status != self.status && !self.plan.is_dry_run() {
+			let api: Api<OutputPort> = Api::namespaced(self.client.clone(), &self.namespace);
+			patch_status(&api, &self.name, &status).await?;
+		}
+
+		Ok(())
+	}
+}
+
+impl OperatorController<OutputPort> for OutputPortController {
+	async fn create_resources(&self) -> Result<(), OperatorError> {
+		self.reconcile_port().await
+	}
+
+	async fn delete_resources(&self) -> Result<(), OperatorError> {
+		let service = self.spec.service_name(&self.name);
+
+		let ingresses: Api<Ingress> = Api::namespaced(self.client.clone(), &self.namespace);
+		self.plan.delete(&ingresses, &service).await?;
+		let policies: Api<NetworkPolicy> = Api::namespaced(self.client.clone(), &self.namespace);
+		self.plan.delete(&policies, &service).await?;
+		let services: Api<Service> = Api::namespaced(self.client.clone(), &self.namespace);
+		self.plan.delete(&services, &service).await?;
+
+		Ok(())
+	}
+
+	async fn update_resources(&self) -> Result<(), OperatorError> {
+		self.reconcile_port().await
+	}
+
+	fn dry_run(mut self, dry_run: bool) -> Self {
+		self.plan = Plan::new(dry_run);
+		self
+	}
+
+	fn plan(&self) -> Plan {
+		self.plan.clone()
+	}
+
+	fn finalizer(&self) -> &str {
+		Self::FINALIZER
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		crd::ProductConsumer,
+		port::crd::{PortIngress, PortProtocol},
+	};
+	use dapp_platform::{
+		core::deployment::DeploymentBuilder,
+		surrealdb::{
+			app::SurrealDBApp,
+			config::SurrealDBConfig,
+			export::{ExportDestination, SurrealDBExport},
+		},
+	};
+	use k8s_openapi::api::core::v1::PodTemplateSpec;
+	use std::collections::BTreeMap;
+
+	fn selects(selector: &BTreeMap<String, String>, labels: &BTreeMap<String, String>) -> bool {
+		selector.iter().all(|(key, value)| labels.get(key) == Some(value))
+	}
+
+	fn spec() -> OutputPortSpec {
+		OutputPortSpec {
+			product: "orders".to_string(),
+			protocol: PortProtocol::GraphQL,
+			port: 4000,
+			contract: None,
+			consumers: vec![],
+			ingress: None,
+		}
+	}
+
+	#[test]
+	fn service_routes_to_the_product_workloads() {
+		let service = OutputPortController::service("api", "shop", &spec());
+		let service_spec = service.spec.unwrap();
+
+		assert_eq!(service.metadata.name.as_deref(), Some("orders-api"));
+		assert_eq!(service_spec.selector.unwrap()[SERVING_LABEL], "orders");
+		assert_eq!(service_spec.ports.unwrap()[0].port, 4000);
+		assert_eq!(spec().url("api", "shop"), "http://orders-api.shop.svc:4000");
+	}
+
+	#[test]
+	fn service_selects_serving_pods_but_not_export_jobs() {
+		let selector = OutputPortController::service("api", "shop", &spec()).spec.unwrap().selector;
+		let mut serving = DeploymentBuilder::default()
+			.metadata("orders-api", "orders", "shop")
+			.spec(2, vec![])
+			.manifest();
+		let template = &mut serving.spec.as_mut().unwrap().template;
+		template.metadata.as_mut().unwrap().labels.as_mut().unwrap().extend([
+			(SERVING_LABEL.to_string(), "orders".to_string()),
+			(PRODUCT_LABEL.to_string(), "orders".to_string()),
+		]);
+		let export = SurrealDBExport::new(
+			SurrealDBConfig::new(SurrealDBApp::db_name("orders"), "shop".to_string()),
+			"orders",
+			"daily",
+			ExportDestination::Volume {
+				claim_name: "exports".to_string(),
+				path: "/orders".to_string(),
+			},
+		)
+		.pod_labels(vec![(PRODUCT_LABEL, "orders")])
+		.manifest();
+
+		let pod_labels = |template: Option<&PodTemplateSpec>| {
+			template.and_then(|template| template.metadata.clone()?.labels).unwrap_or_default()
+		};
+		let selector = selector.unwrap();
+
+		assert!(selects(&selector, &pod_labels(serving.spec.as_ref().map(|spec| &spec.template))));
+		assert!(!selects(&selector, &pod_labels(export.spec.as_ref().map(|spec| &spec.template))));
+	}
+
+	#[test]
+	fn port_without_consumers_is_open_to_the_cluster() {
+		let policy = OutputPortController::network_policy("api", "shop", &spec());
+		let peers = policy.spec.unwrap().ingress.unwrap()[0].from.clone().unwrap();
+
+		assert_eq!(peers[0].namespace_selector, Some(LabelSelector::default()));
+		assert_eq!(peers[0].pod_selector, None);
+	}
+
+	#[test]
+	fn declared_consumers_and_the_ingress_controller_are_admitted() {
+		let mut spec = spec();
+		spec.consumers = vec![ProductConsumer {
+			name: "forecast".to_string(),
+			namespace: Some("planning".to_string()),
+		}];
+		spec.ingress = Some(PortIngress {
+			host: "orders.example.com".to_string(),
+			tls_secret_name: Some("orders-tls".to_string()),
+			..PortIngress::default()
+		});
+
+		let policy = OutputPortController::network_policy("api", "shop", &spec);
+		let ingress = OutputPortController::ingress("api", "shop", &spec).unwrap().spec.unwrap();
+		let peers = policy.spec.unwrap().ingress.unwrap()[0].from.clone().unwrap();
+
+		assert_eq!(peers.len(), 2);
+		assert_eq!(
+			peers[0].pod_selector.clone().unwrap().match_labels.unwrap()[PRODUCT_LABEL],
+			"forecast"
+		);
+		assert_eq!(peers[1], namespace_peer(DEFAULT_INGRESS_CONTROLLER_NAMESPACE));
+		assert_eq!(ingress.rules.unwrap()[0].host.as_deref(), Some("orders.example.com"));
+		assert_eq!(ingress.tls.unwrap()[0].secret_name.as_deref(), Some("orders-tls"));
+	}
+}
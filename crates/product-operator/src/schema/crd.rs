@@ -0,0 +1,104 @@
+use kube::CustomResource;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use dapp_platform::{
+	core::{operator::OperatorResource, status::Condition, validation::DNS_SUBDOMAIN},
+	schema::{compatibility::CompatibilityMode, SchemaFormat},
+};
+
+#[derive(CustomResource, Serialize, Deserialize, Debug, PartialEq, Eq, Clone, JsonSchema)]
+#[kube(
+	doc = "DappMesh versioned schema of the data at rest of a product.",
+	group = "dappmesh.io",
+	version = "v1alpha1",
+	kind = "DataSchema",
+	namespaced,
+	singular = "dataschema",
+	plural = "dataschemas",
+	shortname = "dschema",
+	status = "DataSchemaStatus",
+	derive = "PartialEq"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct DataSchemaSpec {
+	/// Name of the DappProduct in the same namespace whose storage the schema describes.
+	#[schemars(regex = "DNS_SUBDOMAIN", length(max = 253))]
+	pub product: String,
+	pub format: SchemaFormat,
+	/// Checked on every step from the applied version to the one being activated.
+	#[serde(default)]
+	pub compatibility: CompatibilityMode,
+	/// All versions of the schema, oldest first.
+	#[schemars(length(min = 1))]
+	pub versions: Vec<SchemaVersion>,
+	/// The version applied to the product's storage.
+	pub active_version: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, JsonSchema)]
+pub struct SchemaVersion {
+	pub version: String,
+	pub definition: String,
+}
+
+/// A version of a DataSchema in the same namespace.
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+pub struct SchemaReference {
+	pub name: String,
+	/// Defaults to the active version of the schema.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub version: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DataSchemaStatus {
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub active_version: Option<String>,
+	/// Hash of the definition of the active version when it was applied.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub active_definition_hash: Option<String>,
+	/// Every application of a version, oldest first.
+	#[serde(default)]
+	pub applied_versions: Vec<AppliedSchemaVersion>,
+	#[serde(default)]
+	pub conditions: Vec<Condition>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AppliedSchemaVersion {
+	pub version: String,
+	pub applied_at: String,
+}
+
+impl SchemaVersion {
+	/// Hex encoded SHA-256 of the definition, telling whether it changed after it was applied.
+	pub fn hash(&self) -> String {
+		hex::encode(Sha256::digest(&self.definition))
+	}
+}
+
+impl DataSchemaSpec {
+	pub fn version(&self, version: &str) -> Option<&SchemaVersion> {
+		self.versions.iter().find(|candidate| candidate.version == version)
+	}
+
+	/// The versions from `from` to `to`, both included, in the order they are passed through.
+	/// The order is reversed when `to` is listed before `from`.
+	pub fn path(&self, from: &str, to: &str) -> Option<Vec<&SchemaVersion>> {
+		let position =
+			|version: &str| self.versions.iter().position(|candidate| candidate.version == version);
+		let (from, to) = (position(from)?, position(to)?);
+
+		Some(if from <= to {
+			self.versions[from..=to].iter().collect()
+		} else {
+			self.versions[to..=from].iter().rev().collect()
+		})
+	}
+}
+
+impl OperatorResource for DataSchema {}
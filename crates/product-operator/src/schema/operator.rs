@@ -0,0 +1,414 @@
+use kube::{Api, Client};
+use tracing::info;
+
+use dapp_platform::{
+	core::{
+		operator::{OperatorController, OperatorError},
+		plan::Plan,
+		status::{find_condition, now, patch_status, set_condition, Condition, READY},
+	},
+	schema::{compatibility, surrealql::define_statements, Schema},
+	surrealdb::client::SurrealDBClient,
+};
+
+use super::crd::{
+	AppliedSchemaVersion, DataSchema, DataSchemaSpec, DataSchemaStatus, SchemaVersion,
+};
+use crate::{crd::DappProduct, operator::ProductOperatorController};
+
+/// A schema version that still has to be applied to the product's storage.
+#[derive(Debug, PartialEq)]
+pub struct PendingVersion {
+	pub version: String,
+	pub hash: String,
+	pub statements: String,
+}
+
+pub struct DataSchemaController {
+	name: String,
+	namespace: String,
+	spec: DataSchemaSpec,
+	status: DataSchemaStatus,
+	client: Client,
+	plan: Plan,
+}
+
+impl DataSchemaController {
+	pub const FINALIZER: &'static str = "dataschemas.dappmesh.io/finalizer";
+
+	pub fn new(name: String, namespace: String, resource: &DataSchema, client: Client) -> Self {
+		Self {
+			name,
+			namespace,
+			spec: resource.spec.clone(),
+			status: resource.status.clone().unwrap_or_default(),
+			client,
+			plan: Plan::default(),
+		}
+	}
+
+	/// The active version, unless the status records it as applied already. The statements
+	/// are rendered from the parsed schema whatever the format, so nothing else reaches the
+	/// database. A definition changed after it was applied is rejected, changes need a new
+	/// version.
+	pub fn pending_version(
+		spec: &DataSchemaSpec,
+		status: &DataSchemaStatus,
+	) -> Result<Option<PendingVersion>, OperatorError> {
+		let version = spec.version(&spec.active_version).ok_or_else(|| {
+			OperatorError::UserInputError(format!(
+				"Active version {} is not one of the schema versions.",
+				spec.active_version
+			))
+		})?;
+		let hash = version.hash();
+
+		if status.active_version.as_ref() == Some(&spec.active_version) {
+			match &status.active_definition_hash {
+				Some(applied) if *applied == hash => return Ok(None),
+				Some(_) => {
+					return Err(OperatorError::UserInputError(format!(
+						"Version {} changed after it was applied, add the change as a new version.",
+						version.version
+					)))
+				}
+				None => {}
+			}
+		}
+
+		let schema = Self::parse(spec, &version.definition)?;
+
+		if let Some(applied) = &status.active_version {
+			Self::check_compatibility(spec, applied, &version.version)?;
+		}
+
+		Ok(Some(PendingVersion {
+			version: version.version.clone(),
+			hash,
+			statements: define_statements(&schema).join("\n"),
+		}))
+	}
+
+	/// Checks every step between consecutive versions from the applied version to `target`,
+	/// and the applied version against `target` directly.
+	fn check_compatibility(
+		spec: &DataSchemaSpec,
+		applied: &str,
+		target: &str,
+	) -> Result<(), OperatorError> {
+		let path = spec.path(applied, target).ok_or_else(|| {
+			OperatorError::UserInputError(format!(
+				"Applied version {} is no longer one of the schema versions.",
+				applied
+			))
+		})?;
+
+		let mut steps: Vec<(&SchemaVersion, &SchemaVersion)> =
+			path.windows(2).map(|step| (step[0], step[1])).collect();
+		if path.len() > 2 {
+			steps.push((path[0], path[path.len() - 1]));
+		}
+
+		for (previous, next) in steps {
+			let report = compatibility::check(
+				&Self::parse(spec, &previous.definition)?,
+				&Self::parse(spec, &next.definition)?,
+				spec.compatibility,
+			);
+			if !report.is_compatible() {
+				let breaking: Vec<String> =
+					report.breaking().iter().map(|change| change.to_string()).collect();
+				return Err(OperatorError::UserInputError(format!(
+					"Version {} is not {:?} compatible with version {}: {}.",
+					next.version,
+					spec.compatibility,
+					previous.version,
+					breaking.join(", ")
+				)));
+			}
+		}
+
+		Ok(())
+	}
+
+	fn parse(spec: &DataSchemaSpec, definition: &str) -> Result<Schema, OperatorError> {
+		Schema::parse(spec.format, definition)
+			.map_err(|error| OperatorError::UserInputError(error.to_string()))
+	}
+
+	async fn apply_pending_version(&self) -> Result<Option<PendingVersion>, OperatorError> {
+		let products: Api<DappProduct> = Api::namespaced(self.client.clone(), &self.namespace);
+		let Some(product) = products.get_opt(&self.spec.product).await? else {
+			return Err(OperatorError::UserInputError(format!(
+				"DappProduct {} does not exist.",
+				self.spec.product
+			)));
+		};
+
+		let Some(pending) = Self::pending_version(&self.spec, &self.status)? else {
+			return Ok(None);
+		};
+
+		if self.plan.is_dry_run() {
+			info!("Would apply version {} of schema {}", pending.version, self.name);
+			return Ok(Some(pending));
+		}
+
+		info!("Applying version {} of schema {}", pending.version, self.name);
+		let config = ProductOperatorController::database_config(
+			&self.spec.product,
+			&self.namespace,
+			&product.spec,
+		);
+		let database = SurrealDBClient::from_config(&config, &self.spec.product);
+		database.query(&pending.statements).await?;
+
+		Ok(Some(pending))
+	}
+
+	/// Applies the active version and records the outcome in the status.
+	async fn reconcile_schema(&self) -> Result<(), OperatorError> {
+		let result = self.apply_pending_version().await;
+		let mut status = self.status.clone();
+
+		match &result {
+			Ok(Some(pending)) => {
+				status.active_version = Some(pending.version.clone());
+				status.active_definition_hash = Some(pending.hash.clone());
+				status.applied_versions.push(AppliedSchemaVersion {
+					version: pending.version.clone(),
+					applied_at: now(),
+				});
+				let message = format!("Version {} is applied.", pending.version);
+				set_condition(
+					&mut status.conditions,
+					Condition::new(READY, true, "Applied", &message),
+				);
+			}
+			Ok(None) => {
+				if find_condition(&status.conditions, READY).is_some_and(Condition::is_true) {
+					return Ok(());
+				}
+				let message = "The active version is applied.";
+				set_condition(
+					&mut status.conditions,
+					Condition::new(READY, true, "Applied", message),
+				);
+			}
+			Err(error) => {
+				let condition = Condition::new(READY, false, "ApplyFailed", &error.to_string());
+				set_condition(&mut status.conditions, condition);
+			}
+		}
+
+		if !self.plan.is_dry_run() {
+			let api: Api<DataSchema> = Api::namespaced(self.client.clone(), &self.namespace);
+			patch_status(&api, &self.name, &status).await?;
+		}
+
+		result.map(|_| ())
+	}
+}
+
+impl OperatorController<DataSchema> for DataSchemaController {
+	async fn create_resources(&self) -> Result<(), OperatorError> {
+		self.reconcile_schema().await
+	}
+
+	/// Tables are left in place, they are removed together with the product's storage.
+	async fn delete_resources(&self) -> Result<(), OperatorError> {
+		Ok(())
+	}
+
+	async fn update_resources(&self) -> Result<(), OperatorError> {
+		self.reconcile_schema().await
+	}
+
+	fn dry_run(mut self, dry_run: bool) -> Self {
+		self.plan = Plan::new(dry_run);
+		self
+	}
+
+	fn plan(&self) -> Plan {
+		self.plan.clone()
+	}
+
+	fn finalizer(&self) -> &str {
+		Self::FINALIZER
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use dapp_platform::schema::{compatibility::CompatibilityMode, SchemaFormat};
+
+	fn spec(format: SchemaFormat, definition: &str) -> DataSchemaSpec {
+		DataSchemaSpec {
+			product: "orders".to_string(),
+			format,
+			compatibility: CompatibilityMode::Backward,
+			versions: vec![SchemaVersion {
+				version: "v1".to_string(),
+				definition: definition.to_string(),
+			}],
+			active_version: "v1".to_string(),
+		}
+	}
+
+	#[test]
+	fn pending_version_renders_define_statements() {
+		let spec = spec(
+			SchemaFormat::JsonSchema,
+			r#"{ "title": "order", "required": ["id"], "properties": { "id": { "type": "integer" } } }"#,
+		);
+
+		let pending =
+			DataSchemaController::pending_version(&spec, &DataSchemaStatus::default()).unwrap();
+
+		assert_eq!(
+			pending,
+			Some(PendingVersion {
+				version: "v1".to_string(),
+				hash: spec.versions[0].hash(),
+				statements:
+					"DEFINE TABLE order SCHEMAFULL;\nDEFINE FIELD id ON TABLE order TYPE int;"
+						.to_string(),
+			})
+		);
+	}
+
+	#[test]
+	fn pending_version_renders_surrealql_definitions() {
+		let definition =
+			"DEFINE TABLE order; DEFINE INDEX id ON order COLUMNS id PERMISSIONS NONE;";
+		let spec = spec(SchemaFormat::SurrealQL, definition);
+
+		let pending =
+			DataSchemaController::pending_version(&spec, &DataSchemaStatus::default()).unwrap();
+
+		assert_eq!(
+			pending.unwrap().statements,
+			"DEFINE TABLE order SCHEMAFULL;\nDEFINE INDEX id ON TABLE order COLUMNS id;"
+		);
+	}
+
+	#[test]
+	fn injected_statements_are_rejected() {
+		let surrealql = spec(SchemaFormat::SurrealQL, "DEFINE TABLE x; REMOVE DATABASE y;");
+		let json_schema = spec(
+			SchemaFormat::JsonSchema,
+			r#"{ "title": "x; REMOVE DATABASE y", "type": "object" }"#,
+		);
+
+		for spec in [surrealql, json_schema] {
+			let result = DataSchemaController::pending_version(&spec, &DataSchemaStatus::default());
+
+			assert!(matches!(result, Err(OperatorError::UserInputError(_))));
+		}
+	}
+
+	#[test]
+	fn applied_active_version_is_not_pending() {
+		let spec = spec(SchemaFormat::SurrealQL, "DEFINE TABLE order;");
+		let status = DataSchemaStatus {
+			active_version: Some("v1".to_string()),
+			active_definition_hash: Some(spec.versions[0].hash()),
+			..DataSchemaStatus::default()
+		};
+
+		assert_eq!(DataSchemaController::pending_version(&spec, &status).unwrap(), None);
+	}
+
+	#[test]
+	fn edited_active_version_is_rejected() {
+		let mut spec = spec(SchemaFormat::SurrealQL, "DEFINE TABLE order;");
+		let status = DataSchemaStatus {
+			active_version: Some("v1".to_string()),
+			active_definition_hash: Some(spec.versions[0].hash()),
+			..DataSchemaStatus::default()
+		};
+		spec.versions[0].definition = "DEFINE TABLE order; DEFINE FIELD id ON order;".to_string();
+
+		let result = DataSchemaController::pending_version(&spec, &status);
+
+		assert!(matches!(
+			result,
+			Err(OperatorError::UserInputError(message)) if message.contains("changed after it was applied")
+		));
+	}
+
+	fn applied(version: &str) -> DataSchemaStatus {
+		DataSchemaStatus {
+			active_version: Some(version.to_string()),
+			..DataSchemaStatus::default()
+		}
+	}
+
+	fn add_version(spec: &mut DataSchemaSpec, version: &str, definition: &str) {
+		spec.versions.push(SchemaVersion {
+			version: version.to_string(),
+			definition: definition.to_string(),
+		});
+		spec.active_version = version.to_string();
+	}
+
+	#[test]
+	fn version_violating_the_compatibility_mode_is_rejected() {
+		let mut spec = spec(SchemaFormat::SurrealQL, "DEFINE TABLE order;");
+		add_version(&mut spec, "v2", "DEFINE TABLE order; DEFINE FIELD id ON order TYPE int;");
+
+		let result = DataSchemaController::pending_version(&spec, &applied("v1"));
+		spec.compatibility = CompatibilityMode::Forward;
+		let forward = DataSchemaController::pending_version(&spec, &applied("v1"));
+
+		assert!(matches!(
+			result,
+			Err(OperatorError::UserInputError(message)) if message.contains("order.id: required field added")
+		));
+		assert!(forward.unwrap().is_some());
+	}
+
+	#[test]
+	fn skipped_versions_are_checked_from_the_applied_version() {
+		let mut spec = spec(SchemaFormat::SurrealQL, "DEFINE TABLE order;");
+		add_version(&mut spec, "v2", "DEFINE TABLE order; DEFINE FIELD id ON order TYPE int;");
+		add_version(
+			&mut spec,
+			"v3",
+			"DEFINE TABLE order; DEFINE FIELD id ON order TYPE int; \
+			 DEFINE FIELD note ON order TYPE option<string>;",
+		);
+
+		let from_v1 = DataSchemaController::pending_version(&spec, &applied("v1"));
+		let from_v2 = DataSchemaController::pending_version(&spec, &applied("v2"));
+
+		assert!(matches!(
+			from_v1,
+			Err(OperatorError::UserInputError(message))
+				if message.contains("Version v2") && message.contains("version v1")
+		));
+		assert!(from_v2.unwrap().is_some());
+	}
+
+	#[test]
+	fn first_version_applied_is_not_checked() {
+		let mut spec = spec(SchemaFormat::SurrealQL, "DEFINE TABLE order;");
+		add_version(&mut spec, "v2", "DEFINE TABLE order; DEFINE FIELD id ON order TYPE int;");
+
+		let pending =
+			DataSchemaController::pending_version(&spec, &DataSchemaStatus::default()).unwrap();
+
+		assert_eq!(pending.unwrap().version, "v2");
+	}
+
+	#[test]
+	fn unknown_active_version_is_rejected() {
+		let mut spec = spec(SchemaFormat::SurrealQL, "DEFINE TABLE order;");
+		spec.active_version = "v2".to_string();
+
+		let result = DataSchemaController::pending_version(&spec, &DataSchemaStatus::default());
+
+		assert!(matches!(result, Err(OperatorError::UserInputError(_))));
+	}
+}
@@ -0,0 +1,123 @@
+use kube::CustomResource;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use dapp_platform::{
+	core::{
+		operator::OperatorResource,
+		status::Condition,
+		validation::{schema, Rule, Validated, DNS_SUBDOMAIN},
+	},
+	surrealdb::export::ExportDestination,
+};
+
+use crate::schema::crd::SchemaReference;
+
+pub const FLOW_LABEL: &str = "dappmesh.io/flow";
+
+/// Created by the product operator when a step of a DataFlow completes.
+#[derive(CustomResource, Serialize, Deserialize, Debug, PartialEq, Eq, Clone, JsonSchema)]
+#[kube(
+	doc = "DappMesh snapshot of the data of a product after a step of a data flow.",
+	group = "dappmesh.io",
+	version = "v1alpha1",
+	kind = "DataSnapshot",
+	namespaced,
+	singular = "datasnapshot",
+	plural = "datasnapshots",
+	shortname = "dsnap",
+	status = "DataSnapshotStatus",
+	derive = "PartialEq"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct DataSnapshotSpec {
+	/// Name of the DappProduct in the same namespace whose tables are exported.
+	#[schemars(regex = "DNS_SUBDOMAIN", length(max = 253))]
+	pub product: String,
+	/// Name of the DataFlow that produced the data.
+	pub flow: String,
+	pub step: String,
+	/// Position of the step in the topological order of the flow.
+	pub step_index: i32,
+	/// The schema version of the data the step produced.
+	pub schema: SchemaReference,
+	/// SHA-256 of the flow spec at the time the step completed.
+	pub flow_hash: String,
+	#[schemars(schema_with = "schema::<SnapshotDestination>")]
+	pub destination: SnapshotDestination,
+}
+
+/// Exactly one of `volume` or `bucket`.
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotDestination {
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub volume: Option<VolumeDestination>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub bucket: Option<BucketDestination>,
+}
+
+impl Validated for SnapshotDestination {
+	const RULES: &'static [Rule] = &[Rule::new(
+		"has(self.volume) != has(self.bucket)",
+		"exactly one of volume or bucket has to be set",
+	)];
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct VolumeDestination {
+	/// A PersistentVolumeClaim in the same namespace.
+	pub claim_name: String,
+	/// Directory within the volume, defaults to its root.
+	#[serde(default)]
+	pub path: String,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BucketDestination {
+	/// An S3 compatible URL, e.g. `s3://snapshots/orders`.
+	pub url: String,
+	/// A Secret in the same namespace with the `AWS_*` variables used for the upload.
+	pub secret_name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, JsonSchema)]
+pub enum SnapshotPhase {
+	Exporting,
+	Completed,
+	Failed,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DataSnapshotStatus {
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub phase: Option<SnapshotPhase>,
+	/// Where the export is written to.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub location: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub completed_at: Option<String>,
+	#[serde(default)]
+	pub conditions: Vec<Condition>,
+}
+
+impl SnapshotDestination {
+	pub fn export_destination(&self) -> Result<ExportDestination, String> {
+		match (&self.volume, &self.bucket) {
+			(Some(volume), None) => Ok(ExportDestination::Volume {
+				claim_name: volume.claim_name.clone(),
+				path: volume.path.clone(),
+			}),
+			(None, Some(bucket)) => Ok(ExportDestination::Bucket {
+				url: bucket.url.clone(),
+				secret_name: bucket.secret_name.clone(),
+			}),
+			_ => Err("destination needs exactly one of volume or bucket".to_string()),
+		}
+	}
+}
+
+impl OperatorResource for DataSnapshot {}
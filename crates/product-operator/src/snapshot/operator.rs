@@ -0,0 +1,234 @@
+use k8s_openapi::api::batch::v1::Job;
+use kube::{Api, Client};
+use tracing::info;
+
+use dapp_platform::{
+	core::{
+		labels::PRODUCT_LABEL,
+		operator::{OperatorController, OperatorError},
+		plan::Plan,
+		status::{now, patch_status, set_condition, Condition, READY},
+	},
+	surrealdb::export::{ExportDestination, SurrealDBExport},
+};
+
+use super::crd::{DataSnapshot, DataSnapshotStatus, SnapshotPhase};
+use crate::{crd::DappProduct, operator::ProductOperatorController};
+
+pub struct DataSnapshotController {
+	name: String,
+	namespace: String,
+	product: String,
+	status: DataSnapshotStatus,
+	destination: Result<ExportDestination, String>,
+	client: Client,
+	plan: Plan,
+}
+
+impl DataSnapshotController {
+	pub const FINALIZER: &'static str = "datasnapshots.dappmesh.io/finalizer";
+
+	pub fn new(name: String, namespace: String, resource: &DataSnapshot, client: Client) -> Self {
+		Self {
+			name,
+			namespace,
+			product: resource.spec.product.clone(),
+			status: resource.status.clone().unwrap_or_default(),
+			destination: resource.spec.destination.export_destination(),
+			client,
+			plan: Plan::default(),
+		}
+	}
+
+	/// The phase of the export run by `job`.
+	pub fn phase(job: &Job) -> SnapshotPhase {
+		let conditions = job.status.iter().flat_map(|status| status.conditions.iter().flatten());
+
+		for condition in conditions.filter(|condition| condition.status == "True") {
+			match condition.type_.as_str() {
+				"Complete" => return SnapshotPhase::Completed,
+				"Failed" => return SnapshotPhase::Failed,
+				_ => {}
+			}
+		}
+
+		SnapshotPhase::Exporting
+	}
+
+	/// Records `phase` in `status`, a snapshot is ready once its export completed.
+	pub fn snapshot_status(
+		status: &DataSnapshotStatus,
+		phase: SnapshotPhase,
+		location: Option<String>,
+		message: &str,
+	) -> DataSnapshotStatus {
+		let mut status = status.clone();
+		let condition = match phase {
+			SnapshotPhase::Exporting => Condition::new(READY, false, "Exporting", message),
+			SnapshotPhase::Completed => Condition::new(READY, true, "Completed", message),
+			SnapshotPhase::Failed => Condition::new(READY, false, "Failed", message),
+		};
+
+		if phase == SnapshotPhase::Completed && status.completed_at.is_none() {
+			status.completed_at = Some(now());
+		}
+		status.phase = Some(phase);
+		status.location = location.or(status.location);
+		set_condition(&mut status.conditions, condition);
+
+		status
+	}
+
+	/// The export of the product's storage, reached the way its DappProduct configures it.
+	async fn product_export(
+		&self,
+		destination: &ExportDestination,
+	) -> Result<SurrealDBExport, OperatorError> {
+		let products: Api<DappProduct> = Api::namespaced(self.client.clone(), &self.namespace);
+		let Some(product) = products.get_opt(&self.product).await? else {
+			return Err(OperatorError::UserInputError(format!(
+				"DappProduct {} does not exist.",
+				self.product
+			)));
+		};
+
+		let config = ProductOperatorController::database_config(
+			&self.product,
+			&self.namespace,
+			&product.spec,
+		);
+		Ok(SurrealDBExport::new(config, &self.product, &self.name, destination.clone())
+			.pod_labels(vec![(PRODUCT_LABEL, &self.product)]))
+	}
+
+	async fn export(&self, export: &SurrealDBExport) -> Result<DataSnapshotStatus, OperatorError> {
+		let jobs: Api<Job> = Api::namespaced(self.client.clone(), &self.namespace);
+		let location = Some(export.location());
+
+		let phase = match jobs.get_opt(&export.name).await? {
+			Some(job) => Self::phase(&job),
+			None => {
+				info!("Exporting snapshot {} to {}", self.name, export.location());
+				self.plan.create(&jobs, &export.manifest()).await?;
+				SnapshotPhase::Exporting
+			}
+		};
+		let message = match phase {
+			SnapshotPhase::Exporting => format!("Exporting to {}.", export.location()),
+			SnapshotPhase::Completed => format!("Exported to {}.", export.location()),
+			SnapshotPhase::Failed => format!("Job {} failed.", export.name),
+		};
+
+		Ok(Self::snapshot_status(&self.status, phase, location, &message))
+	}
+
+	async fn reconcile_snapshot(&self) -> Result<(), OperatorError> {
+		// Snapshots are immutable once their export finished.
+		if matches!(self.status.phase, Some(SnapshotPhase::Completed | SnapshotPhase::Failed)) {
+			return Ok(());
+		}
+
+		let status = match &self.destination {
+			Ok(destination) => self.export(&self.product_export(destination).await?).await?,
+			Err(error) => Self::snapshot_status(&self.status, SnapshotPhase::Failed, None, error),
+		};
+
+		if status != self.status && !self.plan.is_dry_run() {
+			let api: Api<DataSnapshot> = Api::namespaced(self.client.clone(), &self.namespace);
+			patch_status(&api, &self.name, &status).await?;
+		}
+
+		Ok(())
+	}
+}
+
+impl OperatorController<DataSnapshot> for DataSnapshotController {
+	async fn create_resources(&self) -> Result<(), OperatorError> {
+		self.reconcile_snapshot().await
+	}
+
+	/// Removes the export Job, the exported data is kept for audits.
+	async fn delete_resources(&self) -> Result<(), OperatorError> {
+		let jobs: Api<Job> = Api::namespaced(self.client.clone(), &self.namespace);
+		self.plan.delete(&jobs, &self.name).await?;
+
+		Ok(())
+	}
+
+	async fn update_resources(&self) -> Result<(), OperatorError> {
+		self.reconcile_snapshot().await
+	}
+
+	fn dry_run(mut self, dry_run: bool) -> Self {
+		self.plan = Plan::new(dry_run);
+		self
+	}
+
+	fn plan(&self) -> Plan {
+		self.plan.clone()
+	}
+
+	fn finalizer(&self) -> &str {
+		Self::FINALIZER
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use k8s_openapi::api::batch::v1::{JobCondition, JobStatus};
+
+	fn job(conditions: Vec<(&str, &str)>) -> Job {
+		let conditions = conditions
+			.into_iter()
+			.map(|(type_, status)| JobCondition {
+				type_: type_.to_string(),
+				status: status.to_string(),
+				..JobCondition::default()
+			})
+			.collect();
+
+		Job {
+			status: Some(JobStatus {
+				conditions: Some(conditions),
+				..JobStatus::default()
+			}),
+			..Job::default()
+		}
+	}
+
+	#[test]
+	fn phase_follows_the_job_conditions() {
+		assert_eq!(DataSnapshotController::phase(&Job::default()), SnapshotPhase::Exporting);
+		assert_eq!(
+			DataSnapshotController::phase(&job(vec![("Complete", "True")])),
+			SnapshotPhase::Completed
+		);
+		assert_eq!(
+			DataSnapshotController::phase(&job(vec![("Complete", "False"), ("Failed", "True")])),
+			SnapshotPhase::Failed
+		);
+	}
+
+	#[test]
+	fn completed_snapshot_is_ready_and_keeps_its_location() {
+		let exporting = DataSnapshotController::snapshot_status(
+			&DataSnapshotStatus::default(),
+			SnapshotPhase::Exporting,
+			Some("s3://snapshots/orders.surql".to_string()),
+			"Exporting.",
+		);
+
+		let completed = DataSnapshotController::snapshot_status(
+			&exporting,
+			SnapshotPhase::Completed,
+			None,
+			"Exported.",
+		);
+
+		assert!(exporting.completed_at.is_none());
+		assert!(completed.completed_at.is_some());
+		assert!(completed.conditions[0].is_true());
+		assert_eq!(completed.location.as_deref(), Some("s3://snapshots/orders.surql"));
+	}
+}
@@ -25,16 +25,19 @@ mod tests {
 		let product_api: Api<DappProduct> = Api::namespaced(client.clone(), PRODUCT_NAMESPACE);
 		let product_resource = DappProduct::new(PRODUCT_NAME, DappProductSpec::default());
 
-		let controller = ProductOperatorController::new(
-			PRODUCT_NAME.to_string(),
-			PRODUCT_NAMESPACE.to_string(),
-			client.clone(),
-		);
+		let controller = || {
+			ProductOperatorController::new(
+				PRODUCT_NAME.to_string(),
+				PRODUCT_NAMESPACE.to_string(),
+				&product_resource.spec,
+				client.clone(),
+			)
+		};
 
 		let context: Arc<OperatorContext> = Arc::new(OperatorContext::new(client.clone()));
 		let product = product_api.create(&PostParams::default(), &product_resource).await?;
 
-		let action = controller.reconcile(Arc::new(product), context.clone()).await?;
+		let action = controller().reconcile(Arc::new(product), context.clone()).await?;
 		ensure!(action == Action::requeue(Duration::from_secs(5)));
 
 		let finalizers = product_api.get(PRODUCT_NAME).await?.metadata.finalizers;
@@ -42,7 +45,7 @@ mod tests {
 
 		let product = product_api.delete(PRODUCT_NAME, &DeleteParams::default()).await?;
 
-		let action = controller.reconcile(Arc::new(product.left_or_default()), context).await?;
+		let action = controller().reconcile(Arc::new(product.left_or_default()), context).await?;
 		ensure!(action == Action::await_change());
 
 		Ok(())